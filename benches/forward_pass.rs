@@ -0,0 +1,93 @@
+//! Compares the forward pass of `ChromosomeEvaluator` (a new map of node values per chromosome)
+//! with the ones of `ArenaChromosomeEvaluator` (reused buffers) and `CompiledChromosomeEvaluator`
+//! (a compiled program) for several graph widths.
+//! Run with `cargo bench --bench forward_pass`.
+#![allow(clippy::needless_return)]
+
+use std::sync::Arc;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use cgp_master::components::cgp_components::cgp_types::CGPType;
+use cgp_master::components::cgp_components::chromosome::Chromosome;
+use cgp_master::components::cgp_components::chromosome_evaluator_operators::{ArenaChromosomeEvaluator, ChromosomeEvaluator, CompiledChromosomeEvaluator, DatasetId, EvaluateChromosomeTrait};
+use cgp_master::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesStandard};
+use cgp_master::components::evo_operators_for_population::crossover_operators::crossover_types::CrossoverType;
+use cgp_master::datasets::{boolean_datasets, regression_benchmarks};
+use cgp_master::function_set::boolean_function_set::get_boolean_function_set;
+use cgp_master::function_set::function_trait::FunctionTrait;
+use cgp_master::function_set::regression_function_set::get_regression_function_set;
+use cgp_master::global_params::CgpParameters;
+use cgp_master::utils::utility_funcs::transpose;
+
+const GRAPH_WIDTHS: [usize; 4] = [100, 500, 1000, 2000];
+const NBR_CHROMOSOMES: usize = 20;
+
+fn chromosomes(graph_width: usize, nbr_inputs: usize, nbr_outputs: usize, number_functions: usize) -> Vec<Chromosome> {
+    let params = CgpParameters {
+        cgp_type: CGPType::Standard,
+        graph_width,
+        elitists: 1,
+        population_size: 4,
+        eval_after_iterations: 0,
+        nbr_inputs,
+        nbr_outputs,
+        mutation_rate: 0.,
+        crossover_type: CrossoverType::NoCrossover,
+        crossover_rate: 0.,
+        multi_point_n: 0,
+        tournament_size: 0,
+        number_functions,
+        fitness_threshold: 0.,
+        multi_n_number_mutations: 0,
+        split_mutation_rate_active: 0.,
+        split_mutation_rate_inactive: 0.,
+        constants: None,
+    };
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    return (0..NBR_CHROMOSOMES).map(|_| Chromosome::new(params.clone(), &mut rng)).collect();
+}
+
+fn bench_evaluators<T: Clone + 'static>(c: &mut Criterion,
+                                        group_name: &str,
+                                        (data, label): (Vec<Vec<T>>, Vec<Vec<T>>),
+                                        nbr_outputs: usize,
+                                        function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>)
+where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      ArenaChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      CompiledChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
+    let nbr_inputs = data[0].len();
+    let data = transpose(data);
+    let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+    let evaluators: [(&str, Box<dyn EvaluateChromosomeTrait<T>>); 3] = [
+        ("map", ChromosomeEvaluator::new()),
+        ("arena", ArenaChromosomeEvaluator::new()),
+        ("compiled", CompiledChromosomeEvaluator::new()),
+    ];
+
+    let mut group = c.benchmark_group(group_name);
+    for graph_width in GRAPH_WIDTHS {
+        let mut population = chromosomes(graph_width, nbr_inputs, nbr_outputs, function_set.len());
+        for (name, evaluator) in &evaluators {
+            group.bench_function(BenchmarkId::new(*name, graph_width), |b| b.iter(|| {
+                for chromosome in population.iter_mut() {
+                    evaluator.evaluate(chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set));
+                }
+            }));
+        }
+    }
+    group.finish();
+}
+
+fn forward_pass(c: &mut Criterion) {
+    // Boolean labels hold one vector per row, regression labels one vector per output
+    let (data, label) = boolean_datasets::multiply::get_dataset();
+    let nbr_outputs = label[0].len();
+    bench_evaluators(c, "forward_pass_multiply", (data, label), nbr_outputs, get_boolean_function_set());
+    let (data, label) = regression_benchmarks::keijzer::get_dataset();
+    let nbr_outputs = label.len();
+    bench_evaluators(c, "forward_pass_keijzer", (data, label), nbr_outputs, get_regression_function_set());
+}
+
+criterion_group!(benches, forward_pass);
+criterion_main!(benches);
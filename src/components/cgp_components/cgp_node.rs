@@ -4,11 +4,14 @@
 //! I.e. How does mutation affect the node.
 
 use rand::Rng;
+use rand_chacha::ChaCha8Rng;
 use crate::components::cgp_components::cgp_node_types::NodeType;
 use crate::utils::cycle_checker::CGPEdges;
+use crate::global_params::ConstantParameters;
+use serde::{Deserialize, Serialize};
 
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CGPNode {
     pub position: usize,
     pub node_type: NodeType,
@@ -18,19 +21,26 @@ pub struct CGPNode {
     pub connection0: usize,
     pub connection1: usize,
     pub number_functions: usize,
+    // output of the node if its function has no inputs; 0 without `constants`
+    pub constant: f64,
+    pub constants: Option<ConstantParameters>,
 }
 
 
 impl CGPNode {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(position: usize,
                nbr_inputs: usize,
                graph_width: usize,
                node_type: NodeType,
                number_functions: usize,
-               cgp_edges: &mut Option<CGPEdges>) -> Self {
+               constants: Option<ConstantParameters>,
+               cgp_edges: &mut Option<CGPEdges>,
+               rng: &mut ChaCha8Rng) -> Self {
         let function_id: usize;
         let connection0: usize;
         let connection1: usize;
+        let mut constant: f64 = 0.;
 
         match node_type {
             NodeType::InputNode => {
@@ -39,9 +49,12 @@ impl CGPNode {
                 connection1 = usize::MAX;
             }
             NodeType::ComputationalNode => {
-                function_id = rand::thread_rng().gen_range(0..number_functions);
-                connection0 = rand::thread_rng().gen_range(0..position);
-                connection1 = rand::thread_rng().gen_range(0..position);
+                function_id = rng.gen_range(0..number_functions);
+                connection0 = rng.gen_range(0..position);
+                connection1 = rng.gen_range(0..position);
+                if let Some(constants) = constants {
+                    constant = rng.gen_range(constants.min..=constants.max);
+                }
 
                 if cgp_edges.is_some() {
                     cgp_edges.as_mut().unwrap().add_edge(position, connection0);
@@ -50,7 +63,7 @@ impl CGPNode {
             }
             NodeType::OutputNode => {
                 function_id = usize::MAX;
-                connection0 = rand::thread_rng().gen_range(0..nbr_inputs + graph_width);
+                connection0 = rng.gen_range(0..nbr_inputs + graph_width);
                 connection1 = usize::MAX;
             }
         }
@@ -64,6 +77,8 @@ impl CGPNode {
             connection0,
             connection1,
             number_functions,
+            constant,
+            constants,
         }
     }
 }
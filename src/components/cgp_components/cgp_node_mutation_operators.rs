@@ -1,19 +1,35 @@
 use rand::distributions::Uniform;
-use rand::{Rng, thread_rng};
+use rand::Rng;
 use rand::distributions::Distribution;
+use rand_chacha::ChaCha8Rng;
+use statrs::distribution::Normal;
 use crate::components::cgp_components::cgp_node::CGPNode;
 use crate::components::cgp_components::cgp_node_types::NodeType;
 use crate::utils::cycle_checker::CGPEdges;
 use crate::utils::utility_funcs::gen_random_number_for_node;
 
 pub trait NodeMutationOperatorTrait {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn NodeMutationOperatorTrait> where Self: Sized;
 
     #[allow(unused_variables)]
-    fn mutate_standard(&self, node: &mut CGPNode) { unimplemented!("Placeholder function; Mutation is configured wrong somewhere!") }
+    fn mutate_standard(&self, node: &mut CGPNode, rng: &mut ChaCha8Rng) { unimplemented!("Placeholder function; Mutation is configured wrong somewhere!") }
 
     #[allow(unused_variables)]
-    fn mutate_dag(&self, node: &mut CGPNode, cgp_edges: &mut CGPEdges) {unimplemented!("Placeholder function; Mutation is configured wrong somewhere!")}
+    fn mutate_dag(&self, node: &mut CGPNode, cgp_edges: &mut CGPEdges, rng: &mut ChaCha8Rng) {unimplemented!("Placeholder function; Mutation is configured wrong somewhere!")}
+}
+
+/// Index of the last gene of a computational node that can be mutated: the connections, the
+/// function and, with `ConstantParameters`, the constant. An `i32`, so nodes without constants
+/// draw the same random numbers as before.
+fn last_gene(node: &CGPNode) -> i32 {
+    if node.constants.is_some() { 3 } else { 2 }
+}
+
+/// Adds a normally distributed value to the constant gene; see `ConstantParameters`.
+fn mutate_constant(node: &mut CGPNode, rng: &mut ChaCha8Rng) {
+    let sigma = node.constants.expect("the node has no constant gene").sigma;
+    node.constant += Normal::new(0., sigma).unwrap().sample(rng);
 }
 
 pub struct NodeMutationStandard;
@@ -26,38 +42,43 @@ impl NodeMutationOperatorTrait for NodeMutationStandard {
         Box::new(Self)
     }
 
-    fn mutate_standard(&self, node: &mut CGPNode) {
+    fn mutate_standard(&self, node: &mut CGPNode, rng: &mut ChaCha8Rng) {
         assert!(node.node_type != NodeType::InputNode);
 
         match node.node_type {
-            NodeType::OutputNode => self.mutate_output_node(node),
-            NodeType::ComputationalNode => self.mutate_computational_node(node),
+            NodeType::OutputNode => self.mutate_output_node(node, rng),
+            NodeType::ComputationalNode => self.mutate_computational_node(node, rng),
             _ => { panic!("Trying to mutate input node") }
         }
     }
 }
 
 impl NodeMutationStandard {
-    fn mutate_output_node(&self, node: &mut CGPNode) {
+    fn mutate_output_node(&self, node: &mut CGPNode, rng: &mut ChaCha8Rng) {
         node.connection0 = gen_random_number_for_node(node.connection0,
-                                                      node.graph_width + node.nbr_inputs);
+                                                      node.graph_width + node.nbr_inputs,
+                                                      rng);
 
         assert!(node.connection0 < node.position);
     }
-    fn mutate_computational_node(&self, node: &mut CGPNode) {
-        let rand_nbr = rand::thread_rng().gen_range(0..=2);
+    fn mutate_computational_node(&self, node: &mut CGPNode, rng: &mut ChaCha8Rng) {
+        let rand_nbr = rng.gen_range(0..=last_gene(node));
         match rand_nbr {
             0 => {
                 node.connection0 = gen_random_number_for_node(node.connection0,
-                                                              node.position);
+                                                              node.position,
+                                                              rng);
             }
 
             1 => {
                 node.connection1 = gen_random_number_for_node(node.connection1,
-                                                              node.position);
+                                                              node.position,
+                                                              rng);
             }
 
-            2 => self.mutate_function(node),
+            2 => self.mutate_function(node, rng),
+
+            3 => mutate_constant(node, rng),
 
             _ => { panic!("Mutation of comp node somehow broken?") }
         };
@@ -65,8 +86,8 @@ impl NodeMutationStandard {
         assert!(node.connection0 < node.position);
         assert!(node.connection1 < node.position);
     }
-    fn mutate_function(&self, node: &mut CGPNode) {
-        node.function_id = gen_random_number_for_node(node.function_id, node.number_functions);
+    fn mutate_function(&self, node: &mut CGPNode, rng: &mut ChaCha8Rng) {
+        node.function_id = gen_random_number_for_node(node.function_id, node.number_functions, rng);
     }
 }
 
@@ -75,19 +96,19 @@ impl NodeMutationOperatorTrait for NodeMutationDAG {
         Box::new(Self)
     }
 
-    fn mutate_dag(&self, node: &mut CGPNode, cgp_edges: &mut CGPEdges) {
+    fn mutate_dag(&self, node: &mut CGPNode, cgp_edges: &mut CGPEdges, rng: &mut ChaCha8Rng) {
         match node.node_type {
-            NodeType::OutputNode => self.mutate_output_node(node),
-            NodeType::ComputationalNode => self.mutate_computational_node(node, cgp_edges),
+            NodeType::OutputNode => self.mutate_output_node(node, rng),
+            NodeType::ComputationalNode => self.mutate_computational_node(node, cgp_edges, rng),
             _ => { panic!("Trying to mutate input node") }
         }
     }
 }
 
 impl NodeMutationDAG {
-    fn mutate_output_node(&self, node: &mut CGPNode) {
+    fn mutate_output_node(&self, node: &mut CGPNode, rng: &mut ChaCha8Rng) {
         loop {
-            let rand_nbr: usize = rand::thread_rng().gen_range(0..(node.nbr_inputs + node.graph_width));
+            let rand_nbr: usize = rng.gen_range(0..(node.nbr_inputs + node.graph_width));
 
             if rand_nbr != node.connection0 {
                 node.connection0 = rand_nbr;
@@ -96,14 +117,15 @@ impl NodeMutationDAG {
         }
     }
 
-    fn mutate_computational_node(&self, node: &mut CGPNode, cgp_edges: &mut CGPEdges) {
-        let rand_nbr = rand::thread_rng().gen_range(0..=2);
+    fn mutate_computational_node(&self, node: &mut CGPNode, cgp_edges: &mut CGPEdges, rng: &mut ChaCha8Rng) {
+        let rand_nbr = rng.gen_range(0..=last_gene(node));
         match rand_nbr {
             0 => {
                 let new_connection_id = self.gen_random_connection_id(node.connection0,
                                                                       node.position,
                                                                       node.nbr_inputs + node.graph_width,
-                                                                      cgp_edges);
+                                                                      cgp_edges,
+                                                                      rng);
 
                 cgp_edges.remove_edge(node.position, node.connection0);
                 cgp_edges.add_edge(node.position, new_connection_id);
@@ -115,7 +137,8 @@ impl NodeMutationDAG {
                 let new_connection_id = self.gen_random_connection_id(node.connection1,
                                                                       node.position,
                                                                       node.nbr_inputs + node.graph_width,
-                                                                      cgp_edges);
+                                                                      cgp_edges,
+                                                                      rng);
 
                 cgp_edges.remove_edge(node.position, node.connection1);
                 cgp_edges.add_edge(node.position, new_connection_id);
@@ -124,7 +147,10 @@ impl NodeMutationDAG {
             }
 
             2 => node.function_id = self.gen_random_function_id(node.function_id,
-                                                                node.number_functions),
+                                                                node.number_functions,
+                                                                rng),
+
+            3 => mutate_constant(node, rng),
 
             _ => { panic!("Mutation: output node something wrong") }
         };
@@ -135,12 +161,12 @@ impl NodeMutationDAG {
                                 previous_connection: usize,
                                 position: usize,
                                 upper_range: usize,
-                                cgp_edges: &CGPEdges) -> usize {
+                                cgp_edges: &CGPEdges,
+                                rng: &mut ChaCha8Rng) -> usize {
         let between = Uniform::from(0..upper_range);
-        let mut rng = thread_rng();
 
         loop {
-            let rand_nbr: usize = between.sample(&mut rng);
+            let rand_nbr: usize = between.sample(rng);
 
             if (rand_nbr != previous_connection) && (rand_nbr != position) {
                 // if rand_nbr != position {
@@ -151,9 +177,8 @@ impl NodeMutationDAG {
         }
     }
 
-    fn gen_random_function_id(&self, excluded: usize, upper_range: usize) -> usize {
+    fn gen_random_function_id(&self, excluded: usize, upper_range: usize, rng: &mut ChaCha8Rng) -> usize {
         loop {
-            let mut rng = thread_rng();
             let rand_nbr: usize = rng.gen_range(0..upper_range);
 
             if rand_nbr != excluded {
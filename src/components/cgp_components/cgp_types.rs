@@ -1,6 +1,8 @@
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
 pub enum CGPType {
     Standard,
     OriginalReorder,
@@ -23,4 +25,22 @@ impl Display for CGPType {
             CGPType::UniformReorder => {write!(f, "Uniform Distribution Reorder")}
         }
     }
-}
\ No newline at end of file
+}
+
+/// Parses the variant name, i.e. "Standard" or "DAG"; the same names are used in experiment files.
+impl FromStr for CGPType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Standard" => Ok(CGPType::Standard),
+            "OriginalReorder" => Ok(CGPType::OriginalReorder),
+            "EReorder" => Ok(CGPType::EReorder),
+            "LSDReorder" => Ok(CGPType::LSDReorder),
+            "NegBiasReorder" => Ok(CGPType::NegBiasReorder),
+            "UniformReorder" => Ok(CGPType::UniformReorder),
+            "DAG" => Ok(CGPType::DAG),
+            _ => Err(format!("unknown cgp_type '{}'", s)),
+        }
+    }
+}
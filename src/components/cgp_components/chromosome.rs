@@ -1,28 +1,38 @@
 //! The chromosome class; a chromosome defined by a CGP graph.
 //! It contains nodes in a single grid-line.
 
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use nohash_hasher::BuildNoHashHasher;
+use rand_chacha::ChaCha8Rng;
 use crate::global_params::CgpParameters;
 use crate::components::cgp_components::cgp_node::CGPNode;
 use crate::components::cgp_components::cgp_types::CGPType;
 use crate::components::cgp_components::cgp_node_types::NodeType;
 use crate::utils::cycle_checker::CGPEdges;
+use crate::function_set::function_trait::FunctionTrait;
+use serde::{Deserialize, Serialize};
+use crate::utils::linear_scaling::LinearScaling;
 
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Chromosome
 {
     pub params: CgpParameters,
     pub nodes_grid: Vec<CGPNode>,
     pub active_nodes: Vec<usize>,
     pub cgp_edges: Option<CGPEdges>, // only used for DAG
+    // scaling of every output node fitted on the training data; only used with linear scaling,
+    // see `LinearScalingMetric`
+    pub linear_scaling: Option<Vec<LinearScaling>>,
 }
 
 
 
 impl Chromosome {
-    pub fn new(params: CgpParameters) -> Self {
-        let mut nodes_grid: Vec<CGPNode> = vec![];
-        nodes_grid.reserve(params.nbr_inputs + params.graph_width + params.nbr_outputs);
+    pub fn new(params: CgpParameters, rng: &mut ChaCha8Rng) -> Self {
+        let mut nodes_grid: Vec<CGPNode> = Vec::with_capacity(params.nbr_inputs + params.graph_width + params.nbr_outputs);
 
         let mut cgp_edges: Option<CGPEdges>;
         if params.cgp_type == CGPType::DAG {
@@ -40,7 +50,9 @@ impl Chromosome {
                                          params.graph_width,
                                          NodeType::InputNode,
                                          params.number_functions,
+                                         params.constants,
                                          &mut cgp_edges,
+                                         rng,
             ));
         }
         // computational nodes
@@ -50,7 +62,9 @@ impl Chromosome {
                                          params.graph_width,
                                          NodeType::ComputationalNode,
                                          params.number_functions,
+                                         params.constants,
                                          &mut cgp_edges,
+                                         rng,
             ));
         }
         // output nodes
@@ -62,8 +76,9 @@ impl Chromosome {
                                          params.graph_width,
                                          NodeType::OutputNode,
                                          params.number_functions,
+                                         params.constants,
                                          &mut cgp_edges,
-
+                                         rng,
             ));
         }
 
@@ -72,7 +87,152 @@ impl Chromosome {
             nodes_grid,
             active_nodes: vec![],
             cgp_edges,
+            linear_scaling: None,
+        }
+    }
+
+    /// True if both chromosomes have the same active nodes with the same functions and used
+    /// connections or constants, i.e. they compute the same outputs. Inactive genes are ignored.
+    /// `active_nodes` must be up to date for both chromosomes.
+    pub fn same_active_genotype<T>(&self, other: &Chromosome, function_set: &[Box<dyn FunctionTrait<T>>]) -> bool {
+        if self.active_nodes != other.active_nodes {
+            return false;
         }
+
+        for node_id in &self.active_nodes {
+            let node = &self.nodes_grid[*node_id];
+            let other_node = &other.nodes_grid[*node_id];
+
+            let same_node = match node.node_type {
+                NodeType::InputNode => true,
+                NodeType::OutputNode => node.connection0 == other_node.connection0,
+                NodeType::ComputationalNode => {
+                    // a constant function uses the constant gene instead of the connections
+                    let nbr_connections = function_set[node.function_id].get_number_inputs_needed();
+                    node.function_id == other_node.function_id
+                        && (nbr_connections < 1 || node.connection0 == other_node.connection0)
+                        && (nbr_connections < 2 || node.connection1 == other_node.connection1)
+                        && (nbr_connections > 0 || node.constant == other_node.constant)
+                }
+            };
+            if !same_node {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// Hash of the active subgraph that does not depend on the positions of its nodes: inputs are
+    /// referenced by their index, computational nodes are renumbered in the order a depth-first
+    /// search from the outputs finishes them. Chromosomes with the same hash compute the same
+    /// outputs, up to hash collisions. Does not need `active_nodes`.
+    pub fn active_subgraph_hash<T>(&self, function_set: &[Box<dyn FunctionTrait<T>>]) -> u64 {
+        let nbr_inputs = self.params.nbr_inputs;
+        let output_start_id = nbr_inputs + self.params.graph_width;
+
+        // canonical number of every finished computational node
+        let mut numbers: HashMap<usize, usize, BuildNoHashHasher<usize>> = HashMap::default();
+        let reference = |numbers: &HashMap<usize, usize, BuildNoHashHasher<usize>>, node_id: usize| -> (bool, usize) {
+            if node_id < nbr_inputs { (false, node_id) } else { (true, numbers[&node_id]) }
+        };
+
+        let mut hasher = DefaultHasher::new();
+        let mut to_visit: Vec<(usize, bool)> = vec![];
+        for output_id in output_start_id..(output_start_id + self.params.nbr_outputs) {
+            let output_connection = self.nodes_grid[output_id].connection0;
+
+            // a node is pushed twice: to visit its connections, then (expanded) to number it
+            to_visit.push((output_connection, false));
+            while let Some((node_id, expanded)) = to_visit.pop() {
+                if node_id < nbr_inputs || numbers.contains_key(&node_id) {
+                    continue;
+                }
+                let node = &self.nodes_grid[node_id];
+                let nbr_connections = function_set[node.function_id].get_number_inputs_needed();
+                let connections = &[node.connection0, node.connection1][..nbr_connections];
+
+                if !expanded {
+                    to_visit.push((node_id, true));
+                    for connection in connections.iter().rev() {
+                        to_visit.push((*connection, false));
+                    }
+                } else {
+                    // the function determines the number of connections, so the records cannot
+                    // be confused
+                    node.function_id.hash(&mut hasher);
+                    for connection in connections {
+                        reference(&numbers, *connection).hash(&mut hasher);
+                    }
+                    if nbr_connections == 0 {
+                        node.constant.to_bits().hash(&mut hasher);
+                    }
+                    numbers.insert(node_id, numbers.len());
+                }
+            }
+            reference(&numbers, output_connection).hash(&mut hasher);
+        }
+        return hasher.finish();
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::SeedableRng;
+    use super::*;
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesStandard};
+    use crate::components::cgp_components::chromosome_reorder_operators::{ChromosomeReorderStandard, ChromosomeReorderTrait};
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+
+    fn evaluated_chromosome(cgp_type: CGPType, seed: u64) -> Chromosome {
+        let params = CgpParameters { cgp_type, ..CgpParameters::for_tests(50, 6, 6, 4) };
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<bool>> = ChromosomeFindActiveNodesStandard::new();
+        let mut chromosome = Chromosome::new(params, &mut ChaCha8Rng::seed_from_u64(seed));
+        active_node_func.execute(&mut chromosome, get_boolean_function_set());
+        return chromosome;
+    }
+
+    /// Changes the function of the first computational node that is (in)active.
+    fn change_function(chromosome: &Chromosome, active: bool) -> Chromosome {
+        let node_id = (6..56).find(|id| chromosome.active_nodes.contains(id) == active).unwrap();
+        let mut changed = chromosome.clone();
+        changed.nodes_grid[node_id].function_id = (changed.nodes_grid[node_id].function_id + 1) % 4;
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<bool>> = ChromosomeFindActiveNodesStandard::new();
+        active_node_func.execute(&mut changed, get_boolean_function_set());
+        return changed;
+    }
+
+    #[test]
+    fn same_active_genotype_ignores_inactive_genes() {
+        let function_set = get_boolean_function_set();
+        let parent = evaluated_chromosome(CGPType::Standard, 0);
+
+        assert!(change_function(&parent, false).same_active_genotype(&parent, &function_set));
+        assert!(!change_function(&parent, true).same_active_genotype(&parent, &function_set));
+    }
+
+    #[test]
+    fn active_subgraph_hash_ignores_inactive_genes() {
+        let function_set = get_boolean_function_set();
+        let chromosome = evaluated_chromosome(CGPType::Standard, 1);
+        let hash = chromosome.active_subgraph_hash(&function_set);
+
+        assert_eq!(hash, change_function(&chromosome, false).active_subgraph_hash(&function_set));
+        assert_ne!(hash, change_function(&chromosome, true).active_subgraph_hash(&function_set));
+        assert_ne!(hash, evaluated_chromosome(CGPType::Standard, 2).active_subgraph_hash(&function_set));
+    }
+
+    #[test]
+    fn active_subgraph_hash_ignores_node_positions() {
+        let function_set = get_boolean_function_set();
+        let chromosome = evaluated_chromosome(CGPType::OriginalReorder, 3);
+
+        let mut reordered = chromosome.clone();
+        let reorder: Box<dyn ChromosomeReorderTrait<bool>> = ChromosomeReorderStandard::new();
+        reorder.execute(&mut reordered, Arc::new(ChromosomeFindActiveNodesStandard::new()), Arc::clone(&function_set), &mut ChaCha8Rng::seed_from_u64(3));
+
+        assert!(chromosome.active_nodes != reordered.active_nodes);
+        assert_eq!(chromosome.active_subgraph_hash(&function_set), reordered.active_subgraph_hash(&function_set));
+    }
+}
@@ -1,67 +1,129 @@
 //! Handles the evaluation of a chromosome given inputs and respective outputs.
 
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 use nohash_hasher::BuildNoHashHasher;
 use crate::components::cgp_components::cgp_node::CGPNode;
 use crate::components::cgp_components::chromosome::Chromosome;
 use crate::components::cgp_components::cgp_node_types::NodeType;
-use crate::utils::fitness_metrics;
+use crate::components::cgp_components::fitness_cache::{CacheStatistics, FitnessCache};
+use crate::components::cgp_components::node_value_tables::{NodeValueTables, NodeValues};
+use crate::components::cgp_components::program::{Interpreter, Program, ProgramValue};
+use crate::components::cgp_components::value_arena::ValueArena;
+use crate::utils::fitness_metrics::{BooleanMetric, RegressionMetric};
+use crate::utils::fitness_trait::FitnessFunction;
+use crate::utils::linear_scaling::LinearScaling;
+use crate::utils::numeric::Numeric;
+use crate::utils::packed_bits::PackedBits;
 use crate::utils::utility_funcs::transpose;
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
 use crate::function_set::function_trait::FunctionTrait;
 
-pub trait EvaluateChromosomeTrait<T> where T: Clone {
+/// The dataset a chromosome is evaluated on. Evaluators that keep results of earlier
+/// evaluations, i.e. `CachedChromosomeEvaluator` and `IncrementalChromosomeEvaluator`, keep them
+/// apart per dataset.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum DatasetId {
+    Train,
+    Eval,
+}
+
+/// Send + Sync, so the children of a generation can be evaluated in parallel.
+pub trait EvaluateChromosomeTrait<T>: Send + Sync where T: Clone {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn EvaluateChromosomeTrait<T>> where Self: Sized;
+    #[allow(clippy::ptr_arg)]
     fn evaluate(&self,
                 chromosome: &mut Chromosome,
-                active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                dataset: DatasetId,
                 inputs: &Vec<Vec<T>>,
                 labels: &Vec<Vec<T>>,
-                function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
     ) -> f32;
+
+    /// Evaluates `chromosome`, a copy of `parent` with the genes of `mutated_nodes` changed; the
+    /// parent was evaluated before, so its active nodes are up to date. Evaluators that keep the
+    /// node values of the parent only recompute the nodes downstream of the mutations; all others
+    /// run a full forward pass. The fitness is the same as `evaluate`.
+    #[allow(clippy::too_many_arguments)]
+    fn evaluate_mutant(&self,
+                       chromosome: &mut Chromosome,
+                       _parent: &Chromosome,
+                       _mutated_nodes: &[usize],
+                       active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                       dataset: DatasetId,
+                       inputs: &Vec<Vec<T>>,
+                       labels: &Vec<Vec<T>>,
+                       function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    ) -> f32 {
+        return self.evaluate(chromosome, active_node_func, dataset, inputs, labels, function_set);
+    }
+
+    /// Statistics of the fitness cache; `None` for evaluators without a cache.
+    fn cache_statistics(&self) -> Option<CacheStatistics> {
+        None
+    }
 }
 
 
+/// Evaluates a chromosome with a new map of node values per forward pass. Like all evaluators,
+/// it compares the outputs to the labels with a `FitnessFunction`; `new` takes the fitness of the
+/// paper, `RegressionMetric::SumAbsoluteError` or `BooleanMetric::Hamming`.
 #[derive(Clone)]
-pub struct ChromosomeEvaluator;
+pub struct ChromosomeEvaluator<T> {
+    fitness_function: Arc<dyn FitnessFunction<T>>,
+}
 
+impl<T> ChromosomeEvaluator<T> {
+    pub fn with_fitness_function(fitness_function: Arc<dyn FitnessFunction<T>>) -> Self {
+        Self { fitness_function }
+    }
+}
 
-impl EvaluateChromosomeTrait<f32> for ChromosomeEvaluator {
-    fn new() -> Box<dyn EvaluateChromosomeTrait<f32>> {
-        Box::new(Self)
+
+impl<T: Numeric> EvaluateChromosomeTrait<T> for ChromosomeEvaluator<T> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<T>> {
+        Box::new(Self::with_fitness_function(Arc::new(RegressionMetric::SumAbsoluteError)))
     }
 
     fn evaluate(&self,
                 chromosome: &mut Chromosome,
-                active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<f32>>>,
-                inputs: &Vec<Vec<f32>>,
-                labels: &Vec<Vec<f32>>,
-                function_set: Rc<Vec<Box<dyn FunctionTrait<f32>>>>,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                _dataset: DatasetId,
+                inputs: &Vec<Vec<T>>,
+                labels: &Vec<Vec<T>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
     ) -> f32 {
-        let mut outputs = self.forward_pass(chromosome, active_node_func, inputs, Rc::clone(&function_set));
+        let mut outputs = self.forward_pass(chromosome, active_node_func, inputs, Arc::clone(&function_set));
 
         let output_start_id = chromosome.params.nbr_inputs + chromosome.params.graph_width;
-        // let output_end_id = self.params.nbr_inputs + self.params.graph_width + self.params.nbr_outputs;
-        let outs: Vec<Vec<f32>> = vec![outputs.remove(&output_start_id).unwrap()];
-        let fitness = fitness_metrics::fitness_regression(&outs, labels);
+        let output_end_id = output_start_id + chromosome.params.nbr_outputs;
+        // regression labels hold one vector per output node
+        let outs: Vec<Vec<T>> = (output_start_id..output_end_id)
+            .map(|i| outputs.remove(&i).unwrap())
+            .collect();
+        let fitness = self.fitness_function.chromosome_fitness(chromosome, &outs, labels);
 
         return fitness;
     }
 }
 
-impl EvaluateChromosomeTrait<bool> for ChromosomeEvaluator {
+impl EvaluateChromosomeTrait<bool> for ChromosomeEvaluator<bool> {
     fn new() -> Box<dyn EvaluateChromosomeTrait<bool>> {
-        Box::new(Self)
+        Box::new(Self::with_fitness_function(Arc::new(BooleanMetric::Hamming)))
     }
     fn evaluate(&self,
                 chromosome: &mut Chromosome,
-                active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<bool>>>,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<bool>>>,
+                _dataset: DatasetId,
                 inputs: &Vec<Vec<bool>>,
                 labels: &Vec<Vec<bool>>,
-                function_set: Rc<Vec<Box<dyn FunctionTrait<bool>>>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<bool>>>>,
     ) -> f32 {
-        let mut outputs = self.forward_pass(chromosome, Rc::clone(&active_node_func), inputs, Rc::clone(&function_set));
+        let mut outputs = self.forward_pass(chromosome, Arc::clone(&active_node_func), inputs, Arc::clone(&function_set));
 
         let output_start_id = chromosome.params.nbr_inputs + chromosome.params.graph_width;
         let output_end_id = chromosome.params.nbr_inputs + chromosome.params.graph_width + chromosome.params.nbr_outputs;
@@ -72,33 +134,488 @@ impl EvaluateChromosomeTrait<bool> for ChromosomeEvaluator {
         }
 
         let outs = transpose(outs);
-        let fitness = fitness_metrics::fitness_boolean(&outs, labels);
+        let fitness = self.fitness_function.chromosome_fitness(chromosome, &outs, labels);
+        return fitness;
+    }
+}
+
+/// Bit-parallel variant of the Boolean evaluation; see `packed_bits`.
+impl EvaluateChromosomeTrait<PackedBits> for ChromosomeEvaluator<PackedBits> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<PackedBits>> {
+        Box::new(Self::with_fitness_function(Arc::new(BooleanMetric::Hamming)))
+    }
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<PackedBits>>>,
+                _dataset: DatasetId,
+                inputs: &Vec<Vec<PackedBits>>,
+                labels: &Vec<Vec<PackedBits>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<PackedBits>>>>,
+    ) -> f32 {
+        let mut outputs = self.forward_pass(chromosome, Arc::clone(&active_node_func), inputs, Arc::clone(&function_set));
+
+        let output_start_id = chromosome.params.nbr_inputs + chromosome.params.graph_width;
+        let output_end_id = chromosome.params.nbr_inputs + chromosome.params.graph_width + chromosome.params.nbr_outputs;
+
+        let mut outs: Vec<Vec<PackedBits>> = Vec::with_capacity(output_end_id - output_start_id);
+        for i in output_start_id..output_end_id {
+            outs.push(outputs.remove(&i).unwrap());
+        }
+
+        let outs = transpose(outs);
+        let fitness = self.fitness_function.chromosome_fitness(chromosome, &outs, labels);
         return fitness;
     }
 }
 
-// impl<T> EvaluateChromosomeTrait<T> for ChromosomeEvaluator {
-//     fn new() -> Box<dyn EvaluateChromosomeTrait<T>> {
-//         Box::new(Self)
-//     }
-//     fn evaluate(&self,
-//                 chromosome: &mut Chromosome,
-//                 active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-//                 inputs: &Vec<Vec<T>>,
-//                 labels: &Vec<Vec<T>>,
-//                 function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>,
-//     ) -> f32 {
-//         return -1.;
-//     }
-// }
-
-impl ChromosomeEvaluator {
-    fn forward_pass<T: Clone>(
+/// `ChromosomeEvaluator` without allocations: the node values of every forward pass are kept in
+/// a `ValueArena` that is reused. Each thread that evaluates at the same time takes its own arena
+/// from the pool.
+pub struct ArenaChromosomeEvaluator<T> {
+    arenas: Mutex<Vec<ValueArena<T>>>,
+    fitness_function: Arc<dyn FitnessFunction<T>>,
+}
+
+impl<T: Clone> ArenaChromosomeEvaluator<T> {
+    pub fn with_fitness_function(fitness_function: Arc<dyn FitnessFunction<T>>) -> Self {
+        Self { arenas: Mutex::new(vec![]), fitness_function }
+    }
+
+    fn with_arena(&self,
+                  chromosome: &mut Chromosome,
+                  active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                  inputs: &[Vec<T>],
+                  function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+                  fitness: impl FnOnce(&mut ValueArena<T>, &mut Chromosome) -> f32,
+    ) -> f32 {
+        active_node_func.execute(chromosome, Arc::clone(&function_set));
+
+        let mut arena = self.arenas.lock().unwrap().pop().unwrap_or_default();
+        arena.forward_pass(chromosome, inputs, &function_set);
+        let fitness = fitness(&mut arena, chromosome);
+        self.arenas.lock().unwrap().push(arena);
+        return fitness;
+    }
+}
+
+impl<T: Numeric> EvaluateChromosomeTrait<T> for ArenaChromosomeEvaluator<T> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<T>> {
+        Box::new(Self::with_fitness_function(Arc::new(RegressionMetric::SumAbsoluteError)))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                _dataset: DatasetId,
+                inputs: &Vec<Vec<T>>,
+                labels: &Vec<Vec<T>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    ) -> f32 {
+        return self.with_arena(chromosome, active_node_func, inputs, function_set, |arena, chromosome| {
+            let outputs = arena.outputs_by_column(chromosome, inputs, chromosome.params.nbr_outputs);
+            self.fitness_function.chromosome_fitness(chromosome, outputs, labels)
+        });
+    }
+}
+
+impl EvaluateChromosomeTrait<bool> for ArenaChromosomeEvaluator<bool> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<bool>> {
+        Box::new(Self::with_fitness_function(Arc::new(BooleanMetric::Hamming)))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<bool>>>,
+                _dataset: DatasetId,
+                inputs: &Vec<Vec<bool>>,
+                labels: &Vec<Vec<bool>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<bool>>>>,
+    ) -> f32 {
+        return self.with_arena(chromosome, active_node_func, inputs, function_set, |arena, chromosome| {
+            let outputs = arena.outputs_by_row(chromosome, inputs);
+            self.fitness_function.chromosome_fitness(chromosome, outputs, labels)
+        });
+    }
+}
+
+impl EvaluateChromosomeTrait<PackedBits> for ArenaChromosomeEvaluator<PackedBits> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<PackedBits>> {
+        Box::new(Self::with_fitness_function(Arc::new(BooleanMetric::Hamming)))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<PackedBits>>>,
+                _dataset: DatasetId,
+                inputs: &Vec<Vec<PackedBits>>,
+                labels: &Vec<Vec<PackedBits>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<PackedBits>>>>,
+    ) -> f32 {
+        return self.with_arena(chromosome, active_node_func, inputs, function_set, |arena, chromosome| {
+            let outputs = arena.outputs_by_row(chromosome, inputs);
+            self.fitness_function.chromosome_fitness(chromosome, outputs, labels)
+        });
+    }
+}
+
+/// Compiles a chromosome to a `Program` and runs it with an `Interpreter`: no node lookups and no
+/// dynamic function calls in the forward pass. Each thread that evaluates at the same time takes
+/// its own interpreter from the pool. A chromosome that cannot be compiled, e.g. with a function
+/// the interpreter does not support, is evaluated by an `ArenaChromosomeEvaluator` instead.
+pub struct CompiledChromosomeEvaluator<T> {
+    interpreters: Mutex<Vec<Interpreter<T>>>,
+    fitness_function: Arc<dyn FitnessFunction<T>>,
+    fallback: ArenaChromosomeEvaluator<T>,
+}
+
+impl<T: ProgramValue> CompiledChromosomeEvaluator<T> {
+    pub fn with_fitness_function(fitness_function: Arc<dyn FitnessFunction<T>>) -> Self {
+        Self {
+            interpreters: Mutex::new(vec![]),
+            fallback: ArenaChromosomeEvaluator::with_fitness_function(Arc::clone(&fitness_function)),
+            fitness_function,
+        }
+    }
+
+    /// `None` if `chromosome` cannot be compiled or the interpreter does not support its program.
+    fn with_interpreter(&self,
+                        chromosome: &mut Chromosome,
+                        active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                        inputs: &[Vec<T>],
+                        function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+                        fitness: impl FnOnce(&mut Interpreter<T>, &Program, &mut Chromosome) -> f32,
+    ) -> Option<f32> {
+        active_node_func.execute(chromosome, Arc::clone(&function_set));
+        let program = Program::compile(chromosome, &function_set)
+            .and_then(|program| Interpreter::<T>::check(&program).map(|_| program))
+            .ok()?;
+
+        let mut interpreter = self.interpreters.lock().unwrap().pop().unwrap_or_default();
+        interpreter.run(&program, inputs);
+        let fitness = fitness(&mut interpreter, &program, chromosome);
+        self.interpreters.lock().unwrap().push(interpreter);
+        return Some(fitness);
+    }
+}
+
+impl<T: Numeric + ProgramValue> EvaluateChromosomeTrait<T> for CompiledChromosomeEvaluator<T> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<T>> {
+        Box::new(Self::with_fitness_function(Arc::new(RegressionMetric::SumAbsoluteError)))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                dataset: DatasetId,
+                inputs: &Vec<Vec<T>>,
+                labels: &Vec<Vec<T>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    ) -> f32 {
+        let fitness = self.with_interpreter(chromosome, Arc::clone(&active_node_func), inputs, Arc::clone(&function_set), |interpreter, program, chromosome|
+            self.fitness_function.chromosome_fitness(chromosome, interpreter.outputs_by_column(program, inputs, program.outputs.len()), labels));
+        return fitness.unwrap_or_else(|| self.fallback.evaluate(chromosome, active_node_func, dataset, inputs, labels, function_set));
+    }
+}
+
+impl EvaluateChromosomeTrait<bool> for CompiledChromosomeEvaluator<bool> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<bool>> {
+        Box::new(Self::with_fitness_function(Arc::new(BooleanMetric::Hamming)))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<bool>>>,
+                dataset: DatasetId,
+                inputs: &Vec<Vec<bool>>,
+                labels: &Vec<Vec<bool>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<bool>>>>,
+    ) -> f32 {
+        let fitness = self.with_interpreter(chromosome, Arc::clone(&active_node_func), inputs, Arc::clone(&function_set), |interpreter, program, chromosome|
+            self.fitness_function.chromosome_fitness(chromosome, interpreter.outputs_by_row(program, inputs), labels));
+        return fitness.unwrap_or_else(|| self.fallback.evaluate(chromosome, active_node_func, dataset, inputs, labels, function_set));
+    }
+}
+
+impl EvaluateChromosomeTrait<PackedBits> for CompiledChromosomeEvaluator<PackedBits> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<PackedBits>> {
+        Box::new(Self::with_fitness_function(Arc::new(BooleanMetric::Hamming)))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<PackedBits>>>,
+                dataset: DatasetId,
+                inputs: &Vec<Vec<PackedBits>>,
+                labels: &Vec<Vec<PackedBits>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<PackedBits>>>>,
+    ) -> f32 {
+        let fitness = self.with_interpreter(chromosome, Arc::clone(&active_node_func), inputs, Arc::clone(&function_set), |interpreter, program, chromosome|
+            self.fitness_function.chromosome_fitness(chromosome, interpreter.outputs_by_row(program, inputs), labels));
+        return fitness.unwrap_or_else(|| self.fallback.evaluate(chromosome, active_node_func, dataset, inputs, labels, function_set));
+    }
+}
+
+/// Evaluates a mutant from the node values of its parent; see `node_value_tables`. Only the
+/// active nodes downstream of the mutated nodes are recomputed. The node values of the most
+/// recently evaluated chromosomes are kept, so the children of the next generation find their
+/// parent. A parent without node values, e.g. of the initial population, is evaluated first.
+pub struct IncrementalChromosomeEvaluator<T> {
+    tables: Mutex<NodeValueTables<T>>,
+    fitness_function: Arc<dyn FitnessFunction<T>>,
+}
+
+impl<T: Clone> IncrementalChromosomeEvaluator<T> {
+    pub const DEFAULT_CAPACITY: usize = 16;
+
+    pub fn with_capacity(capacity: usize, fitness_function: Arc<dyn FitnessFunction<T>>) -> Self {
+        Self { tables: Mutex::new(NodeValueTables::new(capacity)), fitness_function }
+    }
+
+    /// Updates the active nodes of `chromosome`, evaluates it and keeps its node values.
+    fn node_values(&self,
+                   chromosome: &mut Chromosome,
+                   parent: Option<(&Chromosome, &[usize])>,
+                   active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                   dataset: DatasetId,
+                   inputs: &[Vec<T>],
+                   function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    ) -> Arc<NodeValues<T>> {
+        active_node_func.execute(chromosome, Arc::clone(&function_set));
+
+        let values = match parent {
+            Some((parent, mutated_nodes)) => {
+                let parent_values = self.parent_values(parent, active_node_func, dataset, inputs, &function_set);
+                NodeValues::forward_pass_from_parent(chromosome, &parent_values, mutated_nodes, inputs, &function_set)
+            }
+            None => NodeValues::forward_pass(chromosome, dataset, inputs, &function_set),
+        };
+        let values = Arc::new(values);
+        self.tables.lock().unwrap().insert(Arc::clone(&values));
+        return values;
+    }
+
+    fn parent_values(&self,
+                     parent: &Chromosome,
+                     active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                     dataset: DatasetId,
+                     inputs: &[Vec<T>],
+                     function_set: &Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    ) -> Arc<NodeValues<T>> {
+        let cached = self.tables.lock().unwrap().get(parent, dataset, function_set);
+        if let Some(values) = cached {
+            return values;
+        }
+
+        let mut parent = parent.clone();
+        active_node_func.execute(&mut parent, Arc::clone(function_set));
+        let values = Arc::new(NodeValues::forward_pass(&parent, dataset, inputs, function_set));
+        self.tables.lock().unwrap().insert(Arc::clone(&values));
+        return values;
+    }
+}
+
+impl<T: Numeric> EvaluateChromosomeTrait<T> for IncrementalChromosomeEvaluator<T> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<T>> {
+        Box::new(Self::with_capacity(Self::DEFAULT_CAPACITY, Arc::new(RegressionMetric::SumAbsoluteError)))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                dataset: DatasetId,
+                inputs: &Vec<Vec<T>>,
+                labels: &Vec<Vec<T>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    ) -> f32 {
+        let values = self.node_values(chromosome, None, active_node_func, dataset, inputs, function_set);
+        let outputs = values.outputs(inputs, chromosome.params.nbr_outputs);
+        return self.fitness_function.chromosome_fitness(chromosome, &outputs, labels);
+    }
+
+    fn evaluate_mutant(&self,
+                       chromosome: &mut Chromosome,
+                       parent: &Chromosome,
+                       mutated_nodes: &[usize],
+                       active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                       dataset: DatasetId,
+                       inputs: &Vec<Vec<T>>,
+                       labels: &Vec<Vec<T>>,
+                       function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    ) -> f32 {
+        let values = self.node_values(chromosome, Some((parent, mutated_nodes)), active_node_func, dataset, inputs, function_set);
+        let outputs = values.outputs(inputs, chromosome.params.nbr_outputs);
+        return self.fitness_function.chromosome_fitness(chromosome, &outputs, labels);
+    }
+}
+
+impl EvaluateChromosomeTrait<bool> for IncrementalChromosomeEvaluator<bool> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<bool>> {
+        Box::new(Self::with_capacity(Self::DEFAULT_CAPACITY, Arc::new(BooleanMetric::Hamming)))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<bool>>>,
+                dataset: DatasetId,
+                inputs: &Vec<Vec<bool>>,
+                labels: &Vec<Vec<bool>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<bool>>>>,
+    ) -> f32 {
+        let values = self.node_values(chromosome, None, active_node_func, dataset, inputs, function_set);
+        let outputs = transpose(values.outputs(inputs, chromosome.params.nbr_outputs));
+        return self.fitness_function.chromosome_fitness(chromosome, &outputs, labels);
+    }
+
+    fn evaluate_mutant(&self,
+                       chromosome: &mut Chromosome,
+                       parent: &Chromosome,
+                       mutated_nodes: &[usize],
+                       active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<bool>>>,
+                       dataset: DatasetId,
+                       inputs: &Vec<Vec<bool>>,
+                       labels: &Vec<Vec<bool>>,
+                       function_set: Arc<Vec<Box<dyn FunctionTrait<bool>>>>,
+    ) -> f32 {
+        let values = self.node_values(chromosome, Some((parent, mutated_nodes)), active_node_func, dataset, inputs, function_set);
+        let outputs = transpose(values.outputs(inputs, chromosome.params.nbr_outputs));
+        return self.fitness_function.chromosome_fitness(chromosome, &outputs, labels);
+    }
+}
+
+impl EvaluateChromosomeTrait<PackedBits> for IncrementalChromosomeEvaluator<PackedBits> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<PackedBits>> {
+        Box::new(Self::with_capacity(Self::DEFAULT_CAPACITY, Arc::new(BooleanMetric::Hamming)))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<PackedBits>>>,
+                dataset: DatasetId,
+                inputs: &Vec<Vec<PackedBits>>,
+                labels: &Vec<Vec<PackedBits>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<PackedBits>>>>,
+    ) -> f32 {
+        let values = self.node_values(chromosome, None, active_node_func, dataset, inputs, function_set);
+        let outputs = transpose(values.outputs(inputs, chromosome.params.nbr_outputs));
+        return self.fitness_function.chromosome_fitness(chromosome, &outputs, labels);
+    }
+
+    fn evaluate_mutant(&self,
+                       chromosome: &mut Chromosome,
+                       parent: &Chromosome,
+                       mutated_nodes: &[usize],
+                       active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<PackedBits>>>,
+                       dataset: DatasetId,
+                       inputs: &Vec<Vec<PackedBits>>,
+                       labels: &Vec<Vec<PackedBits>>,
+                       function_set: Arc<Vec<Box<dyn FunctionTrait<PackedBits>>>>,
+    ) -> f32 {
+        let values = self.node_values(chromosome, Some((parent, mutated_nodes)), active_node_func, dataset, inputs, function_set);
+        let outputs = transpose(values.outputs(inputs, chromosome.params.nbr_outputs));
+        return self.fitness_function.chromosome_fitness(chromosome, &outputs, labels);
+    }
+}
+
+/// Looks up the fitness of a chromosome in a `FitnessCache` before it runs a forward pass with
+/// another evaluator. The cache is not locked during the forward pass; children that are
+/// evaluated at the same time can all miss the same entry. A hit restores the linear scaling the
+/// fitness function fitted to the chromosome, see `LinearScalingMetric`.
+pub struct CachedChromosomeEvaluator<T> {
+    cache: Mutex<FitnessCache<(f32, Option<Vec<LinearScaling>>)>>,
+    evaluator: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+}
+
+impl<T: Clone> CachedChromosomeEvaluator<T> {
+    pub const DEFAULT_CAPACITY: usize = 100_000;
+
+    pub fn with_capacity(capacity: usize, evaluator: Arc<Box<dyn EvaluateChromosomeTrait<T>>>) -> Self {
+        Self {
+            cache: Mutex::new(FitnessCache::new(capacity)),
+            evaluator,
+        }
+    }
+
+    /// The cached fitness of `chromosome` on `dataset`; `evaluate` computes it on a miss.
+    fn fitness(&self,
+               chromosome: &mut Chromosome,
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               dataset: DatasetId,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               evaluate: impl FnOnce(&mut Chromosome) -> f32,
+    ) -> f32 {
+        // the same chromosome has a different fitness on the training and the eval data
+        let mut hasher = DefaultHasher::new();
+        chromosome.active_subgraph_hash(&function_set).hash(&mut hasher);
+        dataset.hash(&mut hasher);
+        let key = hasher.finish();
+
+        let cached = self.cache.lock().unwrap().get(key);
+        if let Some((fitness, linear_scaling)) = cached {
+            // callers rely on the active nodes of an evaluated chromosome
+            active_node_func.execute(chromosome, function_set);
+            chromosome.linear_scaling = linear_scaling;
+            return fitness;
+        }
+
+        let fitness = evaluate(chromosome);
+        self.cache.lock().unwrap().insert(key, (fitness, chromosome.linear_scaling.clone()));
+        return fitness;
+    }
+}
+
+impl<T: Clone + 'static> EvaluateChromosomeTrait<T> for CachedChromosomeEvaluator<T> where ArenaChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
+    fn new() -> Box<dyn EvaluateChromosomeTrait<T>> {
+        Box::new(Self::with_capacity(Self::DEFAULT_CAPACITY, Arc::new(ArenaChromosomeEvaluator::new())))
+    }
+
+    fn evaluate(&self,
+                chromosome: &mut Chromosome,
+                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                dataset: DatasetId,
+                inputs: &Vec<Vec<T>>,
+                labels: &Vec<Vec<T>>,
+                function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    ) -> f32 {
+        return self.fitness(chromosome, Arc::clone(&active_node_func), dataset, Arc::clone(&function_set), |chromosome|
+            self.evaluator.evaluate(chromosome, active_node_func, dataset, inputs, labels, function_set));
+    }
+
+    fn evaluate_mutant(&self,
+                       chromosome: &mut Chromosome,
+                       parent: &Chromosome,
+                       mutated_nodes: &[usize],
+                       active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                       dataset: DatasetId,
+                       inputs: &Vec<Vec<T>>,
+                       labels: &Vec<Vec<T>>,
+                       function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    ) -> f32 {
+        return self.fitness(chromosome, Arc::clone(&active_node_func), dataset, Arc::clone(&function_set), |chromosome|
+            self.evaluator.evaluate_mutant(chromosome, parent, mutated_nodes, active_node_func, dataset, inputs, labels, function_set));
+    }
+
+    fn cache_statistics(&self) -> Option<CacheStatistics> {
+        Some(self.cache.lock().unwrap().statistics())
+    }
+}
+
+/// Values of every output node of `chromosome` on the (transposed) inputs, one vector per output
+/// node; the layout of regression labels. `active_nodes` must be up to date.
+pub fn predict<T: Clone>(chromosome: &Chromosome,
+                         inputs: &[Vec<T>],
+                         function_set: &[Box<dyn FunctionTrait<T>>]) -> Vec<Vec<T>> {
+    let mut arena = ValueArena::new();
+    arena.forward_pass(chromosome, inputs, function_set);
+    return arena.outputs_by_column(chromosome, inputs, chromosome.params.nbr_outputs).clone();
+}
+
+impl<T: Clone> ChromosomeEvaluator<T> {
+    fn forward_pass(
         &self,
         chromosome: &mut Chromosome,
-        active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-        inputs: &Vec<Vec<T>>,
-        function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>,
+        active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+        inputs: &[Vec<T>],
+        function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
     )
         -> HashMap<usize, Vec<T>, BuildNoHashHasher<usize>>
     {
@@ -106,7 +623,7 @@ impl ChromosomeEvaluator {
         // let active_nodes = self.get_active_nodes_id();
         // self.active_nodes = Some(self.get_active_nodes_id());
         // chromosome.get_active_nodes_id();
-        active_node_func.execute(chromosome, Rc::clone(&function_set));
+        active_node_func.execute(chromosome, Arc::clone(&function_set));
 
         let mut outputs: HashMap<usize, Vec<T>, BuildNoHashHasher<usize>> = HashMap::with_capacity_and_hasher(
             chromosome.params.nbr_inputs + chromosome.params.graph_width + chromosome.params.nbr_outputs,
@@ -132,15 +649,24 @@ impl ChromosomeEvaluator {
                     outputs.insert(*node_id, prev_output1.clone());
                 }
                 NodeType::ComputationalNode => {
-                    let prev_output1 = outputs.get(&current_node.connection0).unwrap();
-                    let calculated_result: Vec<T>;
+                    let mut calculated_result: Vec<T>;
                     let function = &function_set[current_node.function_id];
 
-                    if function.get_number_inputs_needed() == 2 {
-                        let prev_output2 = outputs.get(&current_node.connection1).unwrap();
-                        calculated_result = function.execute_function(&[prev_output1, prev_output2]);
-                    } else {
-                        calculated_result = function.execute_function(&[prev_output1]);
+                    match function.get_number_inputs_needed() {
+                        2 => {
+                            let prev_output1 = outputs.get(&current_node.connection0).unwrap();
+                            let prev_output2 = outputs.get(&current_node.connection1).unwrap();
+                            calculated_result = function.execute_function(&[prev_output1, prev_output2]);
+                        }
+                        1 => {
+                            let prev_output1 = outputs.get(&current_node.connection0).unwrap();
+                            calculated_result = function.execute_function(&[prev_output1]);
+                        }
+                        _ => {
+                            calculated_result = Vec::with_capacity(inputs[0].len());
+                            function.execute_constant_into(current_node.constant, inputs[0].len(), &mut calculated_result)
+                                .expect("checked by check_constant_functions");
+                        }
                     }
                     outputs.insert(*node_id, calculated_result);
                 }
@@ -149,4 +675,293 @@ impl ChromosomeEvaluator {
 
         return outputs;
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::cgp_components::cgp_node_mutation_operators::{NodeMutationOperatorTrait, NodeMutationStandard};
+    use crate::components::cgp_components::chromosome_mutation_operators::{ChromosomeMutationSingle, ChromosomeMutationTrait};
+    use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeFindActiveNodesStandard;
+    use crate::datasets::boolean_datasets;
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::datasets::{integer_benchmarks, regression_benchmarks};
+    use crate::function_set::f64_function_set::get_f64_function_set;
+    use crate::function_set::integer_function_set::get_integer_function_set;
+    use crate::function_set::packed_boolean_function_set::get_packed_boolean_function_set;
+    use crate::function_set::regression_function_set::get_regression_function_set;
+    use crate::function_set::constant::with_constant;
+    use crate::global_params::{CgpParameters, ConstantParameters};
+    use crate::utils::packed_bits::pack_dataset;
+
+    fn params(nbr_inputs: usize, nbr_outputs: usize) -> CgpParameters {
+        CgpParameters::for_tests(50, nbr_inputs, nbr_outputs, 4)
+    }
+
+    #[test]
+    fn cached_evaluator_returns_the_fitness_of_the_forward_pass() {
+        let function_set = get_boolean_function_set();
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<bool>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        let data = transpose(data);
+        let mut chromosome = Chromosome::new(params(6, 6), &mut ChaCha8Rng::seed_from_u64(0));
+
+        let expected = ChromosomeEvaluator::<bool>::new().evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set));
+        let cached = CachedChromosomeEvaluator::with_capacity(10, Arc::new(ArenaChromosomeEvaluator::new()));
+        for _ in 0..3 {
+            let mut copy = chromosome.clone();
+            copy.active_nodes.clear();
+            assert_eq!(expected, cached.evaluate(&mut copy, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set)));
+            assert_eq!(chromosome.active_nodes, copy.active_nodes);
+        }
+        // the eval data is not answered from the cache of the training data
+        cached.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Eval, &data, &label, Arc::clone(&function_set));
+
+        let statistics = EvaluateChromosomeTrait::<bool>::cache_statistics(&cached).unwrap();
+        assert_eq!((2, 2), (statistics.hits, statistics.misses));
+    }
+
+    #[test]
+    fn packed_evaluation_matches_boolean_evaluation() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(1);
+        let function_set = get_boolean_function_set();
+        let packed_function_set = get_packed_boolean_function_set();
+
+        for get_dataset in [boolean_datasets::parity::get_dataset, boolean_datasets::encode::get_dataset,
+                            boolean_datasets::decode::get_dataset, boolean_datasets::multiply::get_dataset] {
+            let (data, label) = get_dataset();
+            let (packed_data, packed_label) = (transpose(pack_dataset(&data)), pack_dataset(&label));
+            let params = params(data[0].len(), label[0].len());
+            let data = transpose(data);
+
+            for _ in 0..50 {
+                let mut chromosome = Chromosome::new(params.clone(), rng);
+                let fitness = ChromosomeEvaluator::<bool>::new().evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &data, &label, Arc::clone(&function_set));
+                let packed_fitness = ChromosomeEvaluator::<PackedBits>::new().evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &packed_data, &packed_label, Arc::clone(&packed_function_set));
+
+                assert_eq!(fitness.to_bits(), packed_fitness.to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn arena_and_compiled_evaluation_match_evaluation() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(2);
+        let arena_bool: Box<dyn EvaluateChromosomeTrait<bool>> = ArenaChromosomeEvaluator::new();
+        let arena_packed: Box<dyn EvaluateChromosomeTrait<PackedBits>> = ArenaChromosomeEvaluator::new();
+        let compiled_bool: Box<dyn EvaluateChromosomeTrait<bool>> = CompiledChromosomeEvaluator::new();
+        let compiled_packed: Box<dyn EvaluateChromosomeTrait<PackedBits>> = CompiledChromosomeEvaluator::new();
+        let arena_f32: Box<dyn EvaluateChromosomeTrait<f32>> = ArenaChromosomeEvaluator::new();
+
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        let (packed_data, packed_label) = (transpose(pack_dataset(&data)), pack_dataset(&label));
+        let data = transpose(data);
+        let (regression_data, regression_label) = regression_benchmarks::keijzer::get_dataset();
+        let mut regression_params = params(regression_data[0].len(), regression_label.len());
+        regression_params.number_functions = 8;
+        let regression_data = transpose(regression_data);
+
+        for _ in 0..50 {
+            let mut chromosome = Chromosome::new(params(6, 6), rng);
+            let fitness = ChromosomeEvaluator::<bool>::new().evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &data, &label, get_boolean_function_set());
+            assert_eq!(fitness, arena_bool.evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &data, &label, get_boolean_function_set()));
+            assert_eq!(fitness, arena_packed.evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &packed_data, &packed_label, get_packed_boolean_function_set()));
+            assert_eq!(fitness, compiled_bool.evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &data, &label, get_boolean_function_set()));
+            assert_eq!(fitness, compiled_packed.evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &packed_data, &packed_label, get_packed_boolean_function_set()));
+
+            let mut chromosome = Chromosome::new(regression_params.clone(), rng);
+            let fitness = ChromosomeEvaluator::<f32>::new().evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &regression_data, &regression_label, get_regression_function_set());
+            assert_eq!(fitness.to_bits(), arena_f32.evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &regression_data, &regression_label, get_regression_function_set()).to_bits());
+        }
+    }
+
+    /// Evaluates a random walk of single mutations; every mutant is evaluated from its parent.
+    fn assert_mutants_match_evaluation<T: Clone>(params: CgpParameters,
+                                                 data: &Vec<Vec<T>>,
+                                                 label: &Vec<Vec<T>>,
+                                                 function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+                                                 rng: &mut ChaCha8Rng)
+    where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+          IncrementalChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+        let incremental: Box<dyn EvaluateChromosomeTrait<T>> = IncrementalChromosomeEvaluator::new();
+        let node_mutation: Rc<Box<dyn NodeMutationOperatorTrait>> = Rc::new(NodeMutationStandard::new());
+
+        let mut parent = Chromosome::new(params, rng);
+        incremental.evaluate(&mut parent, Arc::clone(&active_node_func), DatasetId::Train, data, label, Arc::clone(&function_set));
+        for _ in 0..200 {
+            let mut child = parent.clone();
+            let report = ChromosomeMutationSingle.execute(&mut child, Rc::clone(&node_mutation), rng);
+
+            let expected = ChromosomeEvaluator::<T>::new().evaluate(&mut child.clone(), Arc::clone(&active_node_func), DatasetId::Train, data, label, Arc::clone(&function_set));
+            let fitness = incremental.evaluate_mutant(&mut child, &parent, &report.mutated_nodes(), Arc::clone(&active_node_func), DatasetId::Train, data, label, Arc::clone(&function_set));
+            assert_eq!(expected.to_bits(), fitness.to_bits());
+            parent = child;
+        }
+    }
+
+    fn assert_evaluators_match<T: Numeric + ProgramValue>(rng: &mut ChaCha8Rng,
+                                                          (data, label): (Vec<Vec<T>>, Vec<Vec<T>>),
+                                                          function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+                                                          constants: Option<ConstantParameters>) {
+        let evaluators: [Box<dyn EvaluateChromosomeTrait<T>>; 3] = [
+            ArenaChromosomeEvaluator::new(),
+            CompiledChromosomeEvaluator::new(),
+            IncrementalChromosomeEvaluator::new(),
+        ];
+        let mut params = params(data[0].len(), label.len());
+        params.number_functions = function_set.len();
+        params.constants = constants;
+        let data = transpose(data);
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+
+        for _ in 0..50 {
+            let mut chromosome = Chromosome::new(params.clone(), rng);
+            let fitness = ChromosomeEvaluator::<T>::new().evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set));
+            assert!(fitness >= 0.);
+            for evaluator in &evaluators {
+                assert_eq!(fitness.to_bits(), evaluator.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set)).to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn f64_and_i64_evaluation_matches_for_every_evaluator() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(4);
+        assert_evaluators_match(rng, regression_benchmarks::keijzer::get_dataset::<f64>(), get_f64_function_set(), None);
+        assert_evaluators_match(rng, regression_benchmarks::pagie_1::get_dataset::<f64>(), get_f64_function_set(), None);
+        assert_evaluators_match(rng, regression_benchmarks::complex_square::get_dataset::<f64>(), get_f64_function_set(), None);
+        assert_evaluators_match(rng, integer_benchmarks::koza_1::get_dataset(), get_integer_function_set(), None);
+        assert_evaluators_match(rng, integer_benchmarks::sum_of_squares::get_dataset(), get_integer_function_set(), None);
+    }
+
+    #[test]
+    fn every_output_node_is_compared_to_its_labels() {
+        let function_set = get_regression_function_set();
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<f32>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+        let (data, label) = regression_benchmarks::complex_square::get_dataset();
+        let data = transpose(data);
+        let mut params = params(2, 2);
+        params.graph_width = 10;
+        params.number_functions = function_set.len();
+
+        let mut chromosome = Chromosome::new(params, &mut ChaCha8Rng::seed_from_u64(5));
+        // out0 = x0 * x0 - x1 * x1, out1 = (x0 + x0) * x1
+        for (node_id, function_id, connection0, connection1) in [(2, 2, 0, 0), (3, 2, 1, 1), (4, 1, 2, 3), (5, 0, 0, 0), (6, 2, 5, 1)] {
+            let node = &mut chromosome.nodes_grid[node_id];
+            (node.function_id, node.connection0, node.connection1) = (function_id, connection0, connection1);
+        }
+        (chromosome.nodes_grid[12].connection0, chromosome.nodes_grid[13].connection0) = (4, 6);
+
+        let evaluators: [Box<dyn EvaluateChromosomeTrait<f32>>; 4] = [
+            ChromosomeEvaluator::new(),
+            ArenaChromosomeEvaluator::new(),
+            CompiledChromosomeEvaluator::new(),
+            IncrementalChromosomeEvaluator::new(),
+        ];
+        for evaluator in &evaluators {
+            assert_eq!(0., evaluator.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set)));
+        }
+
+        // the second output is wrong now; the fitness is the mean error of both outputs
+        chromosome.nodes_grid[13].connection0 = 4;
+        active_node_func.execute(&mut chromosome, Arc::clone(&function_set));
+        let errors = RegressionMetric::SumAbsoluteError.fitness_per_output(&predict(&chromosome, &data, &function_set), &label);
+        assert_eq!(0., errors[0]);
+        assert!(errors[1] > 0.);
+        for evaluator in &evaluators {
+            assert_eq!(errors[1] / 2., evaluator.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set)));
+        }
+    }
+
+    #[test]
+    fn evaluators_use_their_fitness_function() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(4);
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<f32>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+        let function_set = get_regression_function_set();
+        let (data, label) = regression_benchmarks::keijzer::get_dataset();
+        let data = transpose(data);
+        let metric: Arc<dyn FitnessFunction<f32>> = Arc::new(RegressionMetric::RootMeanSquaredError);
+        let evaluators: [Box<dyn EvaluateChromosomeTrait<f32>>; 4] = [
+            Box::new(ChromosomeEvaluator::with_fitness_function(Arc::clone(&metric))),
+            Box::new(ArenaChromosomeEvaluator::with_fitness_function(Arc::clone(&metric))),
+            Box::new(CompiledChromosomeEvaluator::with_fitness_function(Arc::clone(&metric))),
+            Box::new(IncrementalChromosomeEvaluator::with_capacity(4, Arc::clone(&metric))),
+        ];
+        for _ in 0..20 {
+            let mut chromosome = Chromosome::new(params(1, 1), rng);
+            active_node_func.execute(&mut chromosome, Arc::clone(&function_set));
+            let expected = metric.fitness(&predict(&chromosome, &data, &function_set), &label);
+            for evaluator in &evaluators {
+                assert_eq!(expected, evaluator.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set)));
+            }
+        }
+
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        let metric = BooleanMetric::from_name("weighted_hamming", Some(&vec![1., 2., 4., 8., 16., 32.]), 6).unwrap();
+        let bool_evaluator = ArenaChromosomeEvaluator::with_fitness_function(Arc::new(metric.clone()));
+        let packed_evaluator = CompiledChromosomeEvaluator::with_fitness_function(Arc::new(metric));
+        let (packed_data, packed_label) = (transpose(pack_dataset(&data)), pack_dataset(&label));
+        let data = transpose(data);
+        for _ in 0..20 {
+            let mut chromosome = Chromosome::new(params(6, 6), rng);
+            let fitness = bool_evaluator.evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &data, &label, get_boolean_function_set());
+            let packed_fitness = packed_evaluator.evaluate(&mut chromosome, Arc::new(ChromosomeFindActiveNodesStandard::new()), DatasetId::Train, &packed_data, &packed_label, get_packed_boolean_function_set());
+            assert_eq!(fitness, packed_fitness);
+        }
+    }
+
+    #[test]
+    fn incremental_evaluation_of_mutants_matches_evaluation() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(3);
+
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        let (packed_data, packed_label) = (transpose(pack_dataset(&data)), pack_dataset(&label));
+        assert_mutants_match_evaluation(params(6, 6), &transpose(data), &label, get_boolean_function_set(), rng);
+        assert_mutants_match_evaluation(params(6, 6), &packed_data, &packed_label, get_packed_boolean_function_set(), rng);
+
+        let (data, label) = regression_benchmarks::keijzer::get_dataset();
+        let mut regression_params = params(data[0].len(), label.len());
+        regression_params.number_functions = 8;
+        assert_mutants_match_evaluation(regression_params, &transpose(data), &label, get_regression_function_set(), rng);
+    }
+
+    #[test]
+    fn constant_nodes_match_for_every_evaluator() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(6);
+        let constants = ConstantParameters { min: -2., max: 2., sigma: 0.5 };
+        let function_set = with_constant(get_regression_function_set()).unwrap();
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<f32>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+        let (data, label) = regression_benchmarks::keijzer::get_dataset();
+        let mut params = params(1, 1);
+        params.graph_width = 10;
+        params.number_functions = function_set.len();
+        params.constants = Some(constants);
+
+        // out = x0 * 0.5; the constant node does not read its connections
+        let mut chromosome = Chromosome::new(params.clone(), rng);
+        let node = &mut chromosome.nodes_grid[1];
+        (node.function_id, node.constant) = (8, 0.5);
+        let node = &mut chromosome.nodes_grid[2];
+        (node.function_id, node.connection0, node.connection1) = (2, 0, 1);
+        chromosome.nodes_grid[11].connection0 = 2;
+        active_node_func.execute(&mut chromosome, Arc::clone(&function_set));
+        assert_eq!(vec![0, 1, 2, 11], chromosome.active_nodes);
+
+        let transposed = transpose(data.clone());
+        let expected: Vec<f32> = transposed[0].iter().map(|x| x * 0.5).collect();
+        assert_eq!(vec![expected], predict(&chromosome, &transposed, &function_set));
+        let program = Program::compile(&chromosome, &function_set).unwrap();
+        assert_eq!("r1 = const 0.5\nr2 = mul r0 r1\noutputs: r2\n", program.to_string());
+
+        assert_evaluators_match(rng, (data.clone(), label.clone()), Arc::clone(&function_set), Some(constants));
+        assert_evaluators_match(rng, regression_benchmarks::pagie_1::get_dataset::<f64>(), with_constant(get_f64_function_set()).unwrap(), Some(constants));
+        assert_evaluators_match(rng, integer_benchmarks::koza_1::get_dataset(), with_constant(get_integer_function_set()).unwrap(), Some(constants));
+
+        params.graph_width = 50;
+        assert_mutants_match_evaluation(params, &transpose(data), &label, function_set, rng);
+    }
+}
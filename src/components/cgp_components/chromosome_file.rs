@@ -0,0 +1,439 @@
+//! Genome files of a `Chromosome`, i.e. to archive evolved programs, to re-evaluate them or to
+//! seed new runs. Genome files are written in TOML and contain the `CgpParameters`, the names of
+//! the function set and every node of the graph:
+//!
+//! ```toml
+//! format_version = 1
+//! function_set = ["and", "or", "nand", "nor"]
+//!
+//! [params]
+//! cgp_type = "Standard"
+//! graph_width = 500
+//! ...
+//!
+//! [[nodes]]
+//! position = 0
+//! node_type = "InputNode"
+//!
+//! [[nodes]]
+//! position = 3
+//! node_type = "ComputationalNode"
+//! function_id = 2
+//! connection0 = 0
+//! connection1 = 1
+//! ```
+//!
+//! With `[params.constants]`, every computational node has a `constant` gene.
+//! Genomes of a run with linear scaling have a `[[linear_scaling]]` table (`intercept` and
+//! `slope`) per output node after the nodes.
+//! Unused genes (i.e. the function of an output node) are left out. Function ids refer to
+//! `function_set`; on load, they are mapped to the function set of the caller by name.
+//! Files of a different `format_version` are rejected.
+
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::components::cgp_components::cgp_node::CGPNode;
+use crate::components::cgp_components::cgp_node_types::NodeType;
+use crate::components::cgp_components::cgp_types::CGPType;
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::global_params::CgpParameters;
+use crate::utils::cycle_checker::CGPEdges;
+use crate::utils::linear_scaling::LinearScaling;
+
+pub const CHROMOSOME_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChromosomeFile {
+    format_version: u32,
+    function_set: Vec<String>,
+    params: CgpParameters,
+    nodes: Vec<NodeRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    linear_scaling: Option<Vec<LinearScaling>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeRecord {
+    position: usize,
+    node_type: NodeType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_id: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    connection0: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    connection1: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    constant: Option<f64>,
+}
+
+/// Unused genes are `usize::MAX` in a `CGPNode`.
+fn used_gene(gene: usize) -> Option<usize> {
+    if gene == usize::MAX { None } else { Some(gene) }
+}
+
+fn function_names<T>(function_set: &[Box<dyn FunctionTrait<T>>]) -> Vec<String> {
+    function_set.iter().map(|function| function.get_name().to_string()).collect()
+}
+
+impl Chromosome {
+    pub fn save<T>(&self, path: &Path, function_set: &[Box<dyn FunctionTrait<T>>]) -> Result<(), String> {
+        fs::write(path, self.to_genome_toml(function_set))
+            .map_err(|e| format!("cannot write genome file {}: {}", path.display(), e))
+    }
+
+    pub fn load<T>(path: &Path, function_set: &[Box<dyn FunctionTrait<T>>]) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read genome file {}: {}", path.display(), e))?;
+        Self::from_genome_toml(&content, function_set)
+            .map_err(|e| format!("invalid genome file {}: {}", path.display(), e))
+    }
+
+    pub fn to_genome_toml<T>(&self, function_set: &[Box<dyn FunctionTrait<T>>]) -> String {
+        let nodes = self.nodes_grid
+            .iter()
+            .map(|node| NodeRecord {
+                position: node.position,
+                node_type: node.node_type.clone(),
+                function_id: used_gene(node.function_id),
+                connection0: used_gene(node.connection0),
+                connection1: used_gene(node.connection1),
+                constant: match node.node_type {
+                    NodeType::ComputationalNode if node.constants.is_some() => Some(node.constant),
+                    _ => None,
+                },
+            })
+            .collect();
+
+        let file = ChromosomeFile {
+            format_version: CHROMOSOME_FORMAT_VERSION,
+            function_set: function_names(function_set),
+            params: self.params.clone(),
+            nodes,
+            linear_scaling: self.linear_scaling.clone(),
+        };
+        toml::to_string(&file).expect("chromosome is always serializable")
+    }
+
+    /// Parses and checks a genome file. `active_nodes` is empty, as for a new chromosome.
+    pub fn from_genome_toml<T>(content: &str, function_set: &[Box<dyn FunctionTrait<T>>]) -> Result<Self, String> {
+        let file: ChromosomeFile = toml::from_str(content).map_err(|e| e.to_string())?;
+        if file.format_version != CHROMOSOME_FORMAT_VERSION {
+            return Err(format!("unsupported format_version {}, expected {}",
+                               file.format_version, CHROMOSOME_FORMAT_VERSION));
+        }
+
+        // function ids of the file -> function ids of `function_set`
+        let names = function_names(function_set);
+        let mut function_mapping: Vec<usize> = Vec::with_capacity(file.function_set.len());
+        for name in &file.function_set {
+            let function_id = names.iter()
+                .position(|x| x == name)
+                .ok_or_else(|| format!("function '{}' is not in the function set {:?}", name, names))?;
+            function_mapping.push(function_id);
+        }
+
+        let mut params = file.params;
+        if params.number_functions != file.function_set.len() {
+            return Err(format!("params.number_functions is {}, but function_set has {} functions",
+                               params.number_functions, file.function_set.len()));
+        }
+        params.number_functions = function_set.len();
+
+        let nbr_nodes = params.nbr_inputs + params.graph_width + params.nbr_outputs;
+        if file.nodes.len() != nbr_nodes {
+            return Err(format!("expected {} nodes, got {}", nbr_nodes, file.nodes.len()));
+        }
+
+        if let Some(linear_scaling) = &file.linear_scaling {
+            if linear_scaling.len() != params.nbr_outputs {
+                return Err(format!("expected a linear scaling for each of the {} outputs, got {}",
+                                   params.nbr_outputs, linear_scaling.len()));
+            }
+        }
+
+        let mut cgp_edges: Option<CGPEdges> = None;
+        if params.cgp_type == CGPType::DAG {
+            cgp_edges = Some(CGPEdges::new(params.nbr_inputs + params.graph_width));
+        }
+
+        let mut nodes_grid: Vec<CGPNode> = Vec::with_capacity(nbr_nodes);
+        for (position, record) in file.nodes.into_iter().enumerate() {
+            let node = node_from_record(record, position, &params, &function_mapping, &mut cgp_edges)
+                .map_err(|e| format!("node {}: {}", position, e))?;
+            nodes_grid.push(node);
+        }
+
+        Ok(Self {
+            params,
+            nodes_grid,
+            active_nodes: vec![],
+            cgp_edges,
+            linear_scaling: file.linear_scaling,
+        })
+    }
+}
+
+fn node_from_record(record: NodeRecord,
+                    position: usize,
+                    params: &CgpParameters,
+                    function_mapping: &[usize],
+                    cgp_edges: &mut Option<CGPEdges>) -> Result<CGPNode, String> {
+    if record.position != position {
+        return Err(format!("position is {}", record.position));
+    }
+
+    let node_type = if position < params.nbr_inputs {
+        NodeType::InputNode
+    } else if position < params.nbr_inputs + params.graph_width {
+        NodeType::ComputationalNode
+    } else {
+        NodeType::OutputNode
+    };
+    if record.node_type != node_type {
+        return Err(format!("node_type must be {}, got {}", node_type, record.node_type));
+    }
+
+    let required = |gene: Option<usize>, name: &str| gene.ok_or_else(|| format!("{} is missing", name));
+    let unused = |gene: Option<usize>, name: &str| match gene {
+        Some(_) => Err(format!("{} is not used by a node of type {}", name, node_type)),
+        None => Ok(usize::MAX),
+    };
+    // DAG graphs may connect to any input or computational node but themselves
+    let connection_range = |position: usize| {
+        if params.cgp_type == CGPType::DAG { params.nbr_inputs + params.graph_width } else { position }
+    };
+    let check_connection = |connection: usize, name: &str| {
+        if connection >= connection_range(position) || connection == position {
+            return Err(format!("{} {} is out of range", name, connection));
+        }
+        Ok(connection)
+    };
+
+    let has_constant = node_type == NodeType::ComputationalNode && params.constants.is_some();
+    let constant = match record.constant {
+        None if !has_constant => 0.,
+        None => return Err("constant is missing".to_string()),
+        Some(_) if node_type != NodeType::ComputationalNode => return Err(format!("constant is not used by a node of type {}", node_type)),
+        Some(_) if !has_constant => return Err("constant is not used without params.constants".to_string()),
+        Some(constant) if !constant.is_finite() => return Err(format!("constant {} is not finite", constant)),
+        Some(constant) => constant,
+    };
+
+    let (function_id, connection0, connection1) = match node_type {
+        NodeType::InputNode => (unused(record.function_id, "function_id")?,
+                                unused(record.connection0, "connection0")?,
+                                unused(record.connection1, "connection1")?),
+        NodeType::ComputationalNode => {
+            let function_id = required(record.function_id, "function_id")?;
+            let function_id = *function_mapping.get(function_id)
+                .ok_or_else(|| format!("function_id {} is out of range", function_id))?;
+            let connection0 = check_connection(required(record.connection0, "connection0")?, "connection0")?;
+            let connection1 = check_connection(required(record.connection1, "connection1")?, "connection1")?;
+
+            if let Some(edges) = cgp_edges.as_mut() {
+                for connection in [connection0, connection1] {
+                    if edges.leads_to_cycle(position, connection) {
+                        return Err(format!("connection {} leads to a cycle", connection));
+                    }
+                    edges.add_edge(position, connection);
+                }
+            }
+            (function_id, connection0, connection1)
+        }
+        NodeType::OutputNode => {
+            let connection0 = required(record.connection0, "connection0")?;
+            if connection0 >= params.nbr_inputs + params.graph_width {
+                return Err(format!("connection0 {} is out of range", connection0));
+            }
+            (unused(record.function_id, "function_id")?,
+             connection0,
+             unused(record.connection1, "connection1")?)
+        }
+    };
+
+    Ok(CGPNode {
+        position,
+        node_type,
+        nbr_inputs: params.nbr_inputs,
+        graph_width: params.graph_width,
+        function_id,
+        connection0,
+        connection1,
+        number_functions: params.number_functions,
+        constant,
+        constants: params.constants,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::cgp_components::chromosome_evaluator_operators::{ChromosomeEvaluator, DatasetId, EvaluateChromosomeTrait};
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesDAG, ChromosomeFindActiveNodesStandard};
+    use crate::datasets::boolean_datasets;
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::function_set::constant::with_constant;
+    use crate::function_set::f64_function_set::get_f64_function_set;
+    use crate::function_set::regression_function_set::get_regression_function_set;
+    use crate::global_params::ConstantParameters;
+    use crate::utils::utility_funcs::transpose;
+
+    fn params(cgp_type: CGPType) -> CgpParameters {
+        CgpParameters { cgp_type, fitness_threshold: 0.01, ..CgpParameters::for_tests(50, 6, 6, 4) }
+    }
+
+    fn assert_same_genome(a: &Chromosome, b: &Chromosome) {
+        assert_eq!(a.nodes_grid.len(), b.nodes_grid.len());
+        for (x, y) in a.nodes_grid.iter().zip(b.nodes_grid.iter()) {
+            assert_eq!(x.position, y.position);
+            assert!(x.node_type == y.node_type);
+            assert_eq!(x.function_id, y.function_id);
+            assert_eq!(x.connection0, y.connection0);
+            assert_eq!(x.connection1, y.connection1);
+            assert_eq!(x.constant, y.constant);
+        }
+    }
+
+    fn fitness(chromosome: &mut Chromosome, active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<bool>>>) -> f32 {
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        ChromosomeEvaluator::<bool>::new().evaluate(chromosome, active_node_func, DatasetId::Train, &transpose(data), &label, get_boolean_function_set())
+    }
+
+    #[test]
+    fn round_trip_standard() {
+        let function_set = get_boolean_function_set();
+        let mut chromosome = Chromosome::new(params(CGPType::Standard), &mut ChaCha8Rng::seed_from_u64(0));
+
+        let content = chromosome.to_genome_toml(&function_set);
+        let mut loaded = Chromosome::from_genome_toml(&content, &function_set).unwrap();
+
+        assert_same_genome(&chromosome, &loaded);
+        assert_eq!(content, loaded.to_genome_toml(&function_set));
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<bool>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+        assert_eq!(fitness(&mut chromosome, Arc::clone(&active_node_func)), fitness(&mut loaded, active_node_func));
+    }
+
+    #[test]
+    fn round_trip_keeps_linear_scaling() {
+        let function_set = get_boolean_function_set();
+        let mut chromosome = Chromosome::new(params(CGPType::Standard), &mut ChaCha8Rng::seed_from_u64(0));
+        let scaling: Vec<LinearScaling> = (0..chromosome.params.nbr_outputs)
+            .map(|i| LinearScaling { intercept: i as f64 - 0.5, slope: 1. / 3. })
+            .collect();
+        chromosome.linear_scaling = Some(scaling.clone());
+
+        let loaded = Chromosome::from_genome_toml(&chromosome.to_genome_toml(&function_set), &function_set).unwrap();
+        assert_eq!(Some(scaling), loaded.linear_scaling);
+
+        chromosome.linear_scaling.as_mut().unwrap().pop();
+        let error = Chromosome::from_genome_toml(&chromosome.to_genome_toml(&function_set), &function_set).err().unwrap();
+        assert!(error.contains("linear scaling"), "{}", error);
+    }
+
+    #[test]
+    fn round_trip_keeps_constants() {
+        let function_set = with_constant(get_f64_function_set()).unwrap();
+        let mut params = params(CGPType::Standard);
+        params.number_functions = function_set.len();
+        params.constants = Some(ConstantParameters { min: -1., max: 1., sigma: 0.1 });
+        let chromosome = Chromosome::new(params, &mut ChaCha8Rng::seed_from_u64(0));
+
+        let content = chromosome.to_genome_toml(&function_set);
+        let loaded = Chromosome::from_genome_toml(&content, &function_set).unwrap();
+        assert_same_genome(&chromosome, &loaded);
+        assert_eq!(content, loaded.to_genome_toml(&function_set));
+
+        let mut file: ChromosomeFile = toml::from_str(&content).unwrap();
+        file.nodes[6].constant = None;
+        let error = Chromosome::from_genome_toml(&toml::to_string(&file).unwrap(), &function_set).err().unwrap();
+        assert!(error.contains("node 6: constant is missing"), "{}", error);
+    }
+
+    #[test]
+    fn round_trip_dag_rebuilds_edges() {
+        let function_set = get_boolean_function_set();
+        let mut chromosome = Chromosome::new(params(CGPType::DAG), &mut ChaCha8Rng::seed_from_u64(1));
+
+        let mut loaded = Chromosome::from_genome_toml(&chromosome.to_genome_toml(&function_set), &function_set).unwrap();
+
+        assert_same_genome(&chromosome, &loaded);
+        assert!(loaded.cgp_edges.is_some());
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<bool>>> = Arc::new(ChromosomeFindActiveNodesDAG::new());
+        assert_eq!(fitness(&mut chromosome, Arc::clone(&active_node_func)), fitness(&mut loaded, active_node_func));
+    }
+
+    #[test]
+    fn round_trip_file() {
+        let function_set = get_boolean_function_set();
+        let chromosome = Chromosome::new(params(CGPType::Standard), &mut ChaCha8Rng::seed_from_u64(2));
+        let path = std::env::temp_dir().join(format!("cgp_genome_{}.toml", std::process::id()));
+
+        chromosome.save(&path, &function_set).unwrap();
+        let loaded = Chromosome::load(&path, &function_set);
+        fs::remove_file(&path).unwrap();
+
+        assert_same_genome(&chromosome, &loaded.unwrap());
+    }
+
+    #[test]
+    fn functions_are_mapped_by_name() {
+        let function_set = get_boolean_function_set();
+        let chromosome = Chromosome::new(params(CGPType::Standard), &mut ChaCha8Rng::seed_from_u64(3));
+        let content = chromosome.to_genome_toml(&function_set)
+            .replace(r#"function_set = ["and", "or", "nand", "nor"]"#, r#"function_set = ["or", "and", "nand", "nor"]"#);
+
+        let loaded = Chromosome::from_genome_toml(&content, &function_set).unwrap();
+
+        for (x, y) in chromosome.nodes_grid.iter().zip(loaded.nodes_grid.iter()) {
+            let expected = match x.function_id {
+                0 => 1,
+                1 => 0,
+                other => other,
+            };
+            assert_eq!(expected, y.function_id);
+        }
+    }
+
+    #[test]
+    fn rejects_f32_genome_in_f64_function_set() {
+        let params = CgpParameters { number_functions: 8, ..params(CGPType::Standard) };
+        let chromosome = Chromosome::new(params, &mut ChaCha8Rng::seed_from_u64(3));
+        let content = chromosome.to_genome_toml(&get_regression_function_set());
+
+        let error = Chromosome::from_genome_toml(&content, &get_f64_function_set()).err().unwrap();
+
+        assert!(error.contains("function 'plus' is not in the function set"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_other_format_version() {
+        let function_set = get_boolean_function_set();
+        let chromosome = Chromosome::new(params(CGPType::Standard), &mut ChaCha8Rng::seed_from_u64(4));
+        let content = chromosome.to_genome_toml(&function_set)
+            .replace("format_version = 1", "format_version = 2");
+
+        let error = Chromosome::from_genome_toml(&content, &function_set).err().unwrap();
+
+        assert!(error.contains("format_version"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_forward_connection_in_standard_graph() {
+        let function_set = get_boolean_function_set();
+        let chromosome = Chromosome::new(params(CGPType::Standard), &mut ChaCha8Rng::seed_from_u64(5));
+        let mut file: ChromosomeFile = toml::from_str(&chromosome.to_genome_toml(&function_set)).unwrap();
+        file.nodes[6].connection0 = Some(7);
+
+        let error = Chromosome::from_genome_toml(&toml::to_string(&file).unwrap(), &function_set).err().unwrap();
+
+        assert!(error.contains("node 6"), "{}", error);
+    }
+}
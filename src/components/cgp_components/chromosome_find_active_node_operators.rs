@@ -1,5 +1,5 @@
 use std::collections::HashSet;
-use std::rc::Rc;
+use std::sync::Arc;
 use petgraph::algo::toposort;
 use petgraph::graph::NodeIndex;
 use petgraph::prelude::StableGraph;
@@ -12,9 +12,10 @@ use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::utility_funcs;
 
 
-pub trait ChromosomeActiveNodeTrait<T> {
+pub trait ChromosomeActiveNodeTrait<T>: Send + Sync {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn ChromosomeActiveNodeTrait<T>> where Self: Sized;
-    fn execute(&self, chromosome: &mut Chromosome, function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>);
+    fn execute(&self, chromosome: &mut Chromosome, function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>);
 }
 
 
@@ -28,7 +29,7 @@ impl<T> ChromosomeActiveNodeTrait<T> for ChromosomeFindActiveNodesStandard {
         Box::new(Self)
     }
 
-    fn execute(&self, chromosome: &mut Chromosome, function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
+    fn execute(&self, chromosome: &mut Chromosome, function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) {
         let params = &chromosome.params;
 
         let mut active: HashSet<usize, BuildNoHashHasher<usize>> = HashSet::with_capacity_and_hasher(
@@ -36,8 +37,7 @@ impl<T> ChromosomeActiveNodeTrait<T> for ChromosomeFindActiveNodesStandard {
             BuildNoHashHasher::default(),
         );
 
-        let mut to_visit: Vec<usize> = vec![];
-        to_visit.reserve(params.nbr_inputs + params.graph_width + params.nbr_outputs);
+        let mut to_visit: Vec<usize> = Vec::with_capacity(params.nbr_inputs + params.graph_width + params.nbr_outputs);
 
         // Iterate through all output node ID's
         for output_node_id in params.nbr_inputs + params.graph_width
@@ -55,18 +55,12 @@ impl<T> ChromosomeActiveNodeTrait<T> for ChromosomeFindActiveNodesStandard {
                 NodeType::InputNode => continue,
 
                 NodeType::ComputationalNode => {
-                    let connection0 = current_node.connection0;
-                    if !active.contains(&connection0) {
-                        to_visit.push(connection0);
-                        active.insert(connection0);
-                    }
-
+                    // constant functions have no connections
                     let inputs_needed = function_set[current_node.function_id].get_number_inputs_needed();
-                    if inputs_needed == 2 {
-                        let connection1 = current_node.connection1;
-                        if !active.contains(&connection1) {
-                            to_visit.push(connection1);
-                            active.insert(connection1);
+                    for connection in [current_node.connection0, current_node.connection1].into_iter().take(inputs_needed) {
+                        if !active.contains(&connection) {
+                            to_visit.push(connection);
+                            active.insert(connection);
                         }
                     }
                 }
@@ -91,7 +85,7 @@ impl<T> ChromosomeActiveNodeTrait<T> for ChromosomeFindActiveNodesDAG {
     fn new() -> Box<dyn ChromosomeActiveNodeTrait<T>> where Self: Sized {
         Box::new(Self)
     }
-    fn execute(&self, chromosome: &mut Chromosome, function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
+    fn execute(&self, chromosome: &mut Chromosome, function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) {
         let total_node_count = chromosome.params.nbr_inputs + chromosome.params.graph_width + chromosome.params.nbr_outputs;
 
         // save active nodes here
@@ -99,16 +93,14 @@ impl<T> ChromosomeActiveNodeTrait<T> for ChromosomeFindActiveNodesDAG {
         active.reserve(total_node_count);
 
         // helper vec; which nodes must be currently visited?
-        let mut to_visit: Vec<usize> = vec![];
-        to_visit.reserve(total_node_count);
+        let mut to_visit: Vec<usize> = Vec::with_capacity(total_node_count);
 
         // create a graph to be able to do a topological sort later
         // topo_sort is necessary, because connections can be forward and backwards
         let mut graph = StableGraph::<usize, ()>::new();
 
         // Fill graph with nodes
-        let mut nodes: Vec<NodeIndex> = vec![];
-        nodes.reserve(total_node_count);
+        let mut nodes: Vec<NodeIndex> = Vec::with_capacity(total_node_count);
         for i in 0..total_node_count {
             nodes.push(graph.add_node(i));
         }
@@ -125,22 +117,14 @@ impl<T> ChromosomeActiveNodeTrait<T> for ChromosomeFindActiveNodesDAG {
                 NodeType::InputNode => continue,
 
                 NodeType::ComputationalNode => {
-                    let connection0 = current_node.connection0;
-                    graph.add_edge(nodes[connection0], nodes[current_node.position], ());
-
-                    if !active.contains(&connection0) {
-                        to_visit.push(connection0);
-                        active.insert(connection0);
-                    }
-
+                    // constant functions have no connections
                     let inputs_needed = function_set[current_node.function_id].get_number_inputs_needed();
-                    if inputs_needed == 2 {
-                        let connection1 = current_node.connection1;
-                        graph.add_edge(nodes[connection1], nodes[current_node.position], ());
+                    for connection in [current_node.connection0, current_node.connection1].into_iter().take(inputs_needed) {
+                        graph.add_edge(nodes[connection], nodes[current_node.position], ());
 
-                        if !active.contains(&connection1) {
-                            to_visit.push(connection1);
-                            active.insert(connection1);
+                        if !active.contains(&connection) {
+                            to_visit.push(connection);
+                            active.insert(connection);
                         }
                     }
                 }
@@ -157,8 +141,8 @@ impl<T> ChromosomeActiveNodeTrait<T> for ChromosomeFindActiveNodesDAG {
             }
         }
 
-        let inactive_nodes = (0..(chromosome.params.nbr_inputs + chromosome.params.graph_width)).collect();
-        let inactive_nodes = utility_funcs::vect_difference(&inactive_nodes, &active.into_iter().collect());
+        let inactive_nodes: Vec<usize> = (0..(chromosome.params.nbr_inputs + chromosome.params.graph_width)).collect();
+        let inactive_nodes = utility_funcs::vect_difference(&inactive_nodes, &active.into_iter().collect::<Vec<usize>>());
 
         for i in inactive_nodes {
             graph.remove_node(nodes[i]);
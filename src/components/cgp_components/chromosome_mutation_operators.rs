@@ -1,16 +1,106 @@
-use std::fs::File;
 use std::rc::Rc;
 use rand::distributions::Uniform;
 use rand::prelude::Distribution;
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use crate::components::cgp_components::cgp_node::CGPNode;
 use crate::components::cgp_components::cgp_node_mutation_operators::NodeMutationOperatorTrait;
 use crate::components::cgp_components::cgp_types::CGPType;
 use crate::components::cgp_components::chromosome::Chromosome;
-use std::io::{BufWriter, Write};
+
 pub trait ChromosomeMutationTrait {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn ChromosomeMutationTrait>
     where
         Self: Sized;
-    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, output_file: &mut BufWriter<File>);
+    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, rng: &mut ChaCha8Rng) -> MutationReport;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Gene {
+    Function,
+    Connection0,
+    Connection1,
+    Constant,
+}
+
+/// Value of a gene: function ids and connections are indices, constants are numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum GeneValue {
+    Index(usize),
+    Constant(f64),
+}
+
+/// A single mutated gene.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct NodeMutation {
+    pub node_id: usize,
+    pub gene: Gene,
+    pub old_value: GeneValue,
+    pub new_value: GeneValue,
+    // the node was active before the mutation
+    pub active: bool,
+}
+
+/// All mutations of a chromosome in the order they happened; a node that is mutated twice is
+/// listed twice.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct MutationReport {
+    pub mutations: Vec<NodeMutation>,
+}
+
+impl MutationReport {
+    pub fn len(&self) -> usize {
+        self.mutations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mutations.is_empty()
+    }
+
+    pub fn mutated_nodes(&self) -> Vec<usize> {
+        self.mutations.iter().map(|mutation| mutation.node_id).collect()
+    }
+
+    pub fn nbr_active_mutations(&self) -> usize {
+        self.mutations.iter().filter(|mutation| mutation.active).count()
+    }
+}
+
+fn genes(node: &CGPNode) -> [(Gene, GeneValue); 4] {
+    [(Gene::Function, GeneValue::Index(node.function_id)),
+        (Gene::Connection0, GeneValue::Index(node.connection0)),
+        (Gene::Connection1, GeneValue::Index(node.connection1)),
+        (Gene::Constant, GeneValue::Constant(node.constant))]
+}
+
+/// Mutates a single node with `mutate_function` and returns the mutated gene. Returns `None` if
+/// the node has no alternative value, i.e. a function set with a single function.
+fn mutate_node(chromosome: &mut Chromosome, mutate_function: &Rc<Box<dyn NodeMutationOperatorTrait>>, node_id: usize, rng: &mut ChaCha8Rng) -> Option<NodeMutation> {
+    let before = genes(&chromosome.nodes_grid[node_id]);
+
+    if chromosome.params.cgp_type == CGPType::DAG {
+        mutate_function.mutate_dag(&mut chromosome.nodes_grid[node_id], chromosome.cgp_edges.as_mut().unwrap(), rng);
+    } else {
+        mutate_function.mutate_standard(&mut chromosome.nodes_grid[node_id], rng);
+    }
+
+    // node mutations change at most one gene
+    let after = genes(&chromosome.nodes_grid[node_id]);
+    let (gene, old_value, new_value) = before.iter()
+        .zip(after.iter())
+        .find(|(old, new)| old.1 != new.1)
+        .map(|(old, new)| (old.0, old.1, new.1))?;
+
+    return Some(NodeMutation {
+        node_id,
+        gene,
+        old_value,
+        new_value,
+        active: chromosome.active_nodes.contains(&node_id),
+    });
 }
 
 pub struct ChromosomeMutationSingle;
@@ -30,31 +120,25 @@ impl ChromosomeMutationTrait for ChromosomeMutationSingle {
         Box::new(Self)
     }
 
-    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, output_file: &mut BufWriter<File>) {
+    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, rng: &mut ChaCha8Rng) -> MutationReport {
         let start_id = chromosome.params.nbr_inputs;
         let end_id = chromosome.params.nbr_inputs + chromosome.params.graph_width + chromosome.params.nbr_outputs;
 
         let between = Uniform::from(start_id..end_id);
-        let mut rng = rand::thread_rng();
 
-        let mut mutated_nodes: Vec<usize> = Vec::with_capacity(128);
+        let mut report = MutationReport::default();
 
         loop {
-            let random_node_id = between.sample(&mut rng);
+            let random_node_id = between.sample(rng);
 
-            mutated_nodes.push(random_node_id);
+            let active = chromosome.active_nodes.contains(&random_node_id);
+            report.mutations.extend(mutate_node(chromosome, &mutate_function, random_node_id, rng));
 
-            if chromosome.params.cgp_type == CGPType::DAG {
-                mutate_function.mutate_dag(&mut chromosome.nodes_grid[random_node_id], chromosome.cgp_edges.as_mut().unwrap());
-            } else {
-                mutate_function.mutate_standard(&mut chromosome.nodes_grid[random_node_id]);
-            }
-            if chromosome.active_nodes.contains(&random_node_id) {
+            if active {
                 break;
             }
         }
-        writeln!(*output_file, "{:?}", mutated_nodes.len()).expect("write not okay??");
-
+        return report;
     }
 }
 
@@ -66,23 +150,21 @@ impl ChromosomeMutationTrait for ChromosomeMutationPoint {
         Box::new(Self)
     }
 
-    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, output_file: &mut BufWriter<File>) {
+    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, rng: &mut ChaCha8Rng) -> MutationReport {
         let start_id = chromosome.params.nbr_inputs;
         let end_id = chromosome.params.nbr_inputs + chromosome.params.graph_width + chromosome.params.nbr_outputs;
 
-        let mut rng = rand::thread_rng();
         let between = Uniform::new(0., 1.);
 
+        let mut report = MutationReport::default();
+
         for node_id in start_id..end_id {
-            let rand_val = between.sample(&mut rng);
+            let rand_val = between.sample(rng);
             if rand_val <= chromosome.params.mutation_rate {
-                if chromosome.params.cgp_type == CGPType::DAG {
-                    mutate_function.mutate_dag(&mut chromosome.nodes_grid[node_id], chromosome.cgp_edges.as_mut().unwrap());
-                } else {
-                    mutate_function.mutate_standard(&mut chromosome.nodes_grid[node_id]);
-                }
+                report.mutations.extend(mutate_node(chromosome, &mutate_function, node_id, rng));
             }
         }
+        return report;
     }
 }
 
@@ -94,37 +176,29 @@ impl ChromosomeMutationTrait for ChromosomeMutationMultiN {
         Box::new(Self)
     }
 
-    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, output_file: &mut BufWriter<File>) {
+    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, rng: &mut ChaCha8Rng) -> MutationReport {
         let start_id = chromosome.params.nbr_inputs;
         let end_id = chromosome.params.nbr_inputs + chromosome.params.graph_width + chromosome.params.nbr_outputs;
 
         let between = Uniform::from(start_id..end_id);
-        let mut rng = rand::thread_rng();
 
         let mut mutated_active_nodes_counter = 0;
 
-        let mut mutated_nodes: Vec<usize> = Vec::with_capacity(128);
+        let mut report = MutationReport::default();
 
         loop {
-            let random_node_id = between.sample(&mut rng);
-            mutated_nodes.push(random_node_id);
-
-            if chromosome.params.cgp_type == CGPType::DAG {
-                mutate_function.mutate_dag(&mut chromosome.nodes_grid[random_node_id], chromosome.cgp_edges.as_mut().unwrap());
-            } else {
-                mutate_function.mutate_standard(&mut chromosome.nodes_grid[random_node_id]);
-            }
+            let random_node_id = between.sample(rng);
 
             if chromosome.active_nodes.contains(&random_node_id) {
                 mutated_active_nodes_counter += 1;
             }
+            report.mutations.extend(mutate_node(chromosome, &mutate_function, random_node_id, rng));
 
             if mutated_active_nodes_counter >= chromosome.params.multi_n_number_mutations {
                 break;
             }
         }
-        writeln!(output_file, "{:?}", mutated_nodes.len()).expect("write not okay??");
-
+        return report;
     }
 }
 
@@ -136,15 +210,16 @@ impl ChromosomeMutationTrait for ChromosomeMutationSplit {
         Box::new(Self)
     }
 
-    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, output_file: &mut BufWriter<File>) {
+    fn execute(&self, chromosome: &mut Chromosome, mutate_function: Rc<Box<dyn NodeMutationOperatorTrait>>, rng: &mut ChaCha8Rng) -> MutationReport {
         let start_id = chromosome.params.nbr_inputs;
         let end_id = chromosome.params.nbr_inputs + chromosome.params.graph_width + chromosome.params.nbr_outputs;
 
-        let mut rng = rand::thread_rng();
         let between = Uniform::new(0., 1.);
 
+        let mut report = MutationReport::default();
+
         for node_id in start_id..end_id {
-            let rand_val = between.sample(&mut rng);
+            let rand_val = between.sample(rng);
 
             let mut mutate_flag = false;
             // Check if a node is active or not
@@ -159,12 +234,146 @@ impl ChromosomeMutationTrait for ChromosomeMutationSplit {
             }
 
             if mutate_flag {
-                if chromosome.params.cgp_type == CGPType::DAG {
-                    mutate_function.mutate_dag(&mut chromosome.nodes_grid[node_id], chromosome.cgp_edges.as_mut().unwrap());
-                } else {
-                    mutate_function.mutate_standard(&mut chromosome.nodes_grid[node_id]);
+                report.mutations.extend(mutate_node(chromosome, &mutate_function, node_id, rng));
+            }
+        }
+        return report;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use super::*;
+    use crate::components::cgp_components::cgp_node_mutation_operators::{NodeMutationDAG, NodeMutationStandard};
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesStandard};
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::global_params::{CgpParameters, ConstantParameters};
+
+    fn chromosome(cgp_type: CGPType, rng: &mut ChaCha8Rng) -> Chromosome {
+        return chromosome_with_constants(cgp_type, None, rng);
+    }
+
+    fn chromosome_with_constants(cgp_type: CGPType, constants: Option<ConstantParameters>, rng: &mut ChaCha8Rng) -> Chromosome {
+        let params = CgpParameters {
+            cgp_type,
+            mutation_rate: 1.,
+            multi_n_number_mutations: 3,
+            split_mutation_rate_active: 1.,
+            constants,
+            ..CgpParameters::for_tests(50, 6, 6, 4)
+        };
+        let mut chromosome = Chromosome::new(params, rng);
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<bool>> = ChromosomeFindActiveNodesStandard::new();
+        active_node_func.execute(&mut chromosome, get_boolean_function_set());
+        return chromosome;
+    }
+
+    /// Every mutation of the report is visible in the chromosome.
+    fn assert_report_matches(before: &Chromosome, after: &Chromosome, report: &MutationReport) {
+        let mut expected = before.nodes_grid.clone();
+        for mutation in &report.mutations {
+            let node = &mut expected[mutation.node_id];
+            match (mutation.gene, mutation.old_value, mutation.new_value) {
+                (Gene::Constant, GeneValue::Constant(old_value), GeneValue::Constant(new_value)) => {
+                    assert_eq!(node.constant, old_value);
+                    node.constant = new_value;
+                }
+                (gene, GeneValue::Index(old_value), GeneValue::Index(new_value)) => {
+                    let gene = match gene {
+                        Gene::Function => &mut node.function_id,
+                        Gene::Connection0 => &mut node.connection0,
+                        Gene::Connection1 => &mut node.connection1,
+                        Gene::Constant => panic!("constants are not indices"),
+                    };
+                    assert_eq!(*gene, old_value);
+                    *gene = new_value;
                 }
+                _ => panic!("{:?} has values of different kinds", mutation),
             }
+            assert_eq!(before.active_nodes.contains(&mutation.node_id), mutation.active);
         }
+        for (x, y) in expected.iter().zip(after.nodes_grid.iter()) {
+            assert_eq!((x.function_id, x.connection0, x.connection1, x.constant), (y.function_id, y.connection0, y.connection1, y.constant));
+        }
+    }
+
+    #[test]
+    fn single_mutation_stops_at_first_active_node() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(0);
+        let mut chromosome = chromosome(CGPType::Standard, rng);
+        let before = chromosome.clone();
+
+        let report = ChromosomeMutationSingle.execute(&mut chromosome, Rc::new(NodeMutationStandard::new()), rng);
+
+        assert_eq!(1, report.nbr_active_mutations());
+        assert!(report.mutations.last().unwrap().active);
+        assert_report_matches(&before, &chromosome, &report);
+    }
+
+    #[test]
+    fn multi_mutation_mutates_n_active_nodes() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(1);
+        let mut chromosome = chromosome(CGPType::Standard, rng);
+        let before = chromosome.clone();
+
+        let report = ChromosomeMutationMultiN.execute(&mut chromosome, Rc::new(NodeMutationStandard::new()), rng);
+
+        assert_eq!(3, report.nbr_active_mutations());
+        assert_report_matches(&before, &chromosome, &report);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn point_mutation_with_rate_one_mutates_every_node() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(2);
+        let mut chromosome = chromosome(CGPType::Standard, rng);
+        let before = chromosome.clone();
+
+        let report = ChromosomeMutationPoint.execute(&mut chromosome, Rc::new(NodeMutationStandard::new()), rng);
+
+        assert_eq!((6..62).collect::<Vec<usize>>(), report.mutated_nodes());
+        assert_report_matches(&before, &chromosome, &report);
+    }
+
+    #[test]
+    fn split_mutation_only_mutates_active_nodes() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(3);
+        let mut chromosome = chromosome(CGPType::Standard, rng);
+        let before = chromosome.clone();
+
+        let report = ChromosomeMutationSplit.execute(&mut chromosome, Rc::new(NodeMutationStandard::new()), rng);
+
+        assert_eq!(report.len(), report.nbr_active_mutations());
+        assert_eq!(before.active_nodes.iter().filter(|id| **id >= 6).count(), report.len());
+        assert_report_matches(&before, &chromosome, &report);
+    }
+
+    #[test]
+    fn reports_mutations_of_dag_graphs() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(4);
+        let mut chromosome = chromosome(CGPType::DAG, rng);
+        let before = chromosome.clone();
+
+        let report = ChromosomeMutationPoint.execute(&mut chromosome, Rc::new(NodeMutationDAG::new()), rng);
+
+        assert_eq!(56, report.len());
+        assert_report_matches(&before, &chromosome, &report);
+    }
+
+    #[test]
+    fn reports_mutations_of_constants() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(5);
+        let constants = ConstantParameters { min: -1., max: 1., sigma: 0.5 };
+        for (cgp_type, node_mutation) in [(CGPType::Standard, NodeMutationStandard::new()), (CGPType::DAG, NodeMutationDAG::new())] {
+            let mut chromosome = chromosome_with_constants(cgp_type, Some(constants), rng);
+            assert!(chromosome.nodes_grid[6..56].iter().all(|node| (-1. ..=1.).contains(&node.constant)));
+            let before = chromosome.clone();
+
+            let report = ChromosomeMutationPoint.execute(&mut chromosome, Rc::new(node_mutation), rng);
+
+            assert!(report.mutations.iter().any(|mutation| mutation.gene == Gene::Constant));
+            assert_report_matches(&before, &chromosome, &report);
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use std::collections::{HashMap, HashSet};
-use std::rc::Rc;
+use std::sync::Arc;
 use nohash_hasher::BuildNoHashHasher;
 use rand::distributions::{Distribution, Uniform};
 use statrs::distribution::Beta;
 use rand::prelude::IteratorRandom;
-use rand::thread_rng;
+use rand_chacha::ChaCha8Rng;
 use crate::utils::linspace::linspace;
 use crate::components::cgp_components::cgp_node::CGPNode;
 use crate::components::cgp_components::chromosome::Chromosome;
@@ -13,11 +13,13 @@ use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::utility_funcs;
 
 pub trait ChromosomeReorderTrait<T> {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn ChromosomeReorderTrait<T>> where Self: Sized;
     fn execute(&self,
                chromosome: &mut Chromosome,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>);
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               rng: &mut ChaCha8Rng);
 
     /// Needed for every Reorder class but `ChromosomeReorderStandard`
     /// Updates the connection of a reordered CGP genotype.
@@ -31,9 +33,9 @@ pub trait ChromosomeReorderTrait<T> {
         let con2 = new_nodes_grid[node_id].connection1;
 
         new_nodes_grid[node_id].connection0 = *swapped_pos_indices.get(&con1)
-            .unwrap_or_else(|| { &con1 });
+            .unwrap_or(&con1);
         new_nodes_grid[node_id].connection1 = *swapped_pos_indices.get(&con2)
-            .unwrap_or_else(|| { &con2 });
+            .unwrap_or(&con2);
     }
 
     /// Needed for every Reorder class but `ChromosomeReorderStandard`
@@ -84,12 +86,13 @@ pub trait ChromosomeReorderTrait<T> {
                        chromosome: &mut Chromosome,
                        c_active_nodes: &Vec<usize>,
                        new_pos_active: &Vec<usize>,
-                       active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-                       function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>,
+                       active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                       function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+                       rng: &mut ChaCha8Rng,
     ) {
         let comp_nodes_ids: Vec<usize> = (chromosome.params.nbr_inputs..(chromosome.params.nbr_inputs + chromosome.params.graph_width)).collect();
-        let mut old_pos_inactive = utility_funcs::vect_difference(&comp_nodes_ids, &c_active_nodes);
-        let mut new_pos_inactive = utility_funcs::vect_difference(&comp_nodes_ids, &new_pos_active);
+        let mut old_pos_inactive = utility_funcs::vect_difference(&comp_nodes_ids, c_active_nodes);
+        let mut new_pos_inactive = utility_funcs::vect_difference(&comp_nodes_ids, new_pos_active);
 
         old_pos_inactive.sort_unstable();
         new_pos_inactive.sort_unstable();
@@ -122,7 +125,7 @@ pub trait ChromosomeReorderTrait<T> {
             // new spec is the following: position 2, connection1=2 -> this leads to a cicle
             // if this node mutates its function to something that uses two inputs (i.e. add)
             if node.connection1 >= *new_node_id {
-                node.connection1 = utility_funcs::gen_random_number_for_node(node.connection1, node.position)
+                node.connection1 = utility_funcs::gen_random_number_for_node(node.connection1, node.position, rng)
             }
 
             new_nodes_grid[*new_node_id] = node;
@@ -141,10 +144,10 @@ pub trait ChromosomeReorderTrait<T> {
             // position that is in front of it.
             node.position = *new_node_id;
             if node.connection0 >= *new_node_id {
-                node.connection0 = utility_funcs::gen_random_number_for_node(node.connection0, node.position)
+                node.connection0 = utility_funcs::gen_random_number_for_node(node.connection0, node.position, rng)
             }
             if node.connection1 >= *new_node_id {
-                node.connection1 = utility_funcs::gen_random_number_for_node(node.connection1, node.position)
+                node.connection1 = utility_funcs::gen_random_number_for_node(node.connection1, node.position, rng)
             }
             new_nodes_grid[*new_node_id] = node;
 
@@ -167,7 +170,7 @@ pub trait ChromosomeReorderTrait<T> {
         }
 
         chromosome.nodes_grid = new_nodes_grid;
-        active_node_func.execute(chromosome, Rc::clone(&function_set));
+        active_node_func.execute(chromosome, Arc::clone(&function_set));
     }
 }
 
@@ -188,10 +191,9 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderStandard {
     }
     fn execute(&self,
                chromosome: &mut Chromosome,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
-        let mut rng = thread_rng();
-
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               rng: &mut ChaCha8Rng) {
         let total_nbr_nodes = chromosome.params.nbr_inputs + chromosome.params.graph_width + chromosome.params.nbr_outputs;
 
         // !vars with names from the original paper and its description!
@@ -208,9 +210,9 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderStandard {
         let mut addable: Vec<usize> = vec![];
         self.get_addable(&mut node_dependencies, &mut addable);
 
-        while addable.len() > 0 {
+        while !addable.is_empty() {
             // current_node_id is also the position of the node in the grid
-            let i = (0..addable.len()).choose(&mut rng).unwrap();
+            let i = (0..addable.len()).choose(rng).unwrap();
             let current_node_id = addable.swap_remove(i);
 
             // map old location to new location
@@ -230,7 +232,7 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderStandard {
         self.update_node_index(chromosome, &changed_locations);
         self.update_node_connections(chromosome, &changed_locations);
 
-        active_node_func.execute(chromosome, Rc::clone(&function_set));
+        active_node_func.execute(chromosome, Arc::clone(&function_set));
 
         assert_eq!(changed_locations.len(), chromosome.params.graph_width);
         assert_eq!(used_node_indices.len(), chromosome.params.graph_width);
@@ -287,7 +289,7 @@ impl ChromosomeReorderStandard {
             addable.extend(temp_addable);
         }
 
-        node_dependencies.retain(|_, v| *v != []);
+        node_dependencies.retain(|_, v| !v.is_empty());
     }
 
     fn update_node_index(&self, chromosome: &mut Chromosome, location_mapping: &HashMap<usize, usize, BuildNoHashHasher<usize>>) {
@@ -329,8 +331,9 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderEquidistant {
     }
     fn execute(&self,
                chromosome: &mut Chromosome,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               rng: &mut ChaCha8Rng) {
 
         // Get active computational nodes
         let c_active_nodes: Option<Vec<usize>> = ChromosomeReorderTrait::<T>::private_get_active_computational_nodes(self, chromosome);
@@ -347,8 +350,9 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderEquidistant {
                                                      chromosome,
                                                      &c_active_nodes,
                                                      &new_pos_active,
-                                                     Rc::clone(&active_node_func),
-                                                     Rc::clone(&function_set));
+                                                     Arc::clone(&active_node_func),
+                                                     Arc::clone(&function_set),
+                                                     rng);
 
         // let comp_nodes_ids: Vec<usize> = (chromosome.params.nbr_inputs..(chromosome.params.nbr_inputs + chromosome.params.graph_width)).collect();
         // let mut old_pos_inactive = utility_funcs::vect_difference(&comp_nodes_ids, &c_active_nodes);
@@ -418,7 +422,7 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderEquidistant {
         // }
         //
         // chromosome.nodes_grid = new_nodes_grid;
-        // active_node_func.execute(chromosome, Rc::clone(&function_set));
+        // active_node_func.execute(chromosome, Arc::clone(&function_set));
     }
 }
 
@@ -428,8 +432,9 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderNegativeBias {
     }
     fn execute(&self,
                chromosome: &mut Chromosome,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               rng: &mut ChaCha8Rng) {
         // Get active computational nodes
         let c_active_nodes: Option<Vec<usize>> = ChromosomeReorderTrait::<T>::private_get_active_computational_nodes(self, chromosome);
         if c_active_nodes.is_none() {
@@ -445,8 +450,9 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderNegativeBias {
                                                      chromosome,
                                                      &c_active_nodes,
                                                      &new_pos_active,
-                                                     Rc::clone(&active_node_func),
-                                                     Rc::clone(&function_set));
+                                                     Arc::clone(&active_node_func),
+                                                     Arc::clone(&function_set),
+                                                     rng);
     }
 }
 
@@ -456,10 +462,9 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderNormalDistribution {
     }
     fn execute(&self,
                chromosome: &mut Chromosome,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
-        let mut rng = thread_rng();
-
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               rng: &mut ChaCha8Rng) {
         // Get active computational nodes
         let c_active_nodes: Option<Vec<usize>> = ChromosomeReorderTrait::<T>::private_get_active_computational_nodes(self, chromosome);
         if c_active_nodes.is_none() {
@@ -476,7 +481,7 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderNormalDistribution {
         // cannot draw c_active_nodes.len() random sample points because they could overlap.
         // Hence: Draw into set until desired number of nodes
         loop {
-            new_pos_active.insert(between.sample(&mut rng));
+            new_pos_active.insert(between.sample(rng));
             if new_pos_active.len() == c_active_nodes.len() {
                 break;
             }
@@ -489,8 +494,9 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderNormalDistribution {
                                                      chromosome,
                                                      &c_active_nodes,
                                                      &new_pos_active,
-                                                     Rc::clone(&active_node_func),
-                                                     Rc::clone(&function_set));
+                                                     Arc::clone(&active_node_func),
+                                                     Arc::clone(&function_set),
+                                                     rng);
     }
 }
 
@@ -500,10 +506,9 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderLeftSkewed {
     }
     fn execute(&self,
                chromosome: &mut Chromosome,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
-        let mut rng = thread_rng();
-
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               rng: &mut ChaCha8Rng) {
         // Get active computational nodes
         let c_active_nodes: Option<Vec<usize>> = ChromosomeReorderTrait::<T>::private_get_active_computational_nodes(self, chromosome);
         if c_active_nodes.is_none() {
@@ -529,7 +534,7 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderLeftSkewed {
         // Hence: Draw into set until desired number of nodes
         loop {
             // generate new value and shift it
-            let mut new_sample: f64 = beta_dis.sample(&mut rng);
+            let mut new_sample: f64 = beta_dis.sample(rng);
             new_sample *= scale_factor;
             new_sample += shift_factor;
 
@@ -546,7 +551,8 @@ impl<T> ChromosomeReorderTrait<T> for ChromosomeReorderLeftSkewed {
                                                      chromosome,
                                                      &c_active_nodes,
                                                      &new_pos_active,
-                                                     Rc::clone(&active_node_func),
-                                                     Rc::clone(&function_set));
+                                                     Arc::clone(&active_node_func),
+                                                     Arc::clone(&function_set),
+                                                     rng);
     }
 }
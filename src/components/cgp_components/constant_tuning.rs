@@ -0,0 +1,369 @@
+//! Gradient-based tuning of the constant genes, see `CGPNode::constant`, as in differentiable CGP
+//! (Izzo et al., 2017). The active graph of a chromosome is evaluated on dual numbers with one
+//! variable per active constant node. One forward pass gives the residuals of all outputs and rows
+//! and their derivatives with respect to every constant. A local optimizer then lowers the mean
+//! squared error of the outputs; see `TuneBestConstants` for how it is applied during a run.
+
+use crate::components::cgp_components::cgp_node_types::NodeType;
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::dual::Dual;
+use crate::utils::linear_scaling::to_f64;
+use crate::utils::numeric::Numeric;
+
+pub const CONSTANT_OPTIMIZERS: [&str; 2] = ["lm", "adam"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConstantOptimizer {
+    /// "lm": Levenberg–Marquardt on the residuals; the damping is adapted after every step.
+    LevenbergMarquardt,
+    /// "adam": Adam (Kingma and Ba, 2015) on the gradient of the mean squared error.
+    Adam { learning_rate: f64 },
+}
+
+impl ConstantOptimizer {
+    /// `learning_rate` is only used by Adam.
+    pub fn from_name(name: &str, learning_rate: f64) -> Result<Self, String> {
+        match name {
+            "lm" => Ok(Self::LevenbergMarquardt),
+            "adam" => Ok(Self::Adam { learning_rate }),
+            _ => Err(format!("unknown constant optimizer '{}', allowed: {:?}", name, CONSTANT_OPTIMIZERS)),
+        }
+    }
+}
+
+/// Ids of the active nodes with a function of 0 inputs, i.e. the constants the outputs depend on.
+pub fn active_constant_nodes<T>(chromosome: &Chromosome, function_set: &[Box<dyn FunctionTrait<T>>]) -> Vec<usize> {
+    return chromosome.active_nodes
+        .iter()
+        .copied()
+        .filter(|id| {
+            let node = &chromosome.nodes_grid[*id];
+            node.node_type == NodeType::ComputationalNode && function_set[node.function_id].get_number_inputs_needed() == 0
+        })
+        .collect();
+}
+
+/// Checks that every function with inputs can be evaluated on dual numbers, as
+/// `dual_forward_pass` relies on it; the constant nodes are variables instead.
+pub fn check_dual_functions<T>(function_set: &[Box<dyn FunctionTrait<T>>]) -> Result<(), String> {
+    let no_rows = Vec::new();
+    for function in function_set.iter().filter(|function| function.get_number_inputs_needed() > 0) {
+        let inputs = vec![&no_rows; function.get_number_inputs_needed()];
+        function.execute_dual_into(&inputs, &mut Vec::new())?;
+    }
+    return Ok(());
+}
+
+/// Outputs of `chromosome` on dual numbers, one vector per output node; the active nodes must be
+/// up to date. The node `constant_nodes[i]` has the constant `constants[i]` instead of its gene
+/// and is the variable `i`.
+pub fn dual_forward_pass<T: Numeric>(chromosome: &Chromosome,
+                                     constant_nodes: &[usize],
+                                     constants: &[f64],
+                                     inputs: &[Vec<T>],
+                                     function_set: &[Box<dyn FunctionTrait<T>>]) -> Vec<Vec<Dual>> {
+    let nbr_variables = constant_nodes.len();
+    let nbr_rows = inputs[0].len();
+    let output_start_id = chromosome.params.nbr_inputs + chromosome.params.graph_width;
+
+    let mut values: Vec<Vec<Dual>> = vec![Vec::new(); output_start_id];
+    for (id, input) in inputs.iter().enumerate() {
+        values[id] = input.iter().map(|x| Dual::constant(x.to_f64().unwrap(), nbr_variables)).collect();
+    }
+    for node_id in &chromosome.active_nodes {
+        let node = &chromosome.nodes_grid[*node_id];
+        if node.node_type != NodeType::ComputationalNode {
+            continue;
+        }
+
+        let function = &function_set[node.function_id];
+        let mut value = Vec::with_capacity(nbr_rows);
+        match function.get_number_inputs_needed() {
+            2 => function.execute_dual_into(&[&values[node.connection0], &values[node.connection1]], &mut value)
+                .expect("checked by check_dual_functions"),
+            1 => function.execute_dual_into(&[&values[node.connection0]], &mut value)
+                .expect("checked by check_dual_functions"),
+            _ => {
+                let index = constant_nodes.iter()
+                    .position(|id| id == node_id)
+                    .expect("every active constant node is a variable");
+                value.resize(nbr_rows, Dual::variable(constants[index], index, nbr_variables));
+            }
+        }
+        values[*node_id] = value;
+    }
+
+    return (output_start_id..output_start_id + chromosome.params.nbr_outputs)
+        .map(|output_id| values[chromosome.nodes_grid[output_id].connection0].clone())
+        .collect();
+}
+
+/// The residuals `output - label` of a chromosome as a function of its constants.
+struct Objective<'a, T> {
+    chromosome: &'a Chromosome,
+    constant_nodes: Vec<usize>,
+    inputs: &'a [Vec<T>],
+    labels: Vec<Vec<f64>>,
+    function_set: &'a [Box<dyn FunctionTrait<T>>],
+}
+
+impl<T: Numeric> Objective<'_, T> {
+    /// All residuals, output node by output node.
+    fn residuals(&self, constants: &[f64]) -> Vec<Dual> {
+        let outputs = dual_forward_pass(self.chromosome, &self.constant_nodes, constants, self.inputs, self.function_set);
+        return outputs.into_iter()
+            .zip(self.labels.iter())
+            .flat_map(|(output, label)| output.into_iter()
+                .zip(label.iter())
+                .map(|(mut residual, y)| {
+                    residual.value -= y;
+                    residual
+                })
+                .collect::<Vec<Dual>>())
+            .collect();
+    }
+}
+
+fn mean_squared_error(residuals: &[Dual]) -> f64 {
+    return residuals.iter().map(|r| r.value * r.value).sum::<f64>() / residuals.len() as f64;
+}
+
+/// Gradient of `mean_squared_error` with respect to the constants.
+fn gradient(residuals: &[Dual], nbr_constants: usize) -> Vec<f64> {
+    let mut gradient = vec![0.; nbr_constants];
+    for r in residuals {
+        gradient.iter_mut().zip(r.derivatives.iter()).for_each(|(g, d)| *g += 2. * r.value * d);
+    }
+    gradient.iter_mut().for_each(|g| *g /= residuals.len() as f64);
+    return gradient;
+}
+
+/// Tunes the constants of the active constant nodes of `chromosome` for the mean squared error of
+/// its outputs on `inputs`; the active nodes must be up to date. Runs `steps` steps of `optimizer`
+/// and keeps the constants with the lowest error, the initial ones included. Returns whether a
+/// constant changed; a chromosome without active constants or with a non-finite error is left as
+/// it is.
+pub fn tune_constants<T: Numeric>(chromosome: &mut Chromosome,
+                                  inputs: &[Vec<T>],
+                                  labels: &[Vec<T>],
+                                  function_set: &[Box<dyn FunctionTrait<T>>],
+                                  optimizer: &ConstantOptimizer,
+                                  steps: usize) -> bool {
+    let constant_nodes = active_constant_nodes(chromosome, function_set);
+    if constant_nodes.is_empty() {
+        return false;
+    }
+    let initial: Vec<f64> = constant_nodes.iter().map(|id| chromosome.nodes_grid[*id].constant).collect();
+
+    let objective = Objective { chromosome, constant_nodes: constant_nodes.clone(), inputs, labels: to_f64(labels), function_set };
+    let tuned = match optimizer {
+        ConstantOptimizer::LevenbergMarquardt => levenberg_marquardt(&objective, initial.clone(), steps),
+        ConstantOptimizer::Adam { learning_rate } => adam(&objective, initial.clone(), *learning_rate, steps),
+    };
+    if tuned == initial {
+        return false;
+    }
+
+    for (id, constant) in constant_nodes.iter().zip(tuned) {
+        chromosome.nodes_grid[*id].constant = constant;
+    }
+    return true;
+}
+
+fn levenberg_marquardt<T: Numeric>(objective: &Objective<T>, mut constants: Vec<f64>, steps: usize) -> Vec<f64> {
+    let mut residuals = objective.residuals(&constants);
+    let mut error = mean_squared_error(&residuals);
+    let mut damping = 1e-3;
+
+    for _ in 0..steps {
+        if !error.is_finite() {
+            break;
+        }
+        // (JᵀJ + damping * I) step = -Jᵀr, with the Jacobian J of the residuals r
+        let mut matrix = vec![vec![0.; constants.len()]; constants.len()];
+        let mut rhs = vec![0.; constants.len()];
+        for r in &residuals {
+            for (i, di) in r.derivatives.iter().enumerate() {
+                rhs[i] -= di * r.value;
+                matrix[i].iter_mut().zip(r.derivatives.iter()).for_each(|(x, dj)| *x += di * dj);
+            }
+        }
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] += damping;
+        }
+        let Some(step) = solve(matrix, rhs) else {
+            break;
+        };
+
+        let candidate: Vec<f64> = constants.iter().zip(step.iter()).map(|(c, s)| c + s).collect();
+        let candidate_residuals = objective.residuals(&candidate);
+        let candidate_error = mean_squared_error(&candidate_residuals);
+        // a NaN error is never accepted
+        if candidate_error < error && candidate.iter().all(|c| c.is_finite()) {
+            constants = candidate;
+            residuals = candidate_residuals;
+            error = candidate_error;
+            damping /= 10.;
+        } else {
+            damping *= 10.;
+        }
+    }
+    return constants;
+}
+
+fn adam<T: Numeric>(objective: &Objective<T>, mut constants: Vec<f64>, learning_rate: f64, steps: usize) -> Vec<f64> {
+    const BETA1: f64 = 0.9;
+    const BETA2: f64 = 0.999;
+    const EPSILON: f64 = 1e-8;
+
+    let mut residuals = objective.residuals(&constants);
+    let mut best_error = mean_squared_error(&residuals);
+    let mut best = constants.clone();
+    let mut first_moment = vec![0.; constants.len()];
+    let mut second_moment = vec![0.; constants.len()];
+
+    for step in 1..=steps {
+        if !mean_squared_error(&residuals).is_finite() {
+            break;
+        }
+        let gradient = gradient(&residuals, constants.len());
+        let first_correction = 1. - BETA1.powi(step as i32);
+        let second_correction = 1. - BETA2.powi(step as i32);
+        for ((c, g), (m, v)) in constants.iter_mut()
+            .zip(gradient.iter())
+            .zip(first_moment.iter_mut().zip(second_moment.iter_mut())) {
+            *m = BETA1 * *m + (1. - BETA1) * g;
+            *v = BETA2 * *v + (1. - BETA2) * g * g;
+            *c -= learning_rate * (*m / first_correction) / ((*v / second_correction).sqrt() + EPSILON);
+        }
+
+        residuals = objective.residuals(&constants);
+        let error = mean_squared_error(&residuals);
+        if error < best_error && constants.iter().all(|c| c.is_finite()) {
+            best_error = error;
+            best.clone_from(&constants);
+        }
+    }
+    return best;
+}
+
+/// Solves `matrix * x = rhs` by Gaussian elimination with partial pivoting; `None` if the matrix
+/// is singular.
+fn solve(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let n = rhs.len();
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|a, b| matrix[*a][column].abs().total_cmp(&matrix[*b][column].abs()))
+            .unwrap();
+        if matrix[pivot][column] == 0. || matrix[pivot][column].is_nan() {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        let (pivot_rows, rows) = matrix.split_at_mut(column + 1);
+        let pivot_row = &pivot_rows[column];
+        let (pivot_rhs, rhs_rows) = rhs.split_at_mut(column + 1);
+        for (row, value) in rows.iter_mut().zip(rhs_rows.iter_mut()) {
+            let factor = row[column] / pivot_row[column];
+            row.iter_mut().zip(pivot_row.iter()).skip(column).for_each(|(x, p)| *x -= factor * p);
+            *value -= factor * pivot_rhs[column];
+        }
+    }
+
+    let mut solution = vec![0.; n];
+    for row in (0..n).rev() {
+        let sum: f64 = matrix[row].iter().zip(solution.iter()).skip(row + 1).map(|(a, x)| a * x).sum();
+        solution[row] = (rhs[row] - sum) / matrix[row][row];
+    }
+    return Some(solution);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::cgp_components::chromosome_evaluator_operators::predict;
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesStandard};
+    use crate::function_set::constant::with_constant;
+    use crate::function_set::f64_function_set::get_f64_function_set;
+    use crate::function_set::integer_function_set::get_integer_function_set;
+    use crate::global_params::{CgpParameters, ConstantParameters};
+
+    fn params(graph_width: usize, number_functions: usize) -> CgpParameters {
+        CgpParameters {
+            constants: Some(ConstantParameters { min: -1., max: 1., sigma: 0.1 }),
+            ..CgpParameters::for_tests(graph_width, 1, 1, number_functions)
+        }
+    }
+
+    #[test]
+    fn dual_outputs_match_the_forward_pass() {
+        let function_set = with_constant(get_f64_function_set()).unwrap();
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<f64>> = ChromosomeFindActiveNodesStandard::new();
+        let inputs = vec![vec![-2., -0.5, 0., 0.3, 1.7]];
+        let rng = &mut ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..50 {
+            let mut chromosome = Chromosome::new(params(20, function_set.len()), rng);
+            active_node_func.execute(&mut chromosome, Arc::clone(&function_set));
+            let constant_nodes = active_constant_nodes(&chromosome, &function_set);
+            let constants: Vec<f64> = constant_nodes.iter().map(|id| chromosome.nodes_grid[*id].constant).collect();
+
+            let outputs = dual_forward_pass(&chromosome, &constant_nodes, &constants, &inputs, &function_set);
+            let values: Vec<Vec<f64>> = outputs.iter().map(|output| output.iter().map(|x| x.value).collect()).collect();
+            assert_eq!(predict(&chromosome, &inputs, &function_set), values);
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    /// y = c * x with c = 0.1, to be tuned to y = 0.5 * x
+    fn scaled_input() -> (Chromosome, Arc<Vec<Box<dyn FunctionTrait<f64>>>>) {
+        let function_set = with_constant(get_f64_function_set()).unwrap();
+        let mut chromosome = Chromosome::new(params(2, function_set.len()), &mut ChaCha8Rng::seed_from_u64(0));
+        chromosome.nodes_grid[1].function_id = function_set.len() - 1;
+        chromosome.nodes_grid[1].constant = 0.1;
+        chromosome.nodes_grid[2].function_id = 2;
+        chromosome.nodes_grid[2].connection0 = 0;
+        chromosome.nodes_grid[2].connection1 = 1;
+        chromosome.nodes_grid[3].connection0 = 2;
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<f64>> = ChromosomeFindActiveNodesStandard::new();
+        active_node_func.execute(&mut chromosome, Arc::clone(&function_set));
+        assert_eq!(vec![1], active_constant_nodes(&chromosome, &function_set));
+        return (chromosome, function_set);
+    }
+
+    #[test]
+    fn optimizers_recover_a_constant() {
+        let inputs = vec![vec![-2., -1., 0.5, 1., 3.]];
+        let labels = vec![inputs[0].iter().map(|x| 0.5 * x).collect()];
+
+        for (optimizer, steps, tolerance) in [(ConstantOptimizer::LevenbergMarquardt, 5, 1e-9),
+                                              (ConstantOptimizer::Adam { learning_rate: 0.05 }, 200, 1e-3)] {
+            let (mut chromosome, function_set) = scaled_input();
+            assert!(tune_constants(&mut chromosome, &inputs, &labels, &function_set, &optimizer, steps));
+            let constant = chromosome.nodes_grid[1].constant;
+            assert!((constant - 0.5).abs() < tolerance, "{:?} tuned the constant to {}", optimizer, constant);
+
+            // nothing left to improve
+            let (mut exact, _) = scaled_input();
+            exact.nodes_grid[1].constant = 0.5;
+            assert!(!tune_constants(&mut exact, &inputs, &labels, &function_set, &optimizer, steps));
+        }
+    }
+
+    #[test]
+    fn only_the_real_function_sets_support_dual_numbers() {
+        assert!(check_dual_functions(&with_constant(get_f64_function_set()).unwrap()).is_ok());
+        assert!(check_dual_functions(&get_integer_function_set()).is_err());
+    }
+
+    #[test]
+    fn solves_linear_systems() {
+        let solution = solve(vec![vec![0., 2.], vec![4., 1.]], vec![2., 9.]).unwrap();
+        assert_eq!(vec![2., 1.], solution);
+        assert_eq!(None, solve(vec![vec![1., 2.], vec![2., 4.]], vec![1., 2.]));
+    }
+}
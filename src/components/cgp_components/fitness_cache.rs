@@ -0,0 +1,127 @@
+//! Bounded LRU cache of fitness values, keyed by `Chromosome::active_subgraph_hash`. During
+//! neutral drift, the same phenotype is evaluated over and over again; a cache hit saves its
+//! forward pass. See `CachedChromosomeEvaluator`, which caches the fitness together with the
+//! linear scaling fitted to the chromosome.
+
+use std::collections::{BTreeMap, HashMap};
+use nohash_hasher::BuildNoHashHasher;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheStatistics {
+    pub capacity: usize,
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+}
+
+impl CacheStatistics {
+    pub fn lookups(&self) -> usize {
+        self.hits + self.misses
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups() == 0 {
+            return 0.;
+        }
+        return self.hits as f64 / self.lookups() as f64;
+    }
+}
+
+pub struct FitnessCache<V = f32> {
+    // key -> (fitness, time of the last use)
+    entries: HashMap<u64, (V, u64), BuildNoHashHasher<u64>>,
+    // time of the last use -> key; the first entry is the least recently used one
+    recency: BTreeMap<u64, u64>,
+    clock: u64,
+    statistics: CacheStatistics,
+}
+
+impl<V: Clone> FitnessCache<V> {
+    /// A cache with a capacity of 0 stores nothing; every lookup is a miss.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::default(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            statistics: CacheStatistics { capacity, ..CacheStatistics::default() },
+        }
+    }
+
+    pub fn get(&mut self, key: u64) -> Option<V> {
+        let Some((fitness, last_use)) = self.entries.get_mut(&key) else {
+            self.statistics.misses += 1;
+            return None;
+        };
+
+        self.recency.remove(last_use);
+        self.clock += 1;
+        *last_use = self.clock;
+        self.recency.insert(self.clock, key);
+
+        self.statistics.hits += 1;
+        return Some(fitness.clone());
+    }
+
+    /// Evicts the least recently used entry if the cache is full.
+    pub fn insert(&mut self, key: u64, fitness: V) {
+        if self.statistics.capacity == 0 {
+            return;
+        }
+        if let Some((_, last_use)) = self.entries.get(&key) {
+            self.recency.remove(last_use);
+        } else if self.entries.len() >= self.statistics.capacity {
+            let (_, evicted) = self.recency.pop_first().unwrap();
+            self.entries.remove(&evicted);
+            self.statistics.evictions += 1;
+        }
+
+        self.clock += 1;
+        self.entries.insert(key, (fitness, self.clock));
+        self.recency.insert(self.clock, key);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn statistics(&self) -> CacheStatistics {
+        self.statistics
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_the_least_recently_used_entry() {
+        let mut cache = FitnessCache::new(2);
+        cache.insert(1, 0.1);
+        cache.insert(2, 0.2);
+        assert_eq!(Some(0.1), cache.get(1));
+
+        cache.insert(3, 0.3);
+
+        assert_eq!(2, cache.len());
+        assert_eq!(None, cache.get(2));
+        assert_eq!(Some(0.1), cache.get(1));
+        assert_eq!(Some(0.3), cache.get(3));
+        assert_eq!(CacheStatistics { capacity: 2, hits: 3, misses: 1, evictions: 1 }, cache.statistics());
+        assert_eq!(0.75, cache.statistics().hit_rate());
+    }
+
+    #[test]
+    fn empty_cache_stores_nothing() {
+        let mut cache = FitnessCache::new(0);
+        cache.insert(1, 0.1);
+
+        assert!(cache.is_empty());
+        assert_eq!(None, cache.get(1));
+    }
+}
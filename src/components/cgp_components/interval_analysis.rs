@@ -0,0 +1,259 @@
+//! Interval analysis of evolved regression programs. The active graph of a chromosome is evaluated
+//! on intervals (see `interval`), starting with one interval per input, the input domain. The
+//! interval of an output node encloses every value the program can return for inputs from the
+//! domain, not only for the rows of the training data. A program whose outputs are all defined and
+//! bounded never returns NaN or infinity on the domain and never relies on the protection of
+//! `div` and `ln`, which returns 1 close to their singularity.
+//! `IntervalCheckedMetric` rejects or penalizes the other programs during evolution.
+
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use crate::components::cgp_components::cgp_node_types::NodeType;
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::fitness_trait::FitnessFunction;
+use crate::utils::interval::Interval;
+use crate::utils::numeric::Numeric;
+
+pub const INTERVAL_ACTIONS: [&str; 3] = ["report", "reject", "penalize"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntervalIssueKind {
+    // NaN or a protected argument
+    Undefined,
+    // can overflow to infinity
+    Unbounded,
+}
+
+/// An active node whose interval is undefined or unbounded while the ones of its inputs are not,
+/// i.e. where a problem of the program starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntervalIssue {
+    pub node_id: usize,
+    pub function: &'static str,
+    pub kind: IntervalIssueKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntervalAnalysis {
+    // one interval per output node
+    pub outputs: Vec<Interval>,
+    pub issues: Vec<IntervalIssue>,
+}
+
+impl IntervalAnalysis {
+    /// Every output is defined and bounded.
+    pub fn is_safe(&self) -> bool {
+        return self.outputs.iter().all(|output| output.is_bounded());
+    }
+}
+
+impl Display for IntervalAnalysis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let outputs: Vec<String> = self.outputs.iter().map(|output| output.to_string()).collect();
+        write!(f, "outputs {}", outputs.join(", "))?;
+        if self.issues.is_empty() {
+            return write!(f, "; no issues");
+        }
+        let issues: Vec<String> = self.issues
+            .iter()
+            .map(|issue| format!("node {} ({}) {}", issue.node_id, issue.function, match issue.kind {
+                IntervalIssueKind::Undefined => "undefined",
+                IntervalIssueKind::Unbounded => "unbounded",
+            }))
+            .collect();
+        write!(f, "; issues: {}", issues.join(", "))
+    }
+}
+
+/// Range of every input over all `datasets`, e.g. the training and the eval data; each dataset
+/// holds one vector per input.
+pub fn input_domain_of<T: Numeric>(datasets: &[&Vec<Vec<T>>]) -> Vec<Interval> {
+    return (0..datasets[0].len())
+        .map(|input| {
+            let values = datasets.iter().flat_map(|data| data[input].iter().map(|x| x.to_f64().unwrap()));
+            let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| (min.min(x), max.max(x)));
+            Interval::new(min, max)
+        })
+        .collect();
+}
+
+/// Checks that every function with inputs can be evaluated on intervals, as `analyze` relies on
+/// it; the constant nodes are points instead.
+pub fn check_interval_functions<T>(function_set: &[Box<dyn FunctionTrait<T>>]) -> Result<(), String> {
+    for function in function_set.iter().filter(|function| function.get_number_inputs_needed() > 0) {
+        function.execute_interval(&vec![Interval::point(0.); function.get_number_inputs_needed()])?;
+    }
+    return Ok(());
+}
+
+/// Evaluates the active graph of `chromosome` on `input_domain`, one interval per input; the active
+/// nodes must be up to date. Values beyond the largest value of `T` overflow.
+pub fn analyze<T: Numeric>(chromosome: &Chromosome,
+                           input_domain: &[Interval],
+                           function_set: &[Box<dyn FunctionTrait<T>>]) -> IntervalAnalysis {
+    let max = T::max_value().to_f64().unwrap();
+    let output_start_id = chromosome.params.nbr_inputs + chromosome.params.graph_width;
+
+    let mut intervals = vec![Interval::UNDEFINED; output_start_id];
+    intervals[..input_domain.len()].copy_from_slice(input_domain);
+    let mut issues = vec![];
+    for node_id in &chromosome.active_nodes {
+        let node = &chromosome.nodes_grid[*node_id];
+        if node.node_type != NodeType::ComputationalNode {
+            continue;
+        }
+
+        let function = &function_set[node.function_id];
+        let arguments = &[intervals[node.connection0], intervals[node.connection1]][..function.get_number_inputs_needed()];
+        let interval = match arguments.len() {
+            0 => Interval::point(T::from_constant(node.constant).to_f64().unwrap()),
+            _ => function.execute_interval(arguments)
+                .expect("checked by check_interval_functions")
+                .overflow_above(max),
+        };
+        if !interval.is_bounded() && arguments.iter().all(|argument| argument.is_bounded()) {
+            let kind = if interval.is_undefined() { IntervalIssueKind::Undefined } else { IntervalIssueKind::Unbounded };
+            issues.push(IntervalIssue { node_id: *node_id, function: function.get_name(), kind });
+        }
+        intervals[*node_id] = interval;
+    }
+
+    let outputs = (output_start_id..output_start_id + chromosome.params.nbr_outputs)
+        .map(|output_id| intervals[chromosome.nodes_grid[output_id].connection0])
+        .collect();
+    return IntervalAnalysis { outputs, issues };
+}
+
+/// `metric` plus `penalty` for the chromosomes that are not `IntervalAnalysis::is_safe` on the
+/// input domain; a penalty of `f32::MAX` rejects them.
+pub struct IntervalCheckedMetric<T> {
+    metric: Arc<dyn FitnessFunction<T>>,
+    function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    input_domain: Vec<Interval>,
+    penalty: f32,
+}
+
+impl<T> IntervalCheckedMetric<T> {
+    pub fn new(metric: Arc<dyn FitnessFunction<T>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               input_domain: Vec<Interval>,
+               penalty: f32) -> Self {
+        Self { metric, function_set, input_domain, penalty }
+    }
+}
+
+impl<T: Numeric> FitnessFunction<T> for IntervalCheckedMetric<T> {
+    /// The fitness of `metric`; without the chromosome, there is nothing to analyze.
+    fn fitness(&self, prediction: &Vec<Vec<T>>, labels: &Vec<Vec<T>>) -> f32 {
+        return self.metric.fitness(prediction, labels);
+    }
+
+    fn chromosome_fitness(&self, chromosome: &mut Chromosome, prediction: &Vec<Vec<T>>, labels: &Vec<Vec<T>>) -> f32 {
+        let fitness = self.metric.chromosome_fitness(chromosome, prediction, labels);
+        if analyze(chromosome, &self.input_domain, &self.function_set).is_safe() {
+            return fitness;
+        }
+        // f32::MAX + f32::MAX is infinite
+        return (fitness + self.penalty).min(f32::MAX);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::cgp_components::chromosome_evaluator_operators::predict;
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesStandard};
+    use crate::function_set::f64_function_set::get_f64_function_set;
+    use crate::function_set::integer_function_set::get_integer_function_set;
+    use crate::function_set::regression_function_set::get_regression_function_set;
+    use crate::global_params::CgpParameters;
+    use crate::utils::fitness_metrics::RegressionMetric;
+
+    fn params(graph_width: usize, number_functions: usize) -> CgpParameters {
+        CgpParameters::for_tests(graph_width, 1, 1, number_functions)
+    }
+
+    /// The output is the function `function_id` of the input, applied to itself if it takes two.
+    fn single_function<T: Numeric>(function_id: usize, function_set: &Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> Chromosome {
+        let mut chromosome = Chromosome::new(params(1, function_set.len()), &mut ChaCha8Rng::seed_from_u64(0));
+        chromosome.nodes_grid[1].function_id = function_id;
+        chromosome.nodes_grid[1].connection0 = 0;
+        chromosome.nodes_grid[1].connection1 = 0;
+        chromosome.nodes_grid[2].connection0 = 1;
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<T>> = ChromosomeFindActiveNodesStandard::new();
+        active_node_func.execute(&mut chromosome, Arc::clone(function_set));
+        return chromosome;
+    }
+
+    #[test]
+    fn outputs_enclose_the_predictions() {
+        let function_set = get_f64_function_set();
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<f64>> = ChromosomeFindActiveNodesStandard::new();
+        let inputs = vec![(0..=40).map(|i| -2. + i as f64 / 10.).collect::<Vec<f64>>()];
+        let input_domain = input_domain_of(&[&inputs]);
+        assert_eq!(vec![Interval::new(-2., 2.)], input_domain);
+
+        let rng = &mut ChaCha8Rng::seed_from_u64(1);
+        let mut nbr_safe = 0;
+        for _ in 0..200 {
+            let mut chromosome = Chromosome::new(params(10, function_set.len()), rng);
+            active_node_func.execute(&mut chromosome, Arc::clone(&function_set));
+            let analysis = analyze(&chromosome, &input_domain, &function_set);
+            if !analysis.is_safe() {
+                assert!(!analysis.issues.is_empty());
+                continue;
+            }
+            nbr_safe += 1;
+            let output = analysis.outputs[0];
+            for y in &predict(&chromosome, &inputs, &function_set)[0] {
+                assert!(output.min - 1e-9 <= *y && *y <= output.max + 1e-9, "{} is not in {}", y, output);
+            }
+        }
+        assert!(nbr_safe > 0);
+    }
+
+    #[test]
+    fn flags_protected_functions_and_overflow() {
+        let function_set = get_f64_function_set();
+        // x / x
+        let division = single_function(3, &function_set);
+        let analysis = analyze(&division, &[Interval::new(-1., 1.)], &function_set);
+        assert!(!analysis.is_safe());
+        assert_eq!(vec![IntervalIssue { node_id: 1, function: "div", kind: IntervalIssueKind::Undefined }], analysis.issues);
+        assert_eq!("outputs undefined; issues: node 1 (div) undefined", analysis.to_string());
+        assert!(analyze(&division, &[Interval::new(1., 2.)], &function_set).is_safe());
+
+        // exp(x) overflows in f32, but not in f64
+        let domain = [Interval::new(0., 100.)];
+        assert!(analyze(&single_function(7, &function_set), &domain, &function_set).is_safe());
+        let f32_function_set = get_regression_function_set();
+        let analysis = analyze(&single_function(7, &f32_function_set), &domain, &f32_function_set);
+        assert_eq!(Interval::new(1., f64::INFINITY), analysis.outputs[0]);
+        assert_eq!(IntervalIssueKind::Unbounded, analysis.issues[0].kind);
+    }
+
+    #[test]
+    fn only_the_real_function_sets_support_intervals() {
+        assert!(check_interval_functions(&get_regression_function_set()).is_ok());
+        assert!(check_interval_functions(&get_f64_function_set()).is_ok());
+        assert!(check_interval_functions(&get_integer_function_set()).is_err());
+    }
+
+    #[test]
+    fn penalizes_unsafe_chromosomes() {
+        let function_set = get_f64_function_set();
+        let mut division = single_function(3, &function_set);
+        let prediction = predict(&division, &[vec![0., 1.]], &function_set);
+        let labels = vec![vec![0., 0.]];
+
+        for (domain, penalty, expected) in [(Interval::new(1., 2.), 10., 2.),
+                                            (Interval::new(0., 1.), 10., 12.),
+                                            (Interval::new(0., 1.), f32::MAX, f32::MAX)] {
+            let metric = IntervalCheckedMetric::new(Arc::new(RegressionMetric::SumAbsoluteError), Arc::clone(&function_set), vec![domain], penalty);
+            assert_eq!(expected, metric.chromosome_fitness(&mut division, &prediction, &labels));
+        }
+    }
+}
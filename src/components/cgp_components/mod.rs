@@ -7,4 +7,12 @@ pub mod cgp_types;
 pub mod cgp_node_mutation_operators;
 pub mod chromosome_mutation_operators;
 pub mod chromosome_find_active_node_operators;
-pub mod chromosome_reorder_operators;
\ No newline at end of file
+pub mod chromosome_reorder_operators;
+pub mod chromosome_file;
+pub mod fitness_cache;
+pub mod value_arena;
+pub mod node_value_tables;
+pub mod program;
+pub mod constant_tuning;
+pub mod interval_analysis;
+pub mod objectives;
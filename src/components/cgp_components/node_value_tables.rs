@@ -0,0 +1,241 @@
+//! Values of every active node of recently evaluated chromosomes; see
+//! `IncrementalChromosomeEvaluator`. A mutant shares the values of all nodes that are not
+//! downstream of its mutated nodes with its parent, so only the nodes downstream of the mutations
+//! are recomputed. The values are shared, not copied, between the tables.
+
+use std::sync::Arc;
+use crate::components::cgp_components::cgp_node_types::NodeType;
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::components::cgp_components::chromosome_evaluator_operators::DatasetId;
+use crate::function_set::function_trait::FunctionTrait;
+
+pub struct NodeValues<T> {
+    // the values only depend on the active genotype; see `active_genotype`
+    genotype: Vec<[usize; 4]>,
+    nbr_inputs: usize,
+    output_connections: Vec<usize>,
+    // the dataset of the inputs
+    dataset: DatasetId,
+    // value of every active computational node, indexed by node position
+    values: Vec<Option<Arc<Vec<T>>>>,
+    nbr_recomputed: usize,
+}
+
+impl<T: Clone> NodeValues<T> {
+    /// Evaluates every active node of `chromosome` on the inputs of `dataset`; `active_nodes` must
+    /// be up to date.
+    pub fn forward_pass(chromosome: &Chromosome,
+                        dataset: DatasetId,
+                        inputs: &[Vec<T>],
+                        function_set: &[Box<dyn FunctionTrait<T>>]) -> Self {
+        return Self::evaluate(chromosome, None, dataset, inputs, function_set);
+    }
+
+    /// Evaluates `chromosome`, a copy of the chromosome of `parent` with the genes of
+    /// `mutated_nodes` changed; `active_nodes` must be up to date. A node is only recomputed if
+    /// it is mutated, was inactive in the parent or reads a recomputed value; all other nodes
+    /// have the same value as in the parent, which must be evaluated on the same dataset.
+    pub fn forward_pass_from_parent(chromosome: &Chromosome,
+                                    parent: &NodeValues<T>,
+                                    mutated_nodes: &[usize],
+                                    inputs: &[Vec<T>],
+                                    function_set: &[Box<dyn FunctionTrait<T>>]) -> Self {
+        return Self::evaluate(chromosome, Some((parent, mutated_nodes)), parent.dataset, inputs, function_set);
+    }
+
+    fn evaluate(chromosome: &Chromosome,
+                parent: Option<(&NodeValues<T>, &[usize])>,
+                dataset: DatasetId,
+                inputs: &[Vec<T>],
+                function_set: &[Box<dyn FunctionTrait<T>>]) -> Self {
+        let nbr_inputs = chromosome.params.nbr_inputs;
+        let output_start_id = nbr_inputs + chromosome.params.graph_width;
+
+        let mut values: Vec<Option<Arc<Vec<T>>>> = vec![None; output_start_id];
+        let mut recomputed = vec![false; output_start_id];
+        if let Some((_, mutated_nodes)) = parent {
+            for node_id in mutated_nodes.iter().filter(|node_id| **node_id < output_start_id) {
+                recomputed[*node_id] = true;
+            }
+        }
+
+        let mut nbr_recomputed = 0;
+        for node_id in &chromosome.active_nodes {
+            let node = &chromosome.nodes_grid[*node_id];
+            if node.node_type != NodeType::ComputationalNode {
+                continue;
+            }
+            let function = &function_set[node.function_id];
+            let connections = &[node.connection0, node.connection1][..function.get_number_inputs_needed()];
+
+            let parent_value = match parent {
+                Some((parent, _)) if !recomputed[*node_id] && !connections.iter().any(|connection| recomputed[*connection]) => {
+                    parent.values[*node_id].as_ref().map(Arc::clone)
+                }
+                _ => None,
+            };
+            values[*node_id] = match parent_value {
+                Some(value) => Some(value),
+                None => {
+                    recomputed[*node_id] = true;
+                    nbr_recomputed += 1;
+                    if connections.is_empty() {
+                        let mut value = Vec::with_capacity(inputs[0].len());
+                        function.execute_constant_into(node.constant, inputs[0].len(), &mut value)
+                            .expect("checked by check_constant_functions");
+                        Some(Arc::new(value))
+                    } else {
+                        let connection_values: Vec<&Vec<T>> = connections
+                            .iter()
+                            .map(|connection| if *connection < nbr_inputs { &inputs[*connection] } else { values[*connection].as_deref().unwrap() })
+                            .collect();
+                        Some(Arc::new(function.execute_function(&connection_values)))
+                    }
+                }
+            };
+        }
+
+        let output_connections = chromosome.nodes_grid[output_start_id..]
+            .iter()
+            .map(|node| node.connection0)
+            .collect();
+        Self {
+            genotype: active_genotype(chromosome, function_set),
+            nbr_inputs,
+            output_connections,
+            dataset,
+            values,
+            nbr_recomputed,
+        }
+    }
+
+    /// Number of nodes the forward pass computed; the others were taken from the parent.
+    pub fn nbr_recomputed(&self) -> usize {
+        self.nbr_recomputed
+    }
+
+    /// Values of the first `nbr_outputs` output nodes, one vector per output node.
+    pub fn outputs(&self, inputs: &[Vec<T>], nbr_outputs: usize) -> Vec<Vec<T>> {
+        return self.output_connections[..nbr_outputs]
+            .iter()
+            .map(|connection| if *connection < self.nbr_inputs { inputs[*connection].clone() } else { self.values[*connection].as_deref().unwrap().clone() })
+            .collect();
+    }
+}
+
+/// The genes of the active nodes that `Chromosome::same_active_genotype` compares: position,
+/// function and used connections; a constant function keeps the bits of its constant instead of
+/// the connections. Cheaper to keep and to compare than a copy of the chromosome.
+fn active_genotype<T>(chromosome: &Chromosome, function_set: &[Box<dyn FunctionTrait<T>>]) -> Vec<[usize; 4]> {
+    return chromosome.active_nodes
+        .iter()
+        .map(|node_id| {
+            let node = &chromosome.nodes_grid[*node_id];
+            match node.node_type {
+                NodeType::InputNode => [*node_id, 0, 0, 0],
+                NodeType::OutputNode => [*node_id, 0, node.connection0, 0],
+                NodeType::ComputationalNode => match function_set[node.function_id].get_number_inputs_needed() {
+                    0 => [*node_id, node.function_id, node.constant.to_bits() as usize, usize::MAX],
+                    1 => [*node_id, node.function_id, node.connection0, usize::MAX],
+                    _ => [*node_id, node.function_id, node.connection0, node.connection1],
+                }
+            }
+        })
+        .collect();
+}
+
+/// The `NodeValues` of the most recently used chromosomes.
+pub struct NodeValueTables<T> {
+    capacity: usize,
+    // least recently used first
+    tables: Vec<Arc<NodeValues<T>>>,
+}
+
+impl<T: Clone> NodeValueTables<T> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "NodeValueTables needs a capacity > 0");
+        Self {
+            capacity,
+            tables: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// The values of a chromosome with the same active genotype as `chromosome` on `dataset`;
+    /// `active_nodes` must be up to date.
+    pub fn get(&mut self,
+               chromosome: &Chromosome,
+               dataset: DatasetId,
+               function_set: &[Box<dyn FunctionTrait<T>>]) -> Option<Arc<NodeValues<T>>> {
+        let genotype = active_genotype(chromosome, function_set);
+        let position = self.tables
+            .iter()
+            .rposition(|table| table.dataset == dataset && table.genotype == genotype)?;
+
+        let table = self.tables.remove(position);
+        self.tables.push(Arc::clone(&table));
+        return Some(table);
+    }
+
+    /// Adds the values of a chromosome; evicts the least recently used ones if full.
+    pub fn insert(&mut self, table: Arc<NodeValues<T>>) {
+        if self.tables.len() == self.capacity {
+            self.tables.remove(0);
+        }
+        self.tables.push(table);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesStandard};
+    use crate::datasets::boolean_datasets;
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::global_params::CgpParameters;
+    use crate::utils::utility_funcs::transpose;
+
+    #[test]
+    fn only_nodes_downstream_of_a_mutation_are_recomputed() {
+        let function_set = get_boolean_function_set();
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<bool>> = ChromosomeFindActiveNodesStandard::new();
+        let (data, _) = boolean_datasets::multiply::get_dataset();
+        let data = transpose(data);
+        let params = CgpParameters::for_tests(500, 6, 6, 4);
+
+        let mut parent = Chromosome::new(params, &mut ChaCha8Rng::seed_from_u64(0));
+        active_node_func.execute(&mut parent, Arc::clone(&function_set));
+        let parent_values = NodeValues::forward_pass(&parent, DatasetId::Train, &data, &function_set);
+        let computational: Vec<usize> = parent.active_nodes.iter()
+            .copied()
+            .filter(|id| parent.nodes_grid[*id].node_type == NodeType::ComputationalNode)
+            .collect();
+        assert_eq!(computational.len(), parent_values.nbr_recomputed());
+
+        // all Boolean functions have two inputs, so the active nodes stay the same
+        let mutated = computational[computational.len() / 2];
+        let mut child = parent.clone();
+        child.nodes_grid[mutated].function_id = (child.nodes_grid[mutated].function_id + 1) % 4;
+        active_node_func.execute(&mut child, Arc::clone(&function_set));
+        assert_eq!(parent.active_nodes, child.active_nodes);
+
+        let mut downstream = vec![mutated];
+        for id in &computational {
+            let node = &child.nodes_grid[*id];
+            if downstream.contains(&node.connection0) || downstream.contains(&node.connection1) {
+                downstream.push(*id);
+            }
+        }
+        let child_values = NodeValues::forward_pass_from_parent(&child, &parent_values, &[mutated], &data, &function_set);
+        assert_eq!(downstream.len(), child_values.nbr_recomputed());
+        assert!(downstream.len() < computational.len());
+        assert_eq!(NodeValues::forward_pass(&child, DatasetId::Train, &data, &function_set).outputs(&data, 6), child_values.outputs(&data, 6));
+
+        // the values of the training data are not returned for the eval data
+        let mut tables = NodeValueTables::new(2);
+        tables.insert(Arc::new(parent_values));
+        assert!(tables.get(&parent, DatasetId::Train, &function_set).is_some());
+        assert!(tables.get(&parent, DatasetId::Eval, &function_set).is_none());
+    }
+}
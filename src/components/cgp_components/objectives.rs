@@ -0,0 +1,233 @@
+//! Objectives of the multi-objective selection, see `NSGA2Selection`. The fitness of a chromosome,
+//! as stored in `Runner::fitness_vals`, is always the first objective; the secondary objectives
+//! measure the size of its program. All objectives are minimized.
+//! A chromosome dominates another one if it is at least as good in every objective and better in
+//! one; the chromosomes no other one dominates form the Pareto front.
+
+use std::cmp::Ordering;
+use crate::components::cgp_components::cgp_node_types::NodeType;
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::function_set::function_trait::FunctionTrait;
+
+pub const SECONDARY_OBJECTIVES: [&str; 2] = ["active_nodes", "depth"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Objective {
+    // number of active computational nodes
+    ActiveNodes,
+    // computational nodes on the longest path from an input to an output
+    Depth,
+}
+
+impl Objective {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "active_nodes" => Ok(Objective::ActiveNodes),
+            "depth" => Ok(Objective::Depth),
+            _ => Err(format!("unknown objective '{}', expected one of {:?}", name, SECONDARY_OBJECTIVES)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::ActiveNodes => "active_nodes",
+            Objective::Depth => "depth",
+        }
+    }
+
+    /// The active nodes of `chromosome` must be up to date.
+    pub fn value<T>(&self, chromosome: &Chromosome, function_set: &[Box<dyn FunctionTrait<T>>]) -> f32 {
+        match self {
+            Objective::ActiveNodes => active_node_count(chromosome) as f32,
+            Objective::Depth => depth(chromosome, function_set) as f32,
+        }
+    }
+}
+
+/// `fitness` followed by the value of every objective of `objectives`.
+pub fn objective_values<T>(fitness: f32,
+                           chromosome: &Chromosome,
+                           objectives: &[Objective],
+                           function_set: &[Box<dyn FunctionTrait<T>>]) -> Vec<f32> {
+    let mut values = Vec::with_capacity(objectives.len() + 1);
+    values.push(fitness);
+    values.extend(objectives.iter().map(|objective| objective.value(chromosome, function_set)));
+    return values;
+}
+
+pub fn active_node_count(chromosome: &Chromosome) -> usize {
+    return chromosome.active_nodes
+        .iter()
+        .filter(|node_id| chromosome.nodes_grid[**node_id].node_type == NodeType::ComputationalNode)
+        .count();
+}
+
+/// Longest path over the active computational nodes; only the connections a function reads are
+/// followed. An output connected to an input has depth 0, a constant node has depth 1.
+pub fn depth<T>(chromosome: &Chromosome, function_set: &[Box<dyn FunctionTrait<T>>]) -> usize {
+    let output_start_id = chromosome.params.nbr_inputs + chromosome.params.graph_width;
+
+    // the active nodes are in the order of the evaluation
+    let mut depths = vec![0; output_start_id];
+    for node_id in &chromosome.active_nodes {
+        let node = &chromosome.nodes_grid[*node_id];
+        if node.node_type != NodeType::ComputationalNode {
+            continue;
+        }
+        let arity = function_set[node.function_id].get_number_inputs_needed();
+        depths[*node_id] = 1 + [node.connection0, node.connection1][..arity]
+            .iter()
+            .map(|connection| depths[*connection])
+            .max()
+            .unwrap_or(0);
+    }
+
+    return (output_start_id..output_start_id + chromosome.params.nbr_outputs)
+        .map(|output_id| depths[chromosome.nodes_grid[output_id].connection0])
+        .max()
+        .unwrap_or(0);
+}
+
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    return a.iter().zip(b.iter()).all(|(x, y)| x <= y) && a.iter().zip(b.iter()).any(|(x, y)| x < y);
+}
+
+/// Splits the indices of `values` into fronts: the first one is the Pareto front, the next one
+/// the Pareto front without the first one, and so on.
+pub fn non_dominated_sort(values: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    // for every index, the indices it dominates and the number of indices dominating it
+    let mut dominated: Vec<Vec<usize>> = vec![vec![]; values.len()];
+    let mut nbr_dominating = vec![0; values.len()];
+    for i in 0..values.len() {
+        for j in i + 1..values.len() {
+            if dominates(&values[i], &values[j]) {
+                dominated[i].push(j);
+                nbr_dominating[j] += 1;
+            } else if dominates(&values[j], &values[i]) {
+                dominated[j].push(i);
+                nbr_dominating[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = vec![];
+    let mut front: Vec<usize> = (0..values.len()).filter(|i| nbr_dominating[*i] == 0).collect();
+    while !front.is_empty() {
+        let mut next_front = vec![];
+        for i in &front {
+            for j in &dominated[*i] {
+                nbr_dominating[*j] -= 1;
+                if nbr_dominating[*j] == 0 {
+                    next_front.push(*j);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next_front;
+    }
+    return fronts;
+}
+
+/// Crowding distance of every index of `front`: the sum over the objectives of the distance
+/// between its neighbours, relative to the range of the objective in the front. The extremes of
+/// every objective have an infinite distance. Computed in f64, so the fitness `f32::MAX` of a
+/// rejected chromosome does not overflow.
+pub fn crowding_distances(front: &[usize], values: &[Vec<f32>]) -> Vec<f64> {
+    let mut distances = vec![0.; front.len()];
+    if front.is_empty() {
+        return distances;
+    }
+
+    // the values of the front, one vector per objective
+    let objectives: Vec<Vec<f64>> = (0..values[front[0]].len())
+        .map(|objective| front.iter().map(|i| values[*i][objective] as f64).collect())
+        .collect();
+    for value in &objectives {
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|a, b| value[*a].partial_cmp(&value[*b]).unwrap_or(Ordering::Equal));
+
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distances[first] = f64::INFINITY;
+        distances[last] = f64::INFINITY;
+        let range = value[last] - value[first];
+        if range == 0. {
+            continue;
+        }
+        for window in order.windows(3) {
+            distances[window[1]] += (value[window[2]] - value[window[0]]) / range;
+        }
+    }
+    return distances;
+}
+
+/// Indices of the Pareto front of `values`, without duplicates of the same objective values and
+/// sorted by the first objective, i.e. the fitness.
+pub fn pareto_front(values: &[Vec<f32>]) -> Vec<usize> {
+    let Some(front) = non_dominated_sort(values).into_iter().next() else {
+        return vec![];
+    };
+
+    let mut unique: Vec<usize> = vec![];
+    for i in front {
+        if !unique.iter().any(|j| values[*j] == values[i]) {
+            unique.push(i);
+        }
+    }
+    unique.sort_by(|a, b| values[*a].partial_cmp(&values[*b]).unwrap_or(Ordering::Equal));
+    return unique;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesStandard};
+    use crate::function_set::f64_function_set::get_f64_function_set;
+    use crate::global_params::CgpParameters;
+
+    #[test]
+    fn sorts_fronts_and_crowding() {
+        // (fitness, size)
+        let values = vec![vec![1., 5.], vec![2., 3.], vec![3., 1.], vec![2., 4.], vec![4., 4.], vec![1., 5.]];
+        assert!(dominates(&values[1], &values[3]) && !dominates(&values[0], &values[5]));
+        assert_eq!(vec![vec![0, 1, 2, 5], vec![3], vec![4]], non_dominated_sort(&values));
+
+        let distances = crowding_distances(&[0, 1, 2], &values);
+        assert_eq!(f64::INFINITY, distances[0]);
+        assert_eq!(f64::INFINITY, distances[2]);
+        // (3 - 1) / (3 - 1) + (5 - 1) / (5 - 1)
+        assert_eq!(2., distances[1]);
+        // f32::MAX does not overflow
+        let rejected = vec![vec![f32::MAX, 1.], vec![0., 3.], vec![1., 2.]];
+        assert!(crowding_distances(&[0, 1, 2], &rejected)[2].is_finite());
+
+        assert_eq!(vec![0, 1, 2], pareto_front(&values));
+    }
+
+    #[test]
+    fn counts_size_and_depth() {
+        let function_set = get_f64_function_set();
+        let params = CgpParameters::for_tests(4, 1, 1, function_set.len());
+        let mut chromosome = Chromosome::new(params, &mut ChaCha8Rng::seed_from_u64(0));
+        // node 1 = x + x, node 2 = sin(node 1), node 3 = node 1 * node 2; node 4 is inactive
+        for (node_id, function_id, connection0, connection1) in [(1, 0, 0, 0), (2, 4, 1, 0), (3, 2, 1, 2)] {
+            chromosome.nodes_grid[node_id].function_id = function_id;
+            chromosome.nodes_grid[node_id].connection0 = connection0;
+            chromosome.nodes_grid[node_id].connection1 = connection1;
+        }
+        chromosome.nodes_grid[5].connection0 = 3;
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<f64>> = ChromosomeFindActiveNodesStandard::new();
+        active_node_func.execute(&mut chromosome, Arc::clone(&function_set));
+
+        assert_eq!(3, active_node_count(&chromosome));
+        // sin reads node 1 only
+        assert_eq!(3, depth(&chromosome, &function_set));
+        assert_eq!(vec![0.5, 3., 3.], objective_values(0.5, &chromosome, &[Objective::ActiveNodes, Objective::Depth], &function_set));
+
+        chromosome.nodes_grid[5].connection0 = 0;
+        active_node_func.execute(&mut chromosome, Arc::clone(&function_set));
+        assert_eq!(0, depth(&chromosome, &function_set));
+    }
+}
@@ -0,0 +1,556 @@
+//! Compiles the active nodes of a chromosome to a straight-line program on registers; see
+//! `Program::compile`. The program is independent of the chromosome: exporters and inference
+//! only need the program and the function names. The `Interpreter` runs it over the (transposed)
+//! inputs, one instruction per data column.
+
+use std::fmt::{Display, Formatter};
+use std::mem;
+use serde::{Deserialize, Serialize};
+use crate::components::cgp_components::cgp_node_types::NodeType;
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::function_set::f64_function_set;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::function_set::integer_function_set;
+use crate::function_set::regression_function_set::{protected_div, protected_ln};
+use crate::utils::numeric::Numeric;
+use crate::utils::packed_bits::PackedBits;
+
+/// Operation of an instruction; one per function name of the function sets. An opcode computes
+/// the same function on every type that supports it, e.g. `Sin` is always the sine; see
+/// `ProgramValue`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Opcode {
+    And,
+    Or,
+    Nand,
+    Nor,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Sin,
+    Cos,
+    Ln,
+    Exp,
+    Mod,
+    Neg,
+    Min,
+    Max,
+    Const,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 17] = [Opcode::And, Opcode::Or, Opcode::Nand, Opcode::Nor,
+        Opcode::Add, Opcode::Sub, Opcode::Mul, Opcode::Div,
+        Opcode::Sin, Opcode::Cos, Opcode::Ln, Opcode::Exp,
+        Opcode::Mod, Opcode::Neg, Opcode::Min, Opcode::Max,
+        Opcode::Const];
+
+    /// The opcode of the function with `name`, i.e. `FunctionTrait::get_name`; `plus`, the second
+    /// addition of the `f32` set, is an `Add`.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        if name == "plus" {
+            return Ok(Opcode::Add);
+        }
+        return Self::ALL
+            .into_iter()
+            .find(|opcode| opcode.name() == name)
+            .ok_or_else(|| format!("function '{}' has no opcode", name));
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Opcode::And => "and",
+            Opcode::Or => "or",
+            Opcode::Nand => "nand",
+            Opcode::Nor => "nor",
+            Opcode::Add => "add",
+            Opcode::Sub => "sub",
+            Opcode::Mul => "mul",
+            Opcode::Div => "div",
+            Opcode::Sin => "sin",
+            Opcode::Cos => "cos",
+            Opcode::Ln => "ln",
+            Opcode::Exp => "exp",
+            Opcode::Mod => "mod",
+            Opcode::Neg => "neg",
+            Opcode::Min => "min",
+            Opcode::Max => "max",
+            Opcode::Const => "const",
+        }
+    }
+}
+
+/// `output = opcode(inputs)`; for instructions with one input, `inputs[1]` is unused. The number
+/// of inputs is the one of the compiled function. `const` has no inputs; its `inputs[0]` is the
+/// index of its value in `Program::constants`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub inputs: [usize; 2],
+    pub nbr_inputs: usize,
+    pub output: usize,
+}
+
+/// Straight-line program of the active nodes of a chromosome. Registers `0..nbr_inputs` hold the
+/// inputs and are never written; all others hold intermediate values and are reused as soon as
+/// their value is not read anymore.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Program {
+    pub nbr_inputs: usize,
+    pub nbr_registers: usize,
+    pub instructions: Vec<Instruction>,
+    // register of every output node
+    pub outputs: Vec<usize>,
+    // values of the `const` instructions
+    pub constants: Vec<f64>,
+}
+
+impl Program {
+    /// Compiles the active nodes of `chromosome` in the order of `active_nodes`, which must be up
+    /// to date; both `ChromosomeFindActiveNodesStandard` and `ChromosomeFindActiveNodesDAG` give a
+    /// topological order.
+    pub fn compile<T>(chromosome: &Chromosome, function_set: &[Box<dyn FunctionTrait<T>>]) -> Result<Self, String> {
+        let nbr_inputs = chromosome.params.nbr_inputs;
+        let output_start_id = nbr_inputs + chromosome.params.graph_width;
+        let output_ids = output_start_id..(output_start_id + chromosome.params.nbr_outputs);
+
+        let mut instructions = Vec::with_capacity(chromosome.active_nodes.len());
+        // register of every compiled node, indexed by node position
+        let mut register_of_node: Vec<Option<usize>> = (0..output_start_id)
+            .map(|node_id| if node_id < nbr_inputs { Some(node_id) } else { None })
+            .collect();
+        // index in `instructions` of the last instruction that reads a node
+        let mut last_use = vec![usize::MAX; output_start_id];
+        let mut nodes = Vec::with_capacity(chromosome.active_nodes.len());
+        let opcodes = function_set
+            .iter()
+            .map(|function| Opcode::from_name(function.get_name()).map(|opcode| (opcode, function.get_number_inputs_needed())))
+            .collect::<Result<Vec<(Opcode, usize)>, String>>()?;
+
+        for node_id in &chromosome.active_nodes {
+            let node = &chromosome.nodes_grid[*node_id];
+            if node.node_type != NodeType::ComputationalNode {
+                continue;
+            }
+            let (opcode, nbr_connections) = opcodes[node.function_id];
+            let connections = [node.connection0, node.connection1];
+            for connection in &connections[..nbr_connections] {
+                if *connection >= output_start_id {
+                    return Err(format!("node {} reads output node {}", node_id, connection));
+                }
+                last_use[*connection] = nodes.len();
+            }
+            nodes.push((*node_id, opcode, nbr_connections, connections));
+        }
+        let mut outputs = Vec::with_capacity(output_ids.len());
+        for output_id in output_ids.clone() {
+            let connection = chromosome.nodes_grid[output_id].connection0;
+            // read after the last instruction; the value must not be overwritten
+            last_use[connection] = nodes.len();
+        }
+
+        let mut nbr_registers = nbr_inputs;
+        let mut free_registers: Vec<usize> = vec![];
+        let mut constants = vec![];
+        for (step, (node_id, opcode, nbr_connections, connections)) in nodes.into_iter().enumerate() {
+            let mut inputs = [0; 2];
+            if nbr_connections == 0 {
+                inputs[0] = constants.len();
+                constants.push(chromosome.nodes_grid[node_id].constant);
+            }
+            for (i, connection) in connections[..nbr_connections].iter().enumerate() {
+                inputs[i] = register_of_node[*connection]
+                    .ok_or_else(|| format!("node {} reads node {} before it is computed; active_nodes are not in topological order", node_id, connection))?;
+            }
+            // the output register is taken before the inputs are freed, so an instruction never
+            // overwrites its own inputs
+            let output = free_registers.pop().unwrap_or_else(|| {
+                nbr_registers += 1;
+                nbr_registers - 1
+            });
+            instructions.push(Instruction { opcode, inputs, nbr_inputs: nbr_connections, output });
+            register_of_node[node_id] = Some(output);
+
+            let connections = &connections[..nbr_connections];
+            for (i, connection) in connections.iter().enumerate() {
+                // a value that is read twice by the same node is freed once
+                if *connection >= nbr_inputs && last_use[*connection] == step && !connections[..i].contains(connection) {
+                    free_registers.push(register_of_node[*connection].unwrap());
+                }
+            }
+        }
+
+        for output_id in output_ids {
+            let connection = chromosome.nodes_grid[output_id].connection0;
+            outputs.push(register_of_node[connection]
+                .ok_or_else(|| format!("output node {} reads node {}, which is not active", output_id, connection))?);
+        }
+
+        Ok(Self {
+            nbr_inputs,
+            nbr_registers,
+            instructions,
+            outputs,
+            constants,
+        })
+    }
+}
+
+/// One instruction per line, e.g. `r7 = add r0 r3` or `r8 = const 2.5`; registers below
+/// `nbr_inputs` are inputs.
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for instruction in &self.instructions {
+            write!(f, "r{} = {}", instruction.output, instruction.opcode.name())?;
+            if instruction.opcode == Opcode::Const {
+                write!(f, " {}", self.constants[instruction.inputs[0]])?;
+            }
+            for input in &instruction.inputs[..instruction.nbr_inputs] {
+                write!(f, " r{}", input)?;
+            }
+            writeln!(f)?;
+        }
+        let outputs: Vec<String> = self.outputs.iter().map(|register| format!("r{}", register)).collect();
+        writeln!(f, "outputs: {}", outputs.join(" "))
+    }
+}
+
+/// Values an `Interpreter` can compute with. The opcodes compute the same as the functions of the
+/// same name in the function set of the type.
+pub trait ProgramValue: Clone + Sized {
+    fn supports(opcode: Opcode) -> bool;
+
+    /// Appends `opcode(input0, input1)` of every row to `output`; not called for `const`.
+    fn execute(opcode: Opcode, input0: &[Self], input1: &[Self], output: &mut Vec<Self>);
+
+    /// The value of a `const` instruction; see `Numeric::from_constant`.
+    #[allow(unused_variables)]
+    fn constant(value: f64) -> Self {
+        unreachable!("{} has no constants", std::any::type_name::<Self>())
+    }
+}
+
+fn binary<T: Copy>(input0: &[T], input1: &[T], output: &mut Vec<T>, function: impl Fn(T, T) -> T) {
+    output.extend(input0.iter().zip(input1.iter()).map(|(a, b)| function(*a, *b)));
+}
+
+fn unary<T: Copy>(input0: &[T], output: &mut Vec<T>, function: impl Fn(T) -> T) {
+    output.extend(input0.iter().map(|x| function(*x)));
+}
+
+impl ProgramValue for bool {
+    fn supports(opcode: Opcode) -> bool {
+        matches!(opcode, Opcode::And | Opcode::Or | Opcode::Nand | Opcode::Nor)
+    }
+
+    fn execute(opcode: Opcode, input0: &[bool], input1: &[bool], output: &mut Vec<bool>) {
+        match opcode {
+            Opcode::And => binary(input0, input1, output, |a, b| a & b),
+            Opcode::Or => binary(input0, input1, output, |a, b| a | b),
+            Opcode::Nand => binary(input0, input1, output, |a, b| !(a & b)),
+            Opcode::Nor => binary(input0, input1, output, |a, b| !(a | b)),
+            _ => unreachable!("{:?} is not a Boolean opcode", opcode),
+        }
+    }
+}
+
+impl ProgramValue for PackedBits {
+    fn supports(opcode: Opcode) -> bool {
+        bool::supports(opcode)
+    }
+
+    fn execute(opcode: Opcode, input0: &[PackedBits], input1: &[PackedBits], output: &mut Vec<PackedBits>) {
+        match opcode {
+            Opcode::And => binary(input0, input1, output, |a, b| a & b),
+            Opcode::Or => binary(input0, input1, output, |a, b| a | b),
+            Opcode::Nand => binary(input0, input1, output, |a, b| !(a & b)),
+            Opcode::Nor => binary(input0, input1, output, |a, b| !(a | b)),
+            _ => unreachable!("{:?} is not a Boolean opcode", opcode),
+        }
+    }
+}
+
+impl ProgramValue for f32 {
+    fn supports(opcode: Opcode) -> bool {
+        matches!(opcode, Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div
+            | Opcode::Sin | Opcode::Cos | Opcode::Ln | Opcode::Exp | Opcode::Const)
+    }
+
+    fn execute(opcode: Opcode, input0: &[f32], input1: &[f32], output: &mut Vec<f32>) {
+        match opcode {
+            Opcode::Add => binary(input0, input1, output, |a, b| a + b),
+            Opcode::Sub => binary(input0, input1, output, |a, b| a - b),
+            Opcode::Mul => binary(input0, input1, output, |a, b| a * b),
+            Opcode::Div => binary(input0, input1, output, protected_div),
+            Opcode::Sin => unary(input0, output, f32::sin),
+            Opcode::Cos => unary(input0, output, f32::cos),
+            Opcode::Ln => unary(input0, output, protected_ln),
+            Opcode::Exp => unary(input0, output, f32::exp),
+            _ => unreachable!("{:?} is not a regression opcode", opcode),
+        }
+    }
+
+    fn constant(value: f64) -> f32 {
+        return f32::from_constant(value);
+    }
+}
+
+impl ProgramValue for f64 {
+    fn supports(opcode: Opcode) -> bool {
+        f32::supports(opcode)
+    }
+
+    fn execute(opcode: Opcode, input0: &[f64], input1: &[f64], output: &mut Vec<f64>) {
+        match opcode {
+            Opcode::Add => binary(input0, input1, output, |a, b| a + b),
+            Opcode::Sub => binary(input0, input1, output, |a, b| a - b),
+            Opcode::Mul => binary(input0, input1, output, |a, b| a * b),
+            Opcode::Div => binary(input0, input1, output, f64_function_set::protected_div),
+            Opcode::Sin => unary(input0, output, f64::sin),
+            Opcode::Cos => unary(input0, output, f64::cos),
+            Opcode::Ln => unary(input0, output, f64_function_set::protected_ln),
+            Opcode::Exp => unary(input0, output, f64::exp),
+            _ => unreachable!("{:?} is not a regression opcode", opcode),
+        }
+    }
+
+    fn constant(value: f64) -> f64 {
+        return f64::from_constant(value);
+    }
+}
+
+impl ProgramValue for i64 {
+    fn supports(opcode: Opcode) -> bool {
+        matches!(opcode, Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div
+            | Opcode::Mod | Opcode::Neg | Opcode::Min | Opcode::Max | Opcode::Const)
+    }
+
+    fn execute(opcode: Opcode, input0: &[i64], input1: &[i64], output: &mut Vec<i64>) {
+        match opcode {
+            Opcode::Add => binary(input0, input1, output, i64::wrapping_add),
+            Opcode::Sub => binary(input0, input1, output, i64::wrapping_sub),
+            Opcode::Mul => binary(input0, input1, output, i64::wrapping_mul),
+            Opcode::Div => binary(input0, input1, output, integer_function_set::protected_div),
+            Opcode::Mod => binary(input0, input1, output, integer_function_set::protected_mod),
+            Opcode::Neg => unary(input0, output, i64::wrapping_neg),
+            Opcode::Min => binary(input0, input1, output, i64::min),
+            Opcode::Max => binary(input0, input1, output, i64::max),
+            _ => unreachable!("{:?} is not an integer opcode", opcode),
+        }
+    }
+
+    fn constant(value: f64) -> i64 {
+        return i64::from_constant(value);
+    }
+}
+
+/// Runs programs; keeps its registers between runs, so after the first run of a program size
+/// nothing is allocated anymore.
+pub struct Interpreter<T> {
+    nbr_inputs: usize,
+    // registers from `nbr_inputs` on; the inputs are read in place
+    registers: Vec<Vec<T>>,
+    outputs: Vec<Vec<T>>,
+}
+
+impl<T: ProgramValue> Default for Interpreter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ProgramValue> Interpreter<T> {
+    pub fn new() -> Self {
+        Self {
+            nbr_inputs: 0,
+            registers: vec![],
+            outputs: vec![],
+        }
+    }
+
+    /// Checks that all opcodes of `program` can be computed on `T`.
+    pub fn check(program: &Program) -> Result<(), String> {
+        match program.instructions.iter().find(|instruction| !T::supports(instruction.opcode)) {
+            Some(instruction) => Err(format!("opcode {} is not supported for {}", instruction.opcode.name(), std::any::type_name::<T>())),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs `program` on the (transposed) inputs; afterwards, the outputs can be read with
+    /// `output`, `outputs_by_column` and `outputs_by_row`. See `check` for the opcodes.
+    pub fn run(&mut self, program: &Program, inputs: &[Vec<T>]) {
+        assert_eq!(program.nbr_inputs, inputs.len(), "the program needs {} inputs", program.nbr_inputs);
+        self.nbr_inputs = program.nbr_inputs;
+        if self.registers.len() < program.nbr_registers - program.nbr_inputs {
+            self.registers.resize_with(program.nbr_registers - program.nbr_inputs, Vec::new);
+        }
+
+        for instruction in &program.instructions {
+            let mut output = mem::take(&mut self.registers[instruction.output - self.nbr_inputs]);
+            output.clear();
+            if instruction.opcode == Opcode::Const {
+                output.resize(inputs[0].len(), T::constant(program.constants[instruction.inputs[0]]));
+                self.registers[instruction.output - self.nbr_inputs] = output;
+                continue;
+            }
+            let input0 = self.register(inputs, instruction.inputs[0]);
+            let input1 = if instruction.nbr_inputs == 2 { self.register(inputs, instruction.inputs[1]) } else { input0 };
+            T::execute(instruction.opcode, input0, input1, &mut output);
+            self.registers[instruction.output - self.nbr_inputs] = output;
+        }
+    }
+
+    fn register<'a>(&'a self, inputs: &'a [Vec<T>], register: usize) -> &'a [T] {
+        if register < self.nbr_inputs {
+            return &inputs[register];
+        }
+        return &self.registers[register - self.nbr_inputs];
+    }
+
+    /// Values of the output `i` of the last run.
+    pub fn output<'a>(&'a self, program: &Program, inputs: &'a [Vec<T>], i: usize) -> &'a [T] {
+        return self.register(inputs, program.outputs[i]);
+    }
+
+    /// Values of the first `nbr_outputs` outputs of the last run, one vector per output.
+    pub fn outputs_by_column(&mut self, program: &Program, inputs: &[Vec<T>], nbr_outputs: usize) -> &Vec<Vec<T>> {
+        let mut outputs = mem::take(&mut self.outputs);
+        outputs.resize_with(nbr_outputs, Vec::new);
+        for (i, column) in outputs.iter_mut().enumerate() {
+            column.clear();
+            column.extend_from_slice(self.output(program, inputs, i));
+        }
+        self.outputs = outputs;
+        return &self.outputs;
+    }
+
+    /// Values of the outputs of the last run, one vector per input row; the layout of the labels.
+    pub fn outputs_by_row(&mut self, program: &Program, inputs: &[Vec<T>]) -> &Vec<Vec<T>> {
+        let mut outputs = mem::take(&mut self.outputs);
+        outputs.resize_with(inputs[0].len(), Vec::new);
+        outputs.iter_mut().for_each(|row| row.clear());
+        for i in 0..program.outputs.len() {
+            outputs.iter_mut().zip(self.output(program, inputs, i).iter()).for_each(|(row, x)| row.push(x.clone()));
+        }
+        self.outputs = outputs;
+        return &self.outputs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::cgp_components::cgp_types::CGPType;
+    use crate::components::cgp_components::chromosome_evaluator_operators::{ChromosomeEvaluator, CompiledChromosomeEvaluator, DatasetId, EvaluateChromosomeTrait};
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesDAG, ChromosomeFindActiveNodesStandard};
+    use crate::datasets::regression_benchmarks;
+    use crate::function_set::regression_function_set::get_regression_function_set;
+    use crate::global_params::CgpParameters;
+    use crate::utils::utility_funcs::transpose;
+
+    fn params(cgp_type: CGPType) -> CgpParameters {
+        CgpParameters { cgp_type, ..CgpParameters::for_tests(200, 1, 1, 8) }
+    }
+
+    #[test]
+    fn compiled_program_computes_the_outputs_of_the_chromosome() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(0);
+        let function_set = get_regression_function_set();
+        let (data, label) = regression_benchmarks::keijzer::get_dataset();
+        let data = transpose(data);
+        let compiled: Box<dyn EvaluateChromosomeTrait<f32>> = CompiledChromosomeEvaluator::new();
+
+        for (cgp_type, active_node_func) in [(CGPType::Standard, ChromosomeFindActiveNodesStandard::new()), (CGPType::DAG, ChromosomeFindActiveNodesDAG::new())] {
+            let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<f32>>> = Arc::new(active_node_func);
+            for _ in 0..50 {
+                let mut chromosome = Chromosome::new(params(cgp_type.clone()), rng);
+                let fitness = ChromosomeEvaluator::<f32>::new().evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set));
+                assert_eq!(fitness.to_bits(), compiled.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set)).to_bits());
+
+                // intermediate values share registers
+                let program = Program::compile(&chromosome, &function_set).unwrap();
+                assert_eq!(chromosome.active_nodes.len() - 2, program.instructions.len());
+                assert!(program.nbr_registers <= program.nbr_inputs + program.instructions.len());
+                for instruction in &program.instructions {
+                    assert!(!instruction.inputs[..instruction.nbr_inputs].contains(&instruction.output));
+                }
+            }
+        }
+    }
+
+    struct Square;
+
+    impl FunctionTrait<f32> for Square {
+        fn get_name(&self) -> &'static str {
+            return "square";
+        }
+
+        fn get_number_inputs_needed(&self) -> usize {
+            return 1;
+        }
+
+        fn execute_function_into(&self, inputs: &[&Vec<f32>], output: &mut Vec<f32>) {
+            output.extend(inputs[0].iter().map(|x| x * x));
+        }
+    }
+
+    #[test]
+    fn chromosome_without_opcodes_is_evaluated_without_compiling() {
+        let mut function_set = Arc::try_unwrap(get_regression_function_set()).unwrap_or_else(|_| panic!("the function set is shared"));
+        function_set.push(Box::new(Square));
+        let function_set = Arc::new(function_set);
+        let (data, label) = regression_benchmarks::keijzer::get_dataset();
+        let data = transpose(data);
+        let compiled: Box<dyn EvaluateChromosomeTrait<f32>> = CompiledChromosomeEvaluator::new();
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<f32>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+
+        let mut chromosome = Chromosome::new(CgpParameters::for_tests(200, 1, 1, 9), &mut ChaCha8Rng::seed_from_u64(2));
+        assert!(Program::compile(&chromosome, &function_set).is_err());
+        let fitness = ChromosomeEvaluator::<f32>::new().evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set));
+        assert_eq!(fitness.to_bits(), compiled.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set)).to_bits());
+    }
+
+    #[test]
+    fn program_is_listed_one_instruction_per_line() {
+        let function_set = get_regression_function_set();
+        let mut chromosome = Chromosome::new(params(CGPType::Standard), &mut ChaCha8Rng::seed_from_u64(1));
+        // out = exp(x0 - (x0 * x0))
+        for (node_id, function_id, connection0, connection1) in [(1, 2, 0, 0), (2, 1, 0, 1), (3, 7, 2, 2)] {
+            let node = &mut chromosome.nodes_grid[node_id];
+            (node.function_id, node.connection0, node.connection1) = (function_id, connection0, connection1);
+        }
+        chromosome.nodes_grid[201].connection0 = 3;
+        ChromosomeFindActiveNodesStandard::new().execute(&mut chromosome, Arc::clone(&function_set));
+
+        let program = Program::compile(&chromosome, &function_set).unwrap();
+        assert_eq!("r1 = mul r0 r0\nr2 = sub r0 r1\nr1 = exp r2\noutputs: r1\n", program.to_string());
+
+        let mut interpreter = Interpreter::new();
+        interpreter.run(&program, &[vec![0., 1., 2.]]);
+        assert_eq!(&[1., 1., (-2f32).exp()], interpreter.output(&program, &[vec![0., 1., 2.]], 0));
+    }
+
+    #[test]
+    fn listing_names_what_the_f32_functions_compute() {
+        let function_set = get_regression_function_set();
+        let mut chromosome = Chromosome::new(params(CGPType::Standard), &mut ChaCha8Rng::seed_from_u64(1));
+        // out = sin(x0 plus x0)
+        for (node_id, function_id, connection0, connection1) in [(1, 4, 0, 0), (2, 5, 1, 1)] {
+            let node = &mut chromosome.nodes_grid[node_id];
+            (node.function_id, node.connection0, node.connection1) = (function_id, connection0, connection1);
+        }
+        chromosome.nodes_grid[201].connection0 = 2;
+        ChromosomeFindActiveNodesStandard::new().execute(&mut chromosome, Arc::clone(&function_set));
+
+        let program = Program::compile(&chromosome, &function_set).unwrap();
+        assert_eq!("r1 = add r0 r0\nr2 = sin r1\noutputs: r2\n", program.to_string());
+
+        let mut interpreter = Interpreter::new();
+        interpreter.run(&program, &[vec![0.5, 1.]]);
+        assert_eq!(&[1f32.sin(), 2f32.sin()], interpreter.output(&program, &[vec![0.5, 1.]], 0));
+    }
+}
@@ -0,0 +1,193 @@
+//! Reusable storage of the node values of a forward pass; see `ArenaChromosomeEvaluator`.
+//! The values are kept in a pool of buffers. A buffer goes back to the pool as soon as the last
+//! active node that reads its value is evaluated, so the pool only grows to the number of values
+//! that are needed at the same time. The buffers keep their capacity between forward passes, so
+//! after the first forward pass nothing is allocated anymore. The inputs are read in place.
+
+use std::mem;
+use crate::components::cgp_components::cgp_node_types::NodeType;
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::function_set::function_trait::FunctionTrait;
+
+// last use of the values that are read by the output nodes; they are needed after the pass
+const AFTER_FORWARD_PASS: usize = usize::MAX;
+
+pub struct ValueArena<T> {
+    nbr_inputs: usize,
+    // buffer of the value of every active computational node, indexed by node position
+    buffer_of_node: Vec<usize>,
+    // index in `active_nodes` of the last node that reads a value, indexed by node position
+    last_use: Vec<usize>,
+    buffers: Vec<Vec<T>>,
+    free_buffers: Vec<usize>,
+    outputs: Vec<Vec<T>>,
+}
+
+impl<T: Clone> Default for ValueArena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> ValueArena<T> {
+    pub fn new() -> Self {
+        Self {
+            nbr_inputs: 0,
+            buffer_of_node: vec![],
+            last_use: vec![],
+            buffers: vec![],
+            free_buffers: vec![],
+            outputs: vec![],
+        }
+    }
+
+    /// Number of buffers; the most values that were needed at the same time so far.
+    pub fn nbr_buffers(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Evaluates the active nodes on the (transposed) inputs; `active_nodes` must be up to date.
+    /// Afterwards, the values of the output nodes can be read with `outputs_by_column` and
+    /// `outputs_by_row`.
+    pub fn forward_pass(&mut self,
+                        chromosome: &Chromosome,
+                        inputs: &[Vec<T>],
+                        function_set: &[Box<dyn FunctionTrait<T>>]) {
+        let nbr_inputs = chromosome.params.nbr_inputs;
+        let output_start_id = nbr_inputs + chromosome.params.graph_width;
+
+        self.nbr_inputs = nbr_inputs;
+        self.buffer_of_node.clear();
+        self.buffer_of_node.resize(output_start_id, usize::MAX);
+        self.last_use.clear();
+        self.last_use.resize(output_start_id, 0);
+        self.free_buffers.clear();
+        self.free_buffers.extend((0..self.buffers.len()).rev());
+
+        for (step, node_id) in chromosome.active_nodes.iter().enumerate() {
+            let node = &chromosome.nodes_grid[*node_id];
+            if node.node_type == NodeType::ComputationalNode {
+                let nbr_connections = function_set[node.function_id].get_number_inputs_needed();
+                for connection in &[node.connection0, node.connection1][..nbr_connections] {
+                    self.last_use[*connection] = step;
+                }
+            }
+        }
+        for output_id in output_start_id..(output_start_id + chromosome.params.nbr_outputs) {
+            self.last_use[chromosome.nodes_grid[output_id].connection0] = AFTER_FORWARD_PASS;
+        }
+
+        for (step, node_id) in chromosome.active_nodes.iter().enumerate() {
+            let node = &chromosome.nodes_grid[*node_id];
+            if node.node_type != NodeType::ComputationalNode {
+                continue;
+            }
+
+            let buffer_id = match self.free_buffers.pop() {
+                Some(buffer_id) => buffer_id,
+                None => {
+                    self.buffers.push(Vec::with_capacity(inputs[0].len()));
+                    self.buffers.len() - 1
+                }
+            };
+            let mut value = mem::take(&mut self.buffers[buffer_id]);
+            value.clear();
+
+            let function = &function_set[node.function_id];
+            let nbr_connections = function.get_number_inputs_needed();
+            match nbr_connections {
+                2 => function.execute_function_into(&[self.value(inputs, node.connection0),
+                                                      self.value(inputs, node.connection1)], &mut value),
+                1 => function.execute_function_into(&[self.value(inputs, node.connection0)], &mut value),
+                _ => function.execute_constant_into(node.constant, inputs[0].len(), &mut value)
+                    .expect("checked by check_constant_functions"),
+            }
+            self.buffers[buffer_id] = value;
+            self.buffer_of_node[*node_id] = buffer_id;
+
+            let connections = &[node.connection0, node.connection1][..nbr_connections];
+            for (i, connection) in connections.iter().enumerate() {
+                // a value that is read twice by the same node is freed once
+                if *connection >= nbr_inputs && self.last_use[*connection] == step && !connections[..i].contains(connection) {
+                    self.free_buffers.push(self.buffer_of_node[*connection]);
+                }
+            }
+        }
+    }
+
+    fn value<'a>(&'a self, inputs: &'a [Vec<T>], node_id: usize) -> &'a Vec<T> {
+        if node_id < self.nbr_inputs {
+            return &inputs[node_id];
+        }
+        return &self.buffers[self.buffer_of_node[node_id]];
+    }
+
+    /// Values of the first `nbr_outputs` output nodes of the last forward pass, one vector per
+    /// output node.
+    pub fn outputs_by_column(&mut self, chromosome: &Chromosome, inputs: &[Vec<T>], nbr_outputs: usize) -> &Vec<Vec<T>> {
+        let output_start_id = chromosome.params.nbr_inputs + chromosome.params.graph_width;
+
+        let mut outputs = mem::take(&mut self.outputs);
+        outputs.resize_with(nbr_outputs, Vec::new);
+        for (i, column) in outputs.iter_mut().enumerate() {
+            column.clone_from(self.value(inputs, chromosome.nodes_grid[output_start_id + i].connection0));
+        }
+        self.outputs = outputs;
+        return &self.outputs;
+    }
+
+    /// Values of the output nodes of the last forward pass, one vector per input row; the layout
+    /// of the labels.
+    pub fn outputs_by_row(&mut self, chromosome: &Chromosome, inputs: &[Vec<T>]) -> &Vec<Vec<T>> {
+        let output_start_id = chromosome.params.nbr_inputs + chromosome.params.graph_width;
+        let nbr_outputs = chromosome.params.nbr_outputs;
+
+        let mut outputs = mem::take(&mut self.outputs);
+        outputs.resize_with(inputs[0].len(), Vec::new);
+        outputs.iter_mut().for_each(|row| row.clear());
+        for output_id in output_start_id..(output_start_id + nbr_outputs) {
+            let column = self.value(inputs, chromosome.nodes_grid[output_id].connection0);
+            outputs.iter_mut().zip(column.iter()).for_each(|(row, x)| row.push(x.clone()));
+        }
+        self.outputs = outputs;
+        return &self.outputs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesStandard};
+    use crate::datasets::boolean_datasets;
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::global_params::CgpParameters;
+    use crate::utils::utility_funcs::transpose;
+
+    #[test]
+    fn buffers_are_reused_across_forward_passes() {
+        let function_set = get_boolean_function_set();
+        let active_node_func: Box<dyn ChromosomeActiveNodeTrait<bool>> = ChromosomeFindActiveNodesStandard::new();
+        let (data, _) = boolean_datasets::multiply::get_dataset();
+        let data = transpose(data);
+        let params = CgpParameters::for_tests(500, 6, 6, 4);
+
+        let mut chromosome = Chromosome::new(params, &mut ChaCha8Rng::seed_from_u64(0));
+        active_node_func.execute(&mut chromosome, Arc::clone(&function_set));
+        let nbr_computational = chromosome.active_nodes.iter()
+            .filter(|id| chromosome.nodes_grid[**id].node_type == NodeType::ComputationalNode)
+            .count();
+
+        let mut arena = ValueArena::new();
+        arena.forward_pass(&chromosome, &data, &function_set);
+        let nbr_buffers = arena.nbr_buffers();
+        assert!(nbr_buffers < nbr_computational);
+
+        let expected = arena.outputs_by_row(&chromosome, &data).clone();
+        arena.forward_pass(&chromosome, &data, &function_set);
+        assert_eq!(nbr_buffers, arena.nbr_buffers());
+        assert_eq!(&expected, arena.outputs_by_row(&chromosome, &data));
+    }
+}
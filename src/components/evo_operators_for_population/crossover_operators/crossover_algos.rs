@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::sync::Arc;
 use rand;
 use rand::Rng;
 use rand::distributions::{Distribution, Uniform};
@@ -9,10 +9,11 @@ use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::Runner;
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn single_point_crossover<T: Clone>(runner: &mut Runner<T>,
-                                        new_population: &mut Vec<Chromosome>,
-                                        find_active_node_function: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-                                        function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>,
+                                        new_population: &mut [Chromosome],
+                                        find_active_node_function: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                                        function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
                                         child1_id: usize,
                                         child2_id: usize,
                                         parent1_id: usize,
@@ -27,18 +28,19 @@ pub fn single_point_crossover<T: Clone>(runner: &mut Runner<T>,
         .nodes_grid[..crossover_point]
         .swap_with_slice(&mut cross_chromo_2.nodes_grid[..crossover_point]);
 
-    find_active_node_function.execute(&mut cross_chromo_1, Rc::clone(&function_set));
-    find_active_node_function.execute(&mut cross_chromo_2, Rc::clone(&function_set));
+    find_active_node_function.execute(&mut cross_chromo_1, Arc::clone(&function_set));
+    find_active_node_function.execute(&mut cross_chromo_2, Arc::clone(&function_set));
 
     new_population[child1_id] = cross_chromo_1;
     new_population[child2_id] = cross_chromo_2;
 }
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn multi_point_crossover<T: Clone>(runner: &mut Runner<T>,
-                                       new_population: &mut Vec<Chromosome>,
-                                       active_node_function: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-                                       function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>,
+                                       new_population: &mut [Chromosome],
+                                       active_node_function: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                                       function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
                                        child1_id: usize,
                                        child2_id: usize,
                                        parent1_id: usize,
@@ -56,18 +58,19 @@ pub fn multi_point_crossover<T: Clone>(runner: &mut Runner<T>,
 
     // cross_chromo_1.get_active_nodes_id();
     // cross_chromo_2.get_active_nodes_id();
-    active_node_function.execute(&mut cross_chromo_1, Rc::clone(&function_set));
-    active_node_function.execute(&mut cross_chromo_2, Rc::clone(&function_set));
+    active_node_function.execute(&mut cross_chromo_1, Arc::clone(&function_set));
+    active_node_function.execute(&mut cross_chromo_2, Arc::clone(&function_set));
 
     new_population[child1_id] = cross_chromo_1;
     new_population[child2_id] = cross_chromo_2;
 }
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn uniform_crossover<T: Clone>(runner: &mut Runner<T>,
-                                   new_population: &mut Vec<Chromosome>,
-                                   active_node_function: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-                                   function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>,
+                                   new_population: &mut [Chromosome],
+                                   active_node_function: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                                   function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
                                    child1_id: usize,
                                    child2_id: usize,
                                    parent1_id: usize,
@@ -88,15 +91,15 @@ pub fn uniform_crossover<T: Clone>(runner: &mut Runner<T>,
 
     // cross_chromo_1.get_active_nodes_id();
     // cross_chromo_2.get_active_nodes_id();
-    active_node_function.execute(&mut cross_chromo_1, Rc::clone(&function_set));
-    active_node_function.execute(&mut cross_chromo_2, Rc::clone(&function_set));
+    active_node_function.execute(&mut cross_chromo_1, Arc::clone(&function_set));
+    active_node_function.execute(&mut cross_chromo_2, Arc::clone(&function_set));
 
     new_population[child1_id] = cross_chromo_1;
     new_population[child2_id] = cross_chromo_2;
 }
 
 pub fn no_crossover<T: Clone>(runner: &mut Runner<T>,
-                              new_population: &mut Vec<Chromosome>,
+                              new_population: &mut [Chromosome],
                               child1_id: usize,
                               child2_id: usize,
                               parent1_id: usize,
@@ -105,7 +108,7 @@ pub fn no_crossover<T: Clone>(runner: &mut Runner<T>,
     new_population[child2_id] = runner.population[parent2_id].clone();
 }
 // pub fn subgraph_crossover(runner: &mut Runner,
-//                           new_population: &mut Vec<Chromosome>,
+//                           new_population: &mut [Chromosome],
 //                           child1_id: usize,
 //                           child2_id: usize,
 //                           parent1_id: usize,
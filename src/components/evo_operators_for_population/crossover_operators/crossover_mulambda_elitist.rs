@@ -1,4 +1,5 @@
-use std::rc::Rc;
+use std::sync::Arc;
+use rand::Rng;
 use rand::prelude::SliceRandom;
 use crate::components::cgp_components::chromosome::Chromosome;
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
@@ -18,8 +19,8 @@ impl<T: Clone> GeneralCrossoverTrait<T> for CrossoverMuLambdaElitist {
     }
 
     fn execute(&self, runner: &mut Runner<T>,
-               active_node_function: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>, ) {
+               active_node_function: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>, ) {
         // get all new children ids; i.e. the ID's of chromosomes in the population that
         // can be replaced.
         // It must exclude the elitists, otherwise they may be replaced too
@@ -30,11 +31,11 @@ impl<T: Clone> GeneralCrossoverTrait<T> for CrossoverMuLambdaElitist {
         let mut new_population: Vec<Chromosome> = runner.population.clone();
 
         for child_ids in children_set.chunks(2) {
-            let crossover_prob = rand::random::<f32>();
+            let crossover_prob = runner.rng.gen::<f32>();
 
             let parent_ids: Vec<usize> = runner.elitist_ids
                 .choose_multiple(&mut runner.rng, 2)
-                .map(|x| *x)
+                .copied()
                 .collect();
 
 
@@ -42,24 +43,24 @@ impl<T: Clone> GeneralCrossoverTrait<T> for CrossoverMuLambdaElitist {
                 match runner.params.crossover_type {
                     CrossoverType::SinglePointCrossover => single_point_crossover(runner,
                                                                                   &mut new_population,
-                                                                                  Rc::clone(&active_node_function),
-                                                                                  Rc::clone(&function_set),
+                                                                                  Arc::clone(&active_node_function),
+                                                                                  Arc::clone(&function_set),
                                                                                   child_ids[0],
                                                                                   child_ids[1],
                                                                                   parent_ids[0],
                                                                                   parent_ids[1]),
                     CrossoverType::MultiPointCrossover => multi_point_crossover(runner,
                                                                                 &mut new_population,
-                                                                                Rc::clone(&active_node_function),
-                                                                                Rc::clone(&function_set),
+                                                                                Arc::clone(&active_node_function),
+                                                                                Arc::clone(&function_set),
                                                                                 child_ids[0],
                                                                                 child_ids[1],
                                                                                 parent_ids[0],
                                                                                 parent_ids[1]),
                     CrossoverType::UniformCrossover => uniform_crossover(runner,
                                                                          &mut new_population,
-                                                                         Rc::clone(&active_node_function),
-                                                                         Rc::clone(&function_set),
+                                                                         Arc::clone(&active_node_function),
+                                                                         Arc::clone(&function_set),
                                                                          child_ids[0],
                                                                          child_ids[1],
                                                                          parent_ids[0],
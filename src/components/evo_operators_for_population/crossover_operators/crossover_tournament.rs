@@ -1,4 +1,5 @@
-use std::rc::Rc;
+use std::sync::Arc;
+use rand::Rng;
 use crate::components::cgp_components::chromosome::Chromosome;
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
 use crate::components::evo_operators_for_population::crossover_operators::crossover_algos::*;
@@ -17,8 +18,8 @@ impl<T: Clone> GeneralCrossoverTrait<T> for CrossoverTournament {
     }
 
     fn execute(&self, runner: &mut Runner<T>,
-               active_node_function: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>, ) {
+               active_node_function: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>, ) {
         // get all new children ids; i.e. the ID's of chromosomes in the population that
         // can be replaced.
         // It must exclude the elitists, otherwise they may be replaced too
@@ -29,7 +30,7 @@ impl<T: Clone> GeneralCrossoverTrait<T> for CrossoverTournament {
         let mut new_population: Vec<Chromosome> = runner.population.clone();
 
         for (i, child_ids) in children_set.chunks(2).enumerate() {
-            let crossover_prob = rand::random::<f32>();
+            let crossover_prob = runner.rng.gen::<f32>();
             let parent1_id = runner.tournament_selected.as_deref().unwrap()[2 * i];
             let parent2_id = runner.tournament_selected.as_deref().unwrap()[2 * i + 1];
 
@@ -37,24 +38,24 @@ impl<T: Clone> GeneralCrossoverTrait<T> for CrossoverTournament {
                 match runner.params.crossover_type {
                     CrossoverType::SinglePointCrossover => single_point_crossover(runner,
                                                                                   &mut new_population,
-                                                                                  Rc::clone(&active_node_function),
-                                                                                  Rc::clone(&function_set),
+                                                                                  Arc::clone(&active_node_function),
+                                                                                  Arc::clone(&function_set),
                                                                                   child_ids[0],
                                                                                   child_ids[1],
                                                                                   parent1_id,
                                                                                   parent2_id),
                     CrossoverType::MultiPointCrossover => multi_point_crossover(runner,
                                                                                 &mut new_population,
-                                                                                Rc::clone(&active_node_function),
-                                                                                Rc::clone(&function_set),
+                                                                                Arc::clone(&active_node_function),
+                                                                                Arc::clone(&function_set),
                                                                                 child_ids[0],
                                                                                 child_ids[1],
                                                                                 parent1_id,
                                                                                 parent2_id),
                     CrossoverType::UniformCrossover => uniform_crossover(runner,
                                                                          &mut new_population,
-                                                                         Rc::clone(&active_node_function),
-                                                                         Rc::clone(&function_set),
+                                                                         Arc::clone(&active_node_function),
+                                                                         Arc::clone(&function_set),
                                                                          child_ids[0],
                                                                          child_ids[1],
                                                                          parent1_id,
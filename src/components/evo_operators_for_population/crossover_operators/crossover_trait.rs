@@ -1,13 +1,14 @@
-use std::rc::Rc;
+use std::sync::Arc;
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
 use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::Runner;
 
 pub trait GeneralCrossoverTrait<T> where T: Clone
 {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn GeneralCrossoverTrait<T>> where Self: Sized;
 
     fn execute(&self, runner: &mut Runner<T>,
-               active_node_function: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>,);
+               active_node_function: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,);
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
 pub enum CrossoverType {
     SinglePointCrossover,
     MultiPointCrossover,
@@ -17,4 +19,19 @@ impl Display for CrossoverType {
             CrossoverType::NoCrossover => write!(f, "No Crossover"),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Parses the variant name, i.e. "NoCrossover"; the same names are used in experiment files.
+impl FromStr for CrossoverType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SinglePointCrossover" => Ok(CrossoverType::SinglePointCrossover),
+            "MultiPointCrossover" => Ok(CrossoverType::MultiPointCrossover),
+            "UniformCrossover" => Ok(CrossoverType::UniformCrossover),
+            "NoCrossover" => Ok(CrossoverType::NoCrossover),
+            _ => Err(format!("unknown crossover_type '{}'", s)),
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use std::rc::Rc;
-use crate::components::cgp_components::chromosome_evaluator_operators::{EvaluateChromosomeTrait, ChromosomeEvaluator};
+use std::sync::Arc;
+use rayon::ThreadPool;
+use crate::components::cgp_components::chromosome_evaluator_operators::{DatasetId, EvaluateChromosomeTrait, ChromosomeEvaluator};
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
-use crate::components::evo_operators_for_population::evaluation_operators::eval_population_trait::{GeneralForwardPassPopulationTrait, GeneralTestPopulationTrait};
+use crate::components::evo_operators_for_population::evaluation_operators::eval_population_trait::{evaluate_children, EvaluationCount, GeneralForwardPassPopulationTrait};
 use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::Runner;
 
@@ -11,39 +12,36 @@ pub struct ForwardPassPopulationTournament;
 // pub struct TestPopulationMuPlusLambda;
 // pub struct TestPopulationTournament;
 
-impl<T: Clone> GeneralForwardPassPopulationTrait<T> for ForwardPassPopulationMuPlusLambda where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
+impl<T: Clone + Send + Sync> GeneralForwardPassPopulationTrait<T> for ForwardPassPopulationMuPlusLambda where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
     fn new() -> Box<dyn GeneralForwardPassPopulationTrait<T>> where Self: Sized {
         Box::new(Self)
     }
 
     fn execute(&self, runner: &mut Runner<T>,
-               evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
+               evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               thread_pool: Option<&ThreadPool>) -> EvaluationCount {
 
+        let mut count = EvaluationCount::default();
         // for id in eval_set {
-        for id in &runner.child_ids {
-            let fitness: f32 = evaluator_function.evaluate(&mut runner.population[*id],
-                                                               Rc::clone(&active_node_func),
-                                                               &runner.data,
-                                                               &runner.label,
-                                                               Rc::clone(&function_set));
-
-            runner.fitness_vals[*id] = fitness;
-        }
+        let child_ids = runner.child_ids.clone();
+        evaluate_children(runner, &child_ids, &evaluator_function, &active_node_func, &function_set, thread_pool, &mut count);
 
         runner.sort_fitness_vals();
+        return count;
     }
 
-    fn execute_test_set(&self, runner: &mut Runner<T>, evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>, active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>, function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
+    fn execute_test_set(&self, runner: &mut Runner<T>, evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>, active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>, function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
         let mut best_fitness = f32::MAX;
         // for id in eval_set {
         for id in &runner.child_ids {
             let fitness: f32 = evaluator_function.evaluate(&mut runner.population[*id],
-                                                           Rc::clone(&active_node_func),
-                                                           &runner.eval_data.as_ref().unwrap(),
-                                                           &runner.eval_label.as_ref().unwrap(),
-                                                           Rc::clone(&function_set));
+                                                           Arc::clone(&active_node_func),
+                                                           DatasetId::Eval,
+                                                           runner.eval_data.as_ref().unwrap(),
+                                                           runner.eval_label.as_ref().unwrap(),
+                                                           Arc::clone(&function_set));
             if fitness < best_fitness {
                 best_fitness = fitness
             }
@@ -53,42 +51,43 @@ impl<T: Clone> GeneralForwardPassPopulationTrait<T> for ForwardPassPopulationMuP
         return best_fitness    }
 }
 
-impl<T: Clone> GeneralForwardPassPopulationTrait<T> for ForwardPassPopulationTournament where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
+impl<T: Clone + Send + Sync> GeneralForwardPassPopulationTrait<T> for ForwardPassPopulationTournament where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
     fn new() -> Box<dyn GeneralForwardPassPopulationTrait<T>> where Self: Sized {
         ForwardPassPopulationMuPlusLambda::new()
     }
 
     fn execute(&self, _runner: &mut Runner<T>,
-               _evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>,
-               _active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               _function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
+               _evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+               _active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               _function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               _thread_pool: Option<&ThreadPool>) -> EvaluationCount {
         panic!("How did I get here? ")
 
     }
 
-    fn execute_test_set(&self, runner: &mut Runner<T>, evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>, active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>, function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
+    fn execute_test_set(&self, _runner: &mut Runner<T>, _evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>, _active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>, _function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
         panic!("How did I get here? ")
     }
 }
 
-// impl<T: Clone> GeneralTestPopulationTrait<T> for TestPopulationMuPlusLambda where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
+// impl<T: Clone> GeneralTestPopulationTrait<T> for TestPopulationMuPlusLambda where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
 //     fn new() -> Box<dyn GeneralTestPopulationTrait<T>> where Self: Sized {
 //         Box::new(Self)
 //     }
 //
 //     fn execute(&self, runner: &mut Runner<T>,
-//                evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>,
-//                active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-//                function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
+//                evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+//                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+//                function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
 //
 //         let mut best_fitness = f32::MAX;
 //         // for id in eval_set {
 //         for id in &runner.child_ids {
 //             let fitness: f32 = evaluator_function.evaluate(&mut runner.population[*id],
-//                                                            Rc::clone(&active_node_func),
+//                                                            Arc::clone(&active_node_func),
 //                                                            &runner.eval_data.as_ref().unwrap(),
 //                                                            &runner.eval_label.as_ref().unwrap(),
-//                                                            Rc::clone(&function_set));
+//                                                            Arc::clone(&function_set));
 //             if fitness < best_fitness {
 //                 best_fitness = fitness
 //             }
@@ -99,15 +98,15 @@ impl<T: Clone> GeneralForwardPassPopulationTrait<T> for ForwardPassPopulationTou
 //     }
 // }
 
-// impl<T: Clone> GeneralTestPopulationTrait<T> for TestPopulationTournament where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
+// impl<T: Clone> GeneralTestPopulationTrait<T> for TestPopulationTournament where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
 //     fn new() -> Box<dyn GeneralTestPopulationTrait<T>> where Self: Sized {
 //         TestPopulationMuPlusLambda::new()
 //     }
 //
 //     fn execute(&self, _runner: &mut Runner<T>,
-//                _evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>,
-//                _active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-//                _function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
+//                _evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+//                _active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+//                _function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
 //         panic!("How did I get here? ")
 //
 //     }
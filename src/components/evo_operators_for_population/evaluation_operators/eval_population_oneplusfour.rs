@@ -1,7 +1,8 @@
-use std::rc::Rc;
-use crate::components::cgp_components::chromosome_evaluator_operators::{EvaluateChromosomeTrait, ChromosomeEvaluator};
+use std::sync::Arc;
+use rayon::ThreadPool;
+use crate::components::cgp_components::chromosome_evaluator_operators::{DatasetId, EvaluateChromosomeTrait, ChromosomeEvaluator};
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
-use crate::components::evo_operators_for_population::evaluation_operators::eval_population_trait::{GeneralForwardPassPopulationTrait, GeneralTestPopulationTrait};
+use crate::components::evo_operators_for_population::evaluation_operators::eval_population_trait::{evaluate_children, EvaluationCount, GeneralForwardPassPopulationTrait};
 use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::Runner;
 
@@ -9,34 +10,31 @@ pub struct EAForwardPassPopulationOnePlusFour;
 // pub struct EATestPopulationOnePlusFour;
 
 
-impl<T: Clone> GeneralForwardPassPopulationTrait<T> for EAForwardPassPopulationOnePlusFour
-where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
+impl<T: Clone + Send + Sync> GeneralForwardPassPopulationTrait<T> for EAForwardPassPopulationOnePlusFour
+where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
     fn new() -> Box<dyn GeneralForwardPassPopulationTrait<T>> where Self: Sized {
         Box::new(Self)
     }
 
     fn execute(&self, runner: &mut Runner<T>,
-               evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
+               evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               thread_pool: Option<&ThreadPool>) -> EvaluationCount {
         // because of (1+4), there should only be one elitist
         assert_eq!(1, runner.elitist_ids.len());
 
+        let mut count = EvaluationCount::default();
         let parent_id = runner.elitist_ids[0];
-        for id in 0..(runner.params.elitists + runner.params.population_size) {
-            if id != parent_id {
-                let fitness: f32 = evaluator_function.evaluate(&mut runner.population[id],
-                                                                   Rc::clone(&active_node_func),
-                                                                   &runner.data,
-                                                                   &runner.label,
-                                                                   Rc::clone(&function_set));
-                runner.fitness_vals[id] = fitness;
-            }
-        }
+        let child_ids: Vec<usize> = (0..(runner.params.elitists + runner.params.population_size))
+            .filter(|id| *id != parent_id)
+            .collect();
+        evaluate_children(runner, &child_ids, &evaluator_function, &active_node_func, &function_set, thread_pool, &mut count);
         runner.sort_fitness_vals();
+        return count;
     }
 
-    fn execute_test_set(&self, runner: &mut Runner<T>, evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>, active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>, function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
+    fn execute_test_set(&self, runner: &mut Runner<T>, evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>, active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>, function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32 {
         // because of (1+4), there should only be one elitist
         assert_eq!(1, runner.elitist_ids.len());
         let mut best_fitness = f32::MAX;
@@ -44,10 +42,11 @@ where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
 
         for id in 0..(runner.params.elitists + runner.params.population_size) {
             let fitness: f32 = evaluator_function.evaluate(&mut runner.population[id],
-                                                           Rc::clone(&active_node_func),
-                                                           &runner.eval_data.as_ref().unwrap(),
-                                                           &runner.eval_label.as_ref().unwrap(),
-                                                           Rc::clone(&function_set));
+                                                           Arc::clone(&active_node_func),
+                                                           DatasetId::Eval,
+                                                           runner.eval_data.as_ref().unwrap(),
+                                                           runner.eval_label.as_ref().unwrap(),
+                                                           Arc::clone(&function_set));
             if fitness < best_fitness {
                 best_fitness = fitness;
             }
@@ -57,15 +56,15 @@ where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
 }
 
 // impl<T: Clone> GeneralTestPopulationTrait<T> for EATestPopulationOnePlusFour
-// where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
+// where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
 //     fn new() -> Box<dyn GeneralTestPopulationTrait<T>> where Self: Sized {
 //         Box::new(Self)
 //     }
 //
 //     fn execute(&self, runner: &mut Runner<T>,
-//                evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>,
-//                active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-//                function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32{
+//                evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+//                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+//                function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32{
 //         // because of (1+4), there should only be one elitist
 //         assert_eq!(1, runner.elitist_ids.len());
 //         let mut best_fitness = f32::MAX;
@@ -73,10 +72,10 @@ where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
 //
 //         for id in 0..(runner.params.elitists + runner.params.population_size) {
 //             let fitness: f32 = evaluator_function.evaluate(&mut runner.population[id],
-//                                                            Rc::clone(&active_node_func),
+//                                                            Arc::clone(&active_node_func),
 //                                                            &runner.eval_data.as_ref().unwrap(),
 //                                                            &runner.eval_label.as_ref().unwrap(),
-//                                                            Rc::clone(&function_set));
+//                                                            Arc::clone(&function_set));
 //             if fitness < best_fitness {
 //                 best_fitness = fitness;
 //             }
@@ -86,3 +85,88 @@ where ChromosomeEvaluator: EvaluateChromosomeTrait<T> {
 //     }
 // }
 
+
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::components::cgp_components::chromosome_evaluator_operators::{ChromosomeEvaluator, EvaluateChromosomeTrait};
+    use crate::components::operator_registry::{EvolutionPipeline, EVALUATORS};
+    use crate::datasets::boolean_datasets;
+    use crate::experiment::experiment_config::OperatorConfig;
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::global_params::CgpParameters;
+    use crate::utils::fitness_metrics::BooleanMetric;
+    use crate::utils::runner::{Evolution, Runner, StoppingCriterion};
+    use super::*;
+
+    fn params() -> CgpParameters {
+        CgpParameters { mutation_rate: 0.02, ..CgpParameters::for_tests(100, 6, 6, 4) }
+    }
+
+    #[test]
+    fn unchanged_children_reuse_the_parent_fitness() {
+        let params = params();
+        let function_set = get_boolean_function_set();
+        let pipeline = EvolutionPipeline::new(&OperatorConfig::one_plus_four("point"), &params, Arc::clone(&function_set), Arc::new(BooleanMetric::Hamming)).unwrap();
+        let active_node_func = Arc::clone(&pipeline.chromosome_active_op);
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        let runner = Runner::new(params, data, label, None, None, Arc::clone(&function_set), Arc::clone(&active_node_func), Arc::clone(&pipeline.fitness_function), ChaCha8Rng::seed_from_u64(0));
+
+        let mut evolution = Evolution::new(runner, pipeline, vec![StoppingCriterion::MaxGenerations(100)]);
+        let result = evolution.run(|_, _, _| {});
+
+        assert!(result.skipped_evaluations > 0);
+        assert_eq!(5 + 4 * 100, result.fitness_evaluations + result.skipped_evaluations);
+
+        let runner = &mut evolution.runner;
+        let evaluator: Box<dyn EvaluateChromosomeTrait<bool>> = ChromosomeEvaluator::new();
+        for id in 0..5 {
+            let fitness = evaluator.evaluate(&mut runner.population[id], Arc::clone(&active_node_func), DatasetId::Train, &runner.data, &runner.label, Arc::clone(&function_set));
+            assert_eq!(fitness, runner.fitness_vals[id]);
+        }
+    }
+
+    /// Fitness values, active nodes and fitness evaluations after 300 generations.
+    fn run(evaluator: &str, threads: usize, incremental: bool, fitness_cache_size: usize) -> (Vec<f32>, Vec<Vec<usize>>, usize) {
+        let function_set = get_boolean_function_set();
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+
+        let pipeline = EvolutionPipeline::new(&OperatorConfig::one_plus_four("single"), &params(), Arc::clone(&function_set), Arc::new(BooleanMetric::Hamming))
+            .unwrap()
+            .with_evaluator(evaluator)
+            .unwrap()
+            .with_incremental_evaluation(incremental)
+            .with_fitness_cache(fitness_cache_size)
+            .with_threads(threads)
+            .unwrap();
+        let runner = Runner::new(params(), data, label, None, None, Arc::clone(&function_set), Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.fitness_function), ChaCha8Rng::seed_from_u64(0));
+
+        let mut evolution = Evolution::new(runner, pipeline, vec![StoppingCriterion::MaxGenerations(300)]);
+        let result = evolution.run(|_, _, _| {});
+        let runner = evolution.runner;
+        (runner.fitness_vals, runner.population.into_iter().map(|chromosome| chromosome.active_nodes).collect(), result.fitness_evaluations)
+    }
+
+    #[test]
+    fn parallel_evaluation_gives_the_same_run() {
+        assert_eq!(run("arena", 1, false, 0), run("arena", 4, false, 0));
+    }
+
+    #[test]
+    fn every_evaluator_gives_the_same_run() {
+        let expected = run("arena", 1, false, 0);
+        for evaluator in EVALUATORS {
+            assert_eq!(expected, run(evaluator, 1, false, 0), "{}", evaluator);
+        }
+    }
+
+    #[test]
+    fn incremental_evaluation_gives_the_same_run() {
+        let expected = run("arena", 1, false, 0);
+        assert_eq!(expected, run("arena", 1, true, 0));
+        assert_eq!(expected, run("arena", 4, true, 0));
+        assert_eq!(expected.0, run("arena", 1, true, 1_000).0);
+    }
+}
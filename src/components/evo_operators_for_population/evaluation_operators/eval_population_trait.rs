@@ -1,22 +1,165 @@
-use std::rc::Rc;
-use crate::components::cgp_components::chromosome_evaluator_operators::EvaluateChromosomeTrait;
+use std::sync::Arc;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::components::cgp_components::chromosome_evaluator_operators::{DatasetId, EvaluateChromosomeTrait};
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
 use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::Runner;
 
+/// Fitness evaluations of one generation. A child is skipped if its active genotype is identical
+/// to the one of an elitist; it gets the fitness of the elitist without a forward pass.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EvaluationCount {
+    pub evaluated: usize,
+    pub skipped: usize,
+}
+
 pub trait GeneralForwardPassPopulationTrait<T> where T: Clone
 {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn GeneralForwardPassPopulationTrait<T>> where Self: Sized;
 
+    // evaluates the children in `thread_pool` if given
     fn execute(&self, runner: &mut Runner<T>,
-               evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>);
+               evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               thread_pool: Option<&ThreadPool>) -> EvaluationCount;
 
     fn execute_test_set(&self, runner: &mut Runner<T>,
-                                       evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>,
-                                       active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-                                       function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32;
+                                       evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+                                       active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                                       function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32;
+}
+
+/// Sets the fitness of the child `id`. Reuses the fitness of an elitist with the same active
+/// genotype, otherwise evaluates the child on the training data; a mutant of a parent through
+/// `EvaluateChromosomeTrait::evaluate_mutant`.
+pub fn evaluate_child<T: Clone>(runner: &mut Runner<T>,
+                                id: usize,
+                                evaluator_function: &Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+                                active_node_func: &Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                                function_set: &Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+                                count: &mut EvaluationCount) {
+    if reuse_elitist_fitness(runner, id, active_node_func, function_set, count) {
+        return;
+    }
+
+    runner.fitness_vals[id] = match &runner.lineage[id] {
+        Some(lineage) => {
+            let (child, parent) = child_and_parent(&mut runner.population, id, lineage.parent_id);
+            evaluator_function.evaluate_mutant(child,
+                                               parent,
+                                               &lineage.mutated_nodes,
+                                               Arc::clone(active_node_func),
+                                               DatasetId::Train,
+                                               &runner.data,
+                                               &runner.label,
+                                               Arc::clone(function_set))
+        }
+        None => evaluator_function.evaluate(&mut runner.population[id],
+                                            Arc::clone(active_node_func),
+                                            DatasetId::Train,
+                                            &runner.data,
+                                            &runner.label,
+                                            Arc::clone(function_set)),
+    };
+    count.evaluated += 1;
+}
+
+fn child_and_parent(population: &mut [Chromosome], child_id: usize, parent_id: usize) -> (&mut Chromosome, &Chromosome) {
+    assert_ne!(child_id, parent_id, "a child cannot be its own parent");
+    if child_id < parent_id {
+        let (children, parents) = population.split_at_mut(parent_id);
+        return (&mut children[child_id], &parents[0]);
+    }
+    let (parents, children) = population.split_at_mut(child_id);
+    return (&mut children[0], &parents[parent_id]);
+}
+
+/// `evaluate_child` for each of the children `ids`. With a thread pool, the children that need a
+/// forward pass are evaluated in parallel; the fitness values are the same as without.
+pub fn evaluate_children<T: Clone + Send + Sync>(runner: &mut Runner<T>,
+                                                 ids: &[usize],
+                                                 evaluator_function: &Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+                                                 active_node_func: &Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                                                 function_set: &Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+                                                 thread_pool: Option<&ThreadPool>,
+                                                 count: &mut EvaluationCount) {
+    let thread_pool = match thread_pool {
+        Some(thread_pool) => thread_pool,
+        None => {
+            for id in ids {
+                evaluate_child(runner, *id, evaluator_function, active_node_func, function_set, count);
+            }
+            return;
+        }
+    };
+
+    let mut needs_forward_pass = vec![false; runner.population.len()];
+    for id in ids {
+        needs_forward_pass[*id] = !reuse_elitist_fitness(runner, *id, active_node_func, function_set, count);
+    }
+
+    // the children are borrowed mutably and the rest of the population, i.e. their parents, shared
+    let mut parents: Vec<Option<&Chromosome>> = vec![None; runner.population.len()];
+    let mut children: Vec<(usize, &mut Chromosome)> = vec![];
+    for (id, chromosome) in runner.population.iter_mut().enumerate() {
+        if needs_forward_pass[id] {
+            children.push((id, chromosome));
+        } else {
+            parents[id] = Some(chromosome);
+        }
+    }
+
+    let (lineage, data, label) = (&runner.lineage, &runner.data, &runner.label);
+    let fitness_vals: Vec<(usize, f32)> = thread_pool.install(|| children
+        .par_iter_mut()
+        .map(|(id, chromosome)| (*id, match &lineage[*id] {
+            Some(lineage) => evaluator_function.evaluate_mutant(chromosome,
+                                                                parents[lineage.parent_id].expect("a parent is not evaluated with its children"),
+                                                                &lineage.mutated_nodes,
+                                                                Arc::clone(active_node_func),
+                                                                DatasetId::Train,
+                                                                data,
+                                                                label,
+                                                                Arc::clone(function_set)),
+            None => evaluator_function.evaluate(chromosome,
+                                                Arc::clone(active_node_func),
+                                                DatasetId::Train,
+                                                data,
+                                                label,
+                                                Arc::clone(function_set)),
+        }))
+        .collect());
+
+    for (id, fitness) in fitness_vals {
+        runner.fitness_vals[id] = fitness;
+        count.evaluated += 1;
+    }
+}
+
+/// Updates the active nodes of the child `id` and gives it the fitness and the linear scaling of
+/// an elitist with the same active genotype, if there is one. Returns false if the child needs a
+/// forward pass.
+fn reuse_elitist_fitness<T: Clone>(runner: &mut Runner<T>,
+                                   id: usize,
+                                   active_node_func: &Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                                   function_set: &Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+                                   count: &mut EvaluationCount) -> bool {
+    active_node_func.execute(&mut runner.population[id], Arc::clone(function_set));
+
+    let same_elitist = runner.elitist_ids
+        .iter()
+        .find(|elitist_id| runner.population[id].same_active_genotype(&runner.population[**elitist_id], function_set));
+    if let Some(elitist_id) = same_elitist {
+        runner.fitness_vals[id] = runner.fitness_vals[*elitist_id];
+        runner.population[id].linear_scaling = runner.population[*elitist_id].linear_scaling.clone();
+        count.skipped += 1;
+        return true;
+    }
+    return false;
 }
 
 // pub trait GeneralTestPopulationTrait<T> where T: Clone
@@ -24,7 +167,7 @@ pub trait GeneralForwardPassPopulationTrait<T> where T: Clone
 //     fn new() -> Box<dyn GeneralTestPopulationTrait<T>> where Self: Sized;
 //
 //     fn execute(&self, runner: &mut Runner<T>,
-//                evaluator_function: Rc<Box<dyn EvaluateChromosomeTrait<T>>>,
-//                active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-//                function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32;
+//                evaluator_function: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+//                active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+//                function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> f32;
 // }
\ No newline at end of file
@@ -1,8 +1,9 @@
 use rand::prelude::SliceRandom;
-use crate::utils::runner::Runner;
+use crate::utils::runner::{Lineage, Runner};
 
 pub trait ClonePopulationTrait<T> where T: Clone
 {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn ClonePopulationTrait<T>> where Self: Sized;
 
     fn execute(&self, runner: &mut Runner<T>);
@@ -22,14 +23,14 @@ impl<T: Clone> ClonePopulationTrait<T> for CloneParentToChild {
     fn execute(&self, runner: &mut Runner<T>) {
         for id in &runner.child_ids {
             // get the parent Id, so it can be cloned
-            let parent_id: usize;
-            if runner.params.elitists == 1 {
+            let parent_id: usize = if runner.params.elitists == 1 {
                 // case: (1+4) ES; only one parent available to clone
-                parent_id = runner.elitist_ids[0];
+                runner.elitist_ids[0]
             } else {
-                parent_id = *runner.elitist_ids.choose(&mut runner.rng).unwrap();
-            }
+                *runner.elitist_ids.choose(&mut runner.rng).unwrap()
+            };
             runner.population[*id] = runner.population[parent_id].clone();
+            runner.lineage[*id] = Some(Lineage { parent_id, mutated_nodes: vec![] });
         }
     }
 }
\ No newline at end of file
@@ -1,2 +1,3 @@
 pub mod clone_parent_to_child;
 pub mod reorder_population;
+pub mod tune_constants;
@@ -1,4 +1,5 @@
 use std::rc::Rc;
+use std::sync::Arc;
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
 use crate::components::cgp_components::chromosome_reorder_operators::ChromosomeReorderTrait;
 use crate::function_set::function_trait::FunctionTrait;
@@ -6,13 +7,14 @@ use crate::utils::runner::Runner;
 
 pub trait GeneralReorderPopulationTrait<T> where T: Clone
 {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn GeneralReorderPopulationTrait<T>> where Self: Sized;
 
     fn execute(&self,
                runner: &mut Runner<T>,
                chromosome_reorder_op: Rc<Box<dyn ChromosomeReorderTrait<T>>>,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>);
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>);
 }
 
 pub struct ReorderPopulation;
@@ -25,12 +27,15 @@ impl<T: Clone> GeneralReorderPopulationTrait<T> for ReorderPopulation {
     fn execute(&self,
                runner: &mut Runner<T>,
                chromosome_reorder_op: Rc<Box<dyn ChromosomeReorderTrait<T>>>,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>) {
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) {
         for id in &runner.child_ids {
             chromosome_reorder_op.execute(&mut runner.population[*id],
-                                          Rc::clone(&active_node_func),
-                                          Rc::clone(&function_set));
+                                          Arc::clone(&active_node_func),
+                                          Arc::clone(&function_set),
+                                          &mut runner.rng);
+            // the nodes moved, so the node values of the parent do not apply anymore
+            runner.lineage[*id] = None;
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use crate::components::cgp_components::chromosome_evaluator_operators::{DatasetId, EvaluateChromosomeTrait};
+use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
+use crate::components::cgp_components::constant_tuning::{tune_constants, ConstantOptimizer};
+use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::numeric::Numeric;
+use crate::utils::runner::Runner;
+
+pub trait GeneralTuneConstantsTrait<T> where T: Clone
+{
+    /// Called after the selection of `generation`; returns the number of fitness evaluations.
+    fn execute(&self,
+               generation: usize,
+               runner: &mut Runner<T>,
+               chromosome_eval_op: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> usize;
+}
+
+/// Tunes the constants of the best elitist every `interval` generations with `steps` steps of
+/// `optimizer`; see `constant_tuning`. The optimizers lower the mean squared error, so the tuned
+/// chromosome is evaluated with the evaluator of the run and only replaces the elitist if its
+/// fitness is better.
+pub struct TuneBestConstants {
+    pub interval: usize,
+    pub optimizer: ConstantOptimizer,
+    pub steps: usize,
+}
+
+impl TuneBestConstants {
+    pub fn new(interval: usize, optimizer: ConstantOptimizer, steps: usize) -> Self {
+        Self { interval, optimizer, steps }
+    }
+}
+
+impl<T: Numeric> GeneralTuneConstantsTrait<T> for TuneBestConstants {
+    fn execute(&self,
+               generation: usize,
+               runner: &mut Runner<T>,
+               chromosome_eval_op: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> usize {
+        if !generation.is_multiple_of(self.interval) {
+            return 0;
+        }
+
+        let best_id = *runner.elitist_ids
+            .iter()
+            .min_by(|a, b| runner.fitness_vals[**a].total_cmp(&runner.fitness_vals[**b]))
+            .unwrap();
+        let mut tuned = runner.population[best_id].clone();
+        active_node_func.execute(&mut tuned, Arc::clone(&function_set));
+        if !tune_constants(&mut tuned, &runner.data, &runner.label, &function_set, &self.optimizer, self.steps) {
+            return 0;
+        }
+
+        let fitness = chromosome_eval_op.evaluate(&mut tuned, active_node_func, DatasetId::Train, &runner.data, &runner.label, function_set);
+        if fitness < runner.fitness_vals[best_id] {
+            runner.population[best_id] = tuned;
+            runner.fitness_vals[best_id] = fitness;
+            runner.sort_fitness_vals();
+        }
+        return 1;
+    }
+}
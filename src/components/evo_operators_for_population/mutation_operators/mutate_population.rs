@@ -1,10 +1,9 @@
-use std::fs::File;
 use std::rc::Rc;
 use crate::components::cgp_components::cgp_node_mutation_operators::NodeMutationOperatorTrait;
 use crate::components::cgp_components::chromosome_mutation_operators::ChromosomeMutationTrait;
 use crate::components::evo_operators_for_population::mutation_operators::mutation_trait::GeneralMutatePopulationTrait;
+use crate::utils::observer::{EvolutionObserver, MutationEvent};
 use crate::utils::runner::Runner;
-use std::io::BufWriter;
 pub struct EAMutateStandard;
 
 impl<T: Clone> GeneralMutatePopulationTrait<T> for EAMutateStandard {
@@ -15,13 +14,17 @@ impl<T: Clone> GeneralMutatePopulationTrait<T> for EAMutateStandard {
     fn execute(&mut self, runner: &mut Runner<T>,
                node_mutation_op: Rc<Box<dyn NodeMutationOperatorTrait>>,
                chromosome_mutation_op: Rc<Box<dyn ChromosomeMutationTrait>>,
-               output_file: &mut BufWriter<File>
+               observer: &mut dyn EvolutionObserver<T>
     ) {
         for id in &runner.child_ids {
-            chromosome_mutation_op.execute(&mut runner.population[*id],
-                                           Rc::clone(&node_mutation_op),
-                                            output_file,
+            let report = chromosome_mutation_op.execute(&mut runner.population[*id],
+                                                        Rc::clone(&node_mutation_op),
+                                                        &mut runner.rng,
             );
+            observer.on_mutation(&MutationEvent { chromosome_id: *id, report: &report });
+            if let Some(lineage) = &mut runner.lineage[*id] {
+                lineage.mutated_nodes.extend(report.mutated_nodes());
+            }
         }
     }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
+use std::sync::Arc;
 use crate::components::evo_operators_for_population::selection_operators::selection_trait::GeneralSelectionTrait;
+use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::Runner;
 use crate::utils::utility_funcs::{get_argmins_of_value, vect_difference};
 
@@ -10,7 +12,7 @@ impl<T: Clone> GeneralSelectionTrait<T> for ElitistSelectionMuPlusLambda {
         Box::new(Self)
     }
 
-    fn execute(&self, runner: &mut Runner<T>) {
+    fn execute(&self, runner: &mut Runner<T>, _function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) {
         // Get mu - many best fitness vals
         let mut sorted_fitness_vals: Vec<f32> = runner.fitness_vals_sorted.clone();
         // remove duplicates
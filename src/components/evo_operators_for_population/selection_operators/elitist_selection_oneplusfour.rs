@@ -1,5 +1,7 @@
+use std::sync::Arc;
 use rand::prelude::SliceRandom;
 use crate::components::evo_operators_for_population::selection_operators::selection_trait::GeneralSelectionTrait;
+use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::Runner;
 use crate::utils::utility_funcs::get_argmins_of_value;
 
@@ -10,7 +12,7 @@ impl<T: Clone> GeneralSelectionTrait<T> for EAElitistSelectionOnePlusFour {
         Box::new(Self)
     }
 
-    fn execute(&self, runner: &mut Runner<T>) {
+    fn execute(&self, runner: &mut Runner<T>, _function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) {
         // get the first element of the sorted fitness vals
         // that must be the lowest value, and the fitness of the parent
         // (as there is only one parent)
@@ -1,5 +1,7 @@
+use std::sync::Arc;
 use rand::prelude::IteratorRandom;
 use crate::components::evo_operators_for_population::selection_operators::selection_trait::GeneralSelectionTrait;
+use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::Runner;
 use crate::utils::utility_funcs::{get_argmins_of_value, vect_difference};
 
@@ -13,7 +15,7 @@ impl<T: Clone> GeneralSelectionTrait<T> for ElitistSelectionWithTournament {
         Box::new(Self)
     }
 
-    fn execute(&self, runner: &mut Runner<T>) {
+    fn execute(&self, runner: &mut Runner<T>, _function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) {
         assert!(runner.params.tournament_size > 0);
 
         // Elitists:
@@ -2,4 +2,5 @@ pub mod elitist_selection_oneplusfour;
 pub mod elitist_selection_mupluslambda;
 pub mod tournament_selection;
 pub mod elitist_selection_tournament;
-pub mod selection_trait;
\ No newline at end of file
+pub mod selection_trait;
+pub mod nsga2_selection;
\ No newline at end of file
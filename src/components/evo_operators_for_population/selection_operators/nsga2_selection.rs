@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use crate::components::cgp_components::objectives::{crowding_distances, non_dominated_sort, objective_values, Objective};
+use crate::components::evo_operators_for_population::selection_operators::selection_trait::GeneralSelectionTrait;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::runner::Runner;
+use crate::utils::utility_funcs::vect_difference;
+
+/// NSGA-II survivor selection of a (mu + lambda)-ES: the elitists are chosen from the elitists
+/// and children by their front of the non-dominated sort of the fitness and `objectives`, see
+/// `objectives`, and within the last front that fits by their crowding distance.
+/// Ties of the crowding distance go to the better fitness, so the best chromosome always
+/// survives, and then to the children, i.e. with neutral search.
+/// `Runner::fitness_vals` stays the fitness of the chromosomes, the other objectives are
+/// computed from their active nodes.
+pub struct NSGA2Selection {
+    objectives: Vec<Objective>,
+}
+
+impl NSGA2Selection {
+    pub fn with_objectives<T: Clone>(objectives: Vec<Objective>) -> Box<dyn GeneralSelectionTrait<T>> {
+        Box::new(Self { objectives })
+    }
+}
+
+impl<T: Clone> GeneralSelectionTrait<T> for NSGA2Selection {
+    /// All secondary objectives, as for an experiment without `objectives`.
+    fn new() -> Box<dyn GeneralSelectionTrait<T>> where Self: Sized {
+        Box::new(Self { objectives: vec![Objective::ActiveNodes, Objective::Depth] })
+    }
+
+    fn execute(&self, runner: &mut Runner<T>, function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) {
+        let nbr_chromosomes = runner.params.elitists + runner.params.population_size;
+        let values: Vec<Vec<f32>> = (0..nbr_chromosomes)
+            .map(|id| objective_values(runner.fitness_vals[id], &runner.population[id], &self.objectives, &function_set))
+            .collect();
+
+        let mut new_parent_ids: Vec<usize> = Vec::with_capacity(runner.params.elitists);
+        for mut front in non_dominated_sort(&values) {
+            let remaining_new_parent_spaces = runner.params.elitists - new_parent_ids.len();
+            if front.len() > remaining_new_parent_spaces {
+                let distances = crowding_distances(&front, &values);
+                let mut order: Vec<usize> = (0..front.len()).collect();
+                order.sort_by(|a, b| distances[*b].total_cmp(&distances[*a])
+                    .then(values[front[*a]][0].total_cmp(&values[front[*b]][0]))
+                    .then(runner.elitist_ids.contains(&front[*a]).cmp(&runner.elitist_ids.contains(&front[*b]))));
+                front = order.iter().take(remaining_new_parent_spaces).map(|i| front[*i]).collect();
+            }
+            new_parent_ids.extend(front);
+
+            if new_parent_ids.len() == runner.params.elitists {
+                break;
+            }
+        }
+        assert_eq!(runner.elitist_ids.len(), new_parent_ids.len());
+        runner.elitist_ids = new_parent_ids;
+
+        let child_ids: Vec<usize> = (0..nbr_chromosomes).collect();
+        let child_ids = vect_difference(&child_ids, &runner.elitist_ids);
+        runner.child_ids = child_ids;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::components::cgp_components::objectives::dominates;
+    use crate::components::operator_registry::EvolutionPipeline;
+    use crate::datasets::boolean_datasets;
+    use crate::experiment::experiment_config::OperatorConfig;
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::global_params::CgpParameters;
+    use crate::utils::fitness_metrics::BooleanMetric;
+    use crate::utils::runner::{Evolution, Runner, StoppingCriterion};
+    use super::*;
+
+    #[test]
+    fn keeps_the_best_fitness_and_no_dominated_elitists() {
+        let params = CgpParameters { elitists: 4, population_size: 8, ..CgpParameters::for_tests(100, 6, 6, 4) };
+        let operators = OperatorConfig {
+            evaluation: "mu_plus_lambda".to_string(),
+            selection: "nsga2".to_string(),
+            ..OperatorConfig::one_plus_four("single")
+        };
+        let function_set = get_boolean_function_set();
+        let pipeline = EvolutionPipeline::new(&operators, &params, Arc::clone(&function_set), Arc::new(BooleanMetric::Hamming)).unwrap();
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        let runner = Runner::new(params, data, label, None, None, Arc::clone(&function_set), Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.fitness_function), ChaCha8Rng::seed_from_u64(0));
+
+        let mut evolution = Evolution::new(runner, pipeline, vec![StoppingCriterion::MaxGenerations(300)]);
+        let mut best_fitness = f32::MAX;
+        evolution.run(|result, _, _| {
+            assert!(result.best_fitness <= best_fitness);
+            best_fitness = result.best_fitness;
+        });
+
+        let runner = &evolution.runner;
+        assert!(runner.elitist_ids.iter().any(|id| runner.fitness_vals[*id] == runner.get_best_fitness()));
+        let objectives = [Objective::ActiveNodes, Objective::Depth];
+        let values: Vec<Vec<f32>> = (0..12)
+            .map(|id| objective_values(runner.fitness_vals[id], &runner.population[id], &objectives, &function_set))
+            .collect();
+        for elitist_id in &runner.elitist_ids {
+            assert!(runner.child_ids.iter().all(|child_id| !dominates(&values[*child_id], &values[*elitist_id])));
+        }
+    }
+}
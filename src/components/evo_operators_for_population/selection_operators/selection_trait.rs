@@ -1,8 +1,11 @@
+use std::sync::Arc;
+use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::Runner;
 
 pub trait GeneralSelectionTrait<T>
 {
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<dyn GeneralSelectionTrait<T>> where Self: Sized;
 
-    fn execute(&self, runner: &mut Runner<T>);
+    fn execute(&self, runner: &mut Runner<T>, function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>);
 }
\ No newline at end of file
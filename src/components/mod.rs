@@ -1,3 +1,4 @@
 
 pub mod cgp_components;
 pub mod evo_operators_for_population;
+pub mod operator_registry;
@@ -0,0 +1,471 @@
+//! Maps operator names (as used in experiment files and on the command line) to the operators of
+//! the library and assembles them to an `EvolutionPipeline`.
+//! `check_operator_compatibility` rejects combinations that would panic or silently do something
+//! else than requested, i.e. a DAG graph with standard mutation.
+
+use std::rc::Rc;
+use std::sync::Arc;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use crate::components::cgp_components::cgp_node_mutation_operators::{NodeMutationDAG, NodeMutationOperatorTrait, NodeMutationStandard};
+use crate::components::cgp_components::cgp_types::CGPType;
+use crate::components::cgp_components::chromosome_evaluator_operators::{ArenaChromosomeEvaluator, CachedChromosomeEvaluator, ChromosomeEvaluator, CompiledChromosomeEvaluator, EvaluateChromosomeTrait, IncrementalChromosomeEvaluator};
+use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesDAG, ChromosomeFindActiveNodesStandard};
+use crate::components::cgp_components::chromosome_mutation_operators::{ChromosomeMutationMultiN, ChromosomeMutationPoint, ChromosomeMutationSingle, ChromosomeMutationSplit, ChromosomeMutationTrait};
+use crate::components::cgp_components::objectives::Objective;
+use crate::components::cgp_components::program::ProgramValue;
+use crate::components::cgp_components::chromosome_reorder_operators::{ChromosomeReorderEquidistant, ChromosomeReorderLeftSkewed, ChromosomeReorderNegativeBias, ChromosomeReorderNormalDistribution, ChromosomeReorderStandard, ChromosomeReorderTrait};
+use crate::components::evo_operators_for_population::crossover_operators::crossover_mulambda_elitist::CrossoverMuLambdaElitist;
+use crate::components::evo_operators_for_population::crossover_operators::crossover_tournament::CrossoverTournament;
+use crate::components::evo_operators_for_population::crossover_operators::crossover_trait::GeneralCrossoverTrait;
+use crate::components::evo_operators_for_population::crossover_operators::crossover_types::CrossoverType;
+use crate::components::evo_operators_for_population::evaluation_operators::eval_population_mupluslambda::ForwardPassPopulationMuPlusLambda;
+use crate::components::evo_operators_for_population::evaluation_operators::eval_population_oneplusfour::EAForwardPassPopulationOnePlusFour;
+use crate::components::evo_operators_for_population::evaluation_operators::eval_population_trait::GeneralForwardPassPopulationTrait;
+use crate::components::evo_operators_for_population::general_operators::clone_parent_to_child::{CloneParentToChild, ClonePopulationTrait};
+use crate::components::evo_operators_for_population::general_operators::reorder_population::{GeneralReorderPopulationTrait, ReorderPopulation};
+use crate::components::evo_operators_for_population::general_operators::tune_constants::GeneralTuneConstantsTrait;
+use crate::components::evo_operators_for_population::mutation_operators::mutate_population::EAMutateStandard;
+use crate::components::evo_operators_for_population::mutation_operators::mutation_trait::GeneralMutatePopulationTrait;
+use crate::components::evo_operators_for_population::selection_operators::elitist_selection_mupluslambda::ElitistSelectionMuPlusLambda;
+use crate::components::evo_operators_for_population::selection_operators::elitist_selection_oneplusfour::EAElitistSelectionOnePlusFour;
+use crate::components::evo_operators_for_population::selection_operators::elitist_selection_tournament::ElitistSelectionWithTournament;
+use crate::components::evo_operators_for_population::selection_operators::nsga2_selection::NSGA2Selection;
+use crate::components::evo_operators_for_population::selection_operators::selection_trait::GeneralSelectionTrait;
+use crate::experiment::experiment_config::OperatorConfig;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::fitness_trait::FitnessFunction;
+use crate::utils::numeric::Numeric;
+use crate::global_params::CgpParameters;
+
+pub const NODE_MUTATION_OPERATORS: [&str; 2] = ["standard", "dag"];
+pub const ACTIVE_NODE_OPERATORS: [&str; 2] = ["standard", "dag"];
+pub const CHROMOSOME_MUTATION_OPERATORS: [&str; 4] = ["single", "point", "multi", "split"];
+pub const REORDER_OPERATORS: [&str; 6] = ["none", "standard", "equidistant", "negative_bias", "uniform", "left_skewed"];
+pub const CROSSOVER_OPERATORS: [&str; 3] = ["none", "mu_lambda_elitist", "tournament"];
+pub const EVALUATION_OPERATORS: [&str; 2] = ["one_plus_four", "mu_plus_lambda"];
+pub const SELECTION_OPERATORS: [&str; 4] = ["one_plus_four", "mu_plus_lambda", "tournament", "nsga2"];
+pub const EVALUATORS: [&str; 3] = ["map", "arena", "compiled"];
+
+
+/// All operators needed for one generation: (clone | crossover) -> reorder -> mutate -> evaluate
+/// -> select -> tune constants.
+pub struct EvolutionPipeline<T: Clone> {
+    pub function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+    pub node_mutation_op: Rc<Box<dyn NodeMutationOperatorTrait>>,
+    pub chromosome_mutation_op: Rc<Box<dyn ChromosomeMutationTrait>>,
+    pub chromosome_active_op: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+    pub chromosome_eval_op: Arc<Box<dyn EvaluateChromosomeTrait<T>>>,
+    // the metric of every evaluator, including the one of the initial population; see `Runner`
+    pub fitness_function: Arc<dyn FitnessFunction<T>>,
+    pub chromosome_reorder_op: Option<Rc<Box<dyn ChromosomeReorderTrait<T>>>>,
+    pub clone_operator: Box<dyn ClonePopulationTrait<T>>,
+    pub crossover_operator: Option<Box<dyn GeneralCrossoverTrait<T>>>,
+    pub reorder_operator: Box<dyn GeneralReorderPopulationTrait<T>>,
+    pub mutation_operator: Box<dyn GeneralMutatePopulationTrait<T>>,
+    pub eval_operator: Box<dyn GeneralForwardPassPopulationTrait<T>>,
+    pub selection_operator: Box<dyn GeneralSelectionTrait<T>>,
+    // regression with constants only; `None` leaves the constants to the mutation
+    pub tune_constants_operator: Option<Box<dyn GeneralTuneConstantsTrait<T>>>,
+    // the tournament crossover needs the parents of the tournament selection
+    pub select_before_first_generation: bool,
+    // evaluates the children in parallel; `None` evaluates them one after another
+    pub thread_pool: Option<ThreadPool>,
+}
+
+impl<T: ProgramValue + Send + Sync + 'static> EvolutionPipeline<T>
+where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      ArenaChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      CompiledChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      IncrementalChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
+    /// Checks the combination of operators and creates all of them. The chromosomes are
+    /// evaluated with `fitness_function`.
+    pub fn new(operators: &OperatorConfig,
+               params: &CgpParameters,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               fitness_function: Arc<dyn FitnessFunction<T>>) -> Result<Self, String> {
+        check_operator_compatibility(operators, params)?;
+        let selection_operator = get_selection_operator(&operators.selection, operators.objectives()?)?;
+
+        Ok(Self {
+            function_set,
+            node_mutation_op: get_node_mutation_operator(&operators.node_mutation)?,
+            chromosome_mutation_op: get_chromosome_mutation_operator(&operators.mutation_type)?,
+            chromosome_active_op: get_active_node_operator(&operators.active_nodes)?,
+            chromosome_eval_op: Arc::new(Box::new(ArenaChromosomeEvaluator::with_fitness_function(Arc::clone(&fitness_function)))),
+            fitness_function,
+            chromosome_reorder_op: get_reorder_operator(&operators.reorder)?,
+            clone_operator: CloneParentToChild::new(),
+            crossover_operator: get_crossover_operator(&operators.crossover)?,
+            reorder_operator: ReorderPopulation::new(),
+            mutation_operator: EAMutateStandard::new(),
+            eval_operator: get_evaluation_operator(&operators.evaluation)?,
+            selection_operator,
+            tune_constants_operator: None,
+            select_before_first_generation: operators.crossover == "tournament",
+            thread_pool: None,
+        })
+    }
+
+    /// Evaluates the chromosomes with the evaluator `name`, see `EVALUATORS`; all of them give the
+    /// same fitness values. Must be called before `with_incremental_evaluation` and
+    /// `with_fitness_cache`.
+    pub fn with_evaluator(mut self, name: &str) -> Result<Self, String> {
+        self.chromosome_eval_op = get_chromosome_evaluator(name, Arc::clone(&self.fitness_function))?;
+        return Ok(self);
+    }
+
+    /// Evaluates the mutants of a generation from the node values of their parent; see
+    /// `IncrementalChromosomeEvaluator`. Must be called before `with_fitness_cache`.
+    pub fn with_incremental_evaluation(mut self, incremental: bool) -> Self {
+        if incremental {
+            self.chromosome_eval_op = Arc::new(Box::new(IncrementalChromosomeEvaluator::with_capacity(IncrementalChromosomeEvaluator::<T>::DEFAULT_CAPACITY, Arc::clone(&self.fitness_function))));
+        }
+        return self;
+    }
+
+    /// Evaluates chromosomes through a fitness cache with `capacity` entries in front of the
+    /// current evaluator; see `CachedChromosomeEvaluator`. A capacity of 0 keeps the evaluator
+    /// without a cache.
+    pub fn with_fitness_cache(mut self, capacity: usize) -> Self {
+        if capacity > 0 {
+            self.chromosome_eval_op = Arc::new(Box::new(CachedChromosomeEvaluator::with_capacity(capacity, Arc::clone(&self.chromosome_eval_op))));
+        }
+        return self;
+    }
+
+    /// Evaluates the children of a generation with `threads` threads; 1 evaluates them one after
+    /// another. The results are the same for every number of threads.
+    pub fn with_threads(mut self, threads: usize) -> Result<Self, String> {
+        self.thread_pool = match threads {
+            0 => return Err("threads must be > 0".to_string()),
+            1 => None,
+            _ => Some(ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(|e| format!("cannot start {} threads: {}", threads, e))?),
+        };
+        return Ok(self);
+    }
+}
+
+impl<T: Numeric> EvolutionPipeline<T> {
+    /// Tunes the constants of the best elitist after the selection, see `TuneBestConstants`;
+    /// `None` never tunes them.
+    pub fn with_constant_tuning(mut self, tune_constants_operator: Option<Box<dyn GeneralTuneConstantsTrait<T>>>) -> Self {
+        self.tune_constants_operator = tune_constants_operator;
+        return self;
+    }
+}
+
+fn unknown_operator(kind: &str, name: &str, allowed: &[&str]) -> String {
+    format!("unknown {} operator '{}', allowed: {:?}", kind, name, allowed)
+}
+
+pub fn get_node_mutation_operator(name: &str) -> Result<Rc<Box<dyn NodeMutationOperatorTrait>>, String> {
+    match name {
+        "standard" => Ok(Rc::new(NodeMutationStandard::new())),
+        "dag" => Ok(Rc::new(NodeMutationDAG::new())),
+        _ => Err(unknown_operator("node mutation", name, &NODE_MUTATION_OPERATORS)),
+    }
+}
+
+pub fn get_chromosome_mutation_operator(name: &str) -> Result<Rc<Box<dyn ChromosomeMutationTrait>>, String> {
+    match name {
+        "single" => Ok(Rc::new(ChromosomeMutationSingle::new())),
+        "point" => Ok(Rc::new(ChromosomeMutationPoint::new())),
+        "multi" => Ok(Rc::new(ChromosomeMutationMultiN::new())),
+        "split" => Ok(Rc::new(ChromosomeMutationSplit::new())),
+        _ => Err(unknown_operator("chromosome mutation", name, &CHROMOSOME_MUTATION_OPERATORS)),
+    }
+}
+
+pub fn get_active_node_operator<T>(name: &str) -> Result<Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>, String> {
+    match name {
+        "standard" => Ok(Arc::new(ChromosomeFindActiveNodesStandard::new())),
+        "dag" => Ok(Arc::new(ChromosomeFindActiveNodesDAG::new())),
+        _ => Err(unknown_operator("active node", name, &ACTIVE_NODE_OPERATORS)),
+    }
+}
+
+/// `"none"` returns `None`, i.e. the genome is never reordered.
+#[allow(clippy::type_complexity)]
+pub fn get_reorder_operator<T>(name: &str) -> Result<Option<Rc<Box<dyn ChromosomeReorderTrait<T>>>>, String> {
+    match name {
+        "none" => Ok(None),
+        "standard" => Ok(Some(Rc::new(ChromosomeReorderStandard::new()))),
+        "equidistant" => Ok(Some(Rc::new(ChromosomeReorderEquidistant::new()))),
+        "negative_bias" => Ok(Some(Rc::new(ChromosomeReorderNegativeBias::new()))),
+        "uniform" => Ok(Some(Rc::new(ChromosomeReorderNormalDistribution::new()))),
+        "left_skewed" => Ok(Some(Rc::new(ChromosomeReorderLeftSkewed::new()))),
+        _ => Err(unknown_operator("reorder", name, &REORDER_OPERATORS)),
+    }
+}
+
+/// `"none"` returns `None`, i.e. children are clones of the elitists.
+pub fn get_crossover_operator<T: Clone>(name: &str) -> Result<Option<Box<dyn GeneralCrossoverTrait<T>>>, String> {
+    match name {
+        "none" => Ok(None),
+        "mu_lambda_elitist" => Ok(Some(CrossoverMuLambdaElitist::new())),
+        "tournament" => Ok(Some(CrossoverTournament::new())),
+        _ => Err(unknown_operator("crossover", name, &CROSSOVER_OPERATORS)),
+    }
+}
+
+pub fn get_evaluation_operator<T: Clone + Send + Sync>(name: &str) -> Result<Box<dyn GeneralForwardPassPopulationTrait<T>>, String>
+where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
+    match name {
+        "one_plus_four" => Ok(EAForwardPassPopulationOnePlusFour::new()),
+        "mu_plus_lambda" => Ok(ForwardPassPopulationMuPlusLambda::new()),
+        _ => Err(unknown_operator("evaluation", name, &EVALUATION_OPERATORS)),
+    }
+}
+
+/// "map" keeps the node values of a forward pass in a map, "arena" in buffers that are reused and
+/// "compiled" runs the compiled program of the active nodes; see `CompiledChromosomeEvaluator`.
+#[allow(clippy::type_complexity)]
+pub fn get_chromosome_evaluator<T: ProgramValue + 'static>(name: &str, fitness_function: Arc<dyn FitnessFunction<T>>) -> Result<Arc<Box<dyn EvaluateChromosomeTrait<T>>>, String>
+where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      ArenaChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      CompiledChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
+    match name {
+        "map" => Ok(Arc::new(Box::new(ChromosomeEvaluator::with_fitness_function(fitness_function)))),
+        "arena" => Ok(Arc::new(Box::new(ArenaChromosomeEvaluator::with_fitness_function(fitness_function)))),
+        "compiled" => Ok(Arc::new(Box::new(CompiledChromosomeEvaluator::with_fitness_function(fitness_function)))),
+        _ => Err(format!("unknown evaluator '{}', allowed: {:?}", name, EVALUATORS)),
+    }
+}
+
+/// `objectives` are the secondary objectives of "nsga2" and ignored by the other operators.
+pub fn get_selection_operator<T: Clone>(name: &str, objectives: Vec<Objective>) -> Result<Box<dyn GeneralSelectionTrait<T>>, String> {
+    match name {
+        "one_plus_four" => Ok(EAElitistSelectionOnePlusFour::new()),
+        "mu_plus_lambda" => Ok(ElitistSelectionMuPlusLambda::new()),
+        "tournament" => Ok(ElitistSelectionWithTournament::new()),
+        "nsga2" => Ok(NSGA2Selection::with_objectives(objectives)),
+        _ => Err(unknown_operator("selection", name, &SELECTION_OPERATORS)),
+    }
+}
+
+/// The reorder operator each `CGPType` is defined by.
+pub fn reorder_operator_for_cgp_type(cgp_type: &CGPType) -> &'static str {
+    match cgp_type {
+        CGPType::Standard => "none",
+        CGPType::DAG => "none",
+        CGPType::OriginalReorder => "standard",
+        CGPType::EReorder => "equidistant",
+        CGPType::NegBiasReorder => "negative_bias",
+        CGPType::UniformReorder => "uniform",
+        CGPType::LSDReorder => "left_skewed",
+    }
+}
+
+/// Checks that every operator name exists and that the operators can work together with the
+/// given parameters.
+pub fn check_operator_compatibility(operators: &OperatorConfig, params: &CgpParameters) -> Result<(), String> {
+    for (kind, name, allowed) in [
+        ("node mutation", &operators.node_mutation, &NODE_MUTATION_OPERATORS[..]),
+        ("active node", &operators.active_nodes, &ACTIVE_NODE_OPERATORS[..]),
+        ("chromosome mutation", &operators.mutation_type, &CHROMOSOME_MUTATION_OPERATORS[..]),
+        ("reorder", &operators.reorder, &REORDER_OPERATORS[..]),
+        ("crossover", &operators.crossover, &CROSSOVER_OPERATORS[..]),
+        ("evaluation", &operators.evaluation, &EVALUATION_OPERATORS[..]),
+        ("selection", &operators.selection, &SELECTION_OPERATORS[..]),
+    ] {
+        if !allowed.contains(&name.as_str()) {
+            return Err(unknown_operator(kind, name, allowed));
+        }
+    }
+
+    // DAG graphs can have connections in both directions; they need the cycle-aware mutation and
+    // a topological sort of the active nodes.
+    let graph_operator = if params.cgp_type == CGPType::DAG { "dag" } else { "standard" };
+    if operators.node_mutation != graph_operator {
+        return Err(format!("cgp_type {} needs node_mutation '{}', got '{}'",
+                           params.cgp_type, graph_operator, operators.node_mutation));
+    }
+    if operators.active_nodes != graph_operator {
+        return Err(format!("cgp_type {} needs active_nodes '{}', got '{}'",
+                           params.cgp_type, graph_operator, operators.active_nodes));
+    }
+
+    let reorder_operator = reorder_operator_for_cgp_type(&params.cgp_type);
+    if operators.reorder != reorder_operator {
+        return Err(format!("cgp_type {} needs reorder '{}', got '{}'",
+                           params.cgp_type, reorder_operator, operators.reorder));
+    }
+
+    if params.elitists == 0 {
+        return Err("elitists must be > 0".to_string());
+    }
+    if (operators.evaluation == "one_plus_four" || operators.selection == "one_plus_four") && params.elitists != 1 {
+        return Err(format!("the one_plus_four operators need exactly one elitist, got {}", params.elitists));
+    }
+
+    // NSGA-II selects the elitists of a (mu + lambda)-ES by more than the fitness
+    if operators.selection == "nsga2" {
+        if operators.evaluation != "mu_plus_lambda" || params.elitists < 2 {
+            return Err(format!("selection 'nsga2' needs evaluation 'mu_plus_lambda' and at least two elitists, got '{}' and {}",
+                               operators.evaluation, params.elitists));
+        }
+        let objectives = operators.objectives()?;
+        if objectives.is_empty() {
+            return Err("selection 'nsga2' needs at least one objective".to_string());
+        }
+        if (1..objectives.len()).any(|i| objectives[..i].contains(&objectives[i])) {
+            return Err(format!("objectives must not repeat, got {:?}", operators.objectives.as_ref().unwrap()));
+        }
+    } else if operators.objectives.is_some() {
+        return Err("objectives are only used by selection 'nsga2'".to_string());
+    }
+
+    // Tournament selection only produces the parents for the tournament crossover and vice versa
+    if (operators.selection == "tournament") != (operators.crossover == "tournament") {
+        return Err("selection 'tournament' and crossover 'tournament' must be used together".to_string());
+    }
+    if operators.selection == "tournament"
+        && (params.tournament_size == 0 || params.tournament_size > params.elitists + params.population_size) {
+        return Err(format!("tournament_size must be in [1, {}], got {}",
+                           params.elitists + params.population_size, params.tournament_size));
+    }
+
+    if operators.crossover == "none" {
+        if params.crossover_type != CrossoverType::NoCrossover {
+            return Err(format!("crossover_type {} needs a crossover operator", params.crossover_type));
+        }
+    } else {
+        // crossover swaps nodes without updating the edges of the cycle checker
+        if params.cgp_type == CGPType::DAG {
+            return Err(format!("cgp_type {} does not support crossover", params.cgp_type));
+        }
+        // children are created pairwise
+        if !params.population_size.is_multiple_of(2) {
+            return Err(format!("crossover needs an even population_size, got {}", params.population_size));
+        }
+        if operators.crossover == "mu_lambda_elitist" && params.elitists < 2 {
+            return Err(format!("crossover 'mu_lambda_elitist' needs at least two elitists, got {}", params.elitists));
+        }
+        if params.crossover_type == CrossoverType::MultiPointCrossover
+            && (params.multi_point_n == 0 || params.multi_point_n > params.graph_width) {
+            return Err(format!("multi point crossover needs multi_point_n in [1, {}], got {}",
+                               params.graph_width, params.multi_point_n));
+        }
+    }
+
+    return Ok(());
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use super::*;
+    use crate::experiment::batch::BatchConfig;
+    use crate::experiment::experiment_config::ExperimentConfig;
+
+    type Change = fn(&mut OperatorConfig, &mut CgpParameters);
+
+    fn dag(operators: &mut OperatorConfig, params: &mut CgpParameters) {
+        params.cgp_type = CGPType::DAG;
+        operators.node_mutation = "dag".to_string();
+        operators.active_nodes = "dag".to_string();
+    }
+
+    fn mu_plus_lambda(operators: &mut OperatorConfig, params: &mut CgpParameters) {
+        params.elitists = 2;
+        operators.evaluation = "mu_plus_lambda".to_string();
+        operators.selection = "mu_plus_lambda".to_string();
+    }
+
+    fn tournament(operators: &mut OperatorConfig, params: &mut CgpParameters) {
+        mu_plus_lambda(operators, params);
+        operators.selection = "tournament".to_string();
+        operators.crossover = "tournament".to_string();
+        params.tournament_size = 2;
+    }
+
+    #[test]
+    fn rejects_incompatible_operators() {
+        let cases: Vec<(Change, &str)> = vec![
+            (|o, _| o.node_mutation = "cyclic".to_string(), "unknown node mutation operator 'cyclic'"),
+            (|o, _| o.selection = "roulette".to_string(), "unknown selection operator 'roulette'"),
+            // DAG graphs need the DAG operators and standard graphs the standard ones
+            (|_, p| p.cgp_type = CGPType::DAG, "cgp_type DAG needs node_mutation 'dag', got 'standard'"),
+            (|o, p| { dag(o, p); o.active_nodes = "standard".to_string() }, "cgp_type DAG needs active_nodes 'dag', got 'standard'"),
+            (|o, _| o.node_mutation = "dag".to_string(), "cgp_type Standard CGP needs node_mutation 'standard', got 'dag'"),
+            // the reorder operator follows from the CGP type
+            (|_, p| p.cgp_type = CGPType::OriginalReorder, "cgp_type Original Reorder needs reorder 'standard', got 'none'"),
+            (|o, _| o.reorder = "equidistant".to_string(), "cgp_type Standard CGP needs reorder 'none', got 'equidistant'"),
+            (|o, p| { p.cgp_type = CGPType::EReorder; o.reorder = "standard".to_string() }, "cgp_type Equidistant Reorder needs reorder 'equidistant', got 'standard'"),
+            (|_, p| p.elitists = 0, "elitists must be > 0"),
+            (|_, p| p.elitists = 2, "the one_plus_four operators need exactly one elitist, got 2"),
+            (|o, p| { mu_plus_lambda(o, p); o.evaluation = "one_plus_four".to_string() }, "the one_plus_four operators need exactly one elitist, got 2"),
+            // NSGA-II needs a (mu + lambda)-ES with at least two elitists and distinct objectives
+            (|o, _| o.selection = "nsga2".to_string(), "selection 'nsga2' needs evaluation 'mu_plus_lambda' and at least two elitists, got 'one_plus_four' and 1"),
+            (|o, p| { mu_plus_lambda(o, p); o.selection = "nsga2".to_string(); p.elitists = 1 }, "selection 'nsga2' needs evaluation 'mu_plus_lambda' and at least two elitists, got 'mu_plus_lambda' and 1"),
+            (|o, p| { mu_plus_lambda(o, p); o.selection = "nsga2".to_string(); o.objectives = Some(vec![]) }, "selection 'nsga2' needs at least one objective"),
+            (|o, p| { mu_plus_lambda(o, p); o.selection = "nsga2".to_string(); o.objectives = Some(vec!["depth".to_string(), "depth".to_string()]) }, "objectives must not repeat"),
+            (|o, p| { mu_plus_lambda(o, p); o.selection = "nsga2".to_string(); o.objectives = Some(vec!["size".to_string()]) }, "unknown objective 'size'"),
+            (|o, p| { mu_plus_lambda(o, p); o.objectives = Some(vec!["depth".to_string()]) }, "objectives are only used by selection 'nsga2'"),
+            // tournament selection and crossover only work together
+            (|o, p| { tournament(o, p); o.crossover = "mu_lambda_elitist".to_string() }, "selection 'tournament' and crossover 'tournament' must be used together"),
+            (|o, p| { tournament(o, p); o.selection = "mu_plus_lambda".to_string() }, "selection 'tournament' and crossover 'tournament' must be used together"),
+            (|o, p| { tournament(o, p); p.tournament_size = 0 }, "tournament_size must be in [1, 6], got 0"),
+            (|o, p| { tournament(o, p); p.tournament_size = 7 }, "tournament_size must be in [1, 6], got 7"),
+            // crossover
+            (|_, p| p.crossover_type = CrossoverType::SinglePointCrossover, "crossover_type One Point Crossover needs a crossover operator"),
+            (|o, p| { dag(o, p); mu_plus_lambda(o, p); o.crossover = "mu_lambda_elitist".to_string() }, "cgp_type DAG does not support crossover"),
+            (|o, p| { mu_plus_lambda(o, p); o.crossover = "mu_lambda_elitist".to_string(); p.population_size = 5 }, "crossover needs an even population_size, got 5"),
+            (|o, _| o.crossover = "mu_lambda_elitist".to_string(), "crossover 'mu_lambda_elitist' needs at least two elitists, got 1"),
+            (|o, p| { mu_plus_lambda(o, p); o.crossover = "mu_lambda_elitist".to_string(); p.crossover_type = CrossoverType::MultiPointCrossover }, "multi point crossover needs multi_point_n in [1, 10], got 0"),
+            (|o, p| { mu_plus_lambda(o, p); o.crossover = "mu_lambda_elitist".to_string(); p.crossover_type = CrossoverType::MultiPointCrossover; p.multi_point_n = 11 }, "multi point crossover needs multi_point_n in [1, 10], got 11"),
+        ];
+
+        for (change, message) in cases {
+            let mut operators = OperatorConfig::one_plus_four("single");
+            let mut params = CgpParameters::for_tests(10, 2, 1, 4);
+            change(&mut operators, &mut params);
+
+            let error = check_operator_compatibility(&operators, &params).unwrap_err();
+            assert!(error.contains(message), "expected '{}', got '{}'", message, error);
+        }
+    }
+
+    #[test]
+    fn accepts_compatible_operators() {
+        let cases: Vec<Change> = vec![
+            |_, _| {},
+            dag,
+            |o, p| { dag(o, p); mu_plus_lambda(o, p) },
+            |o, p| { p.cgp_type = CGPType::LSDReorder; o.reorder = "left_skewed".to_string() },
+            |o, p| { mu_plus_lambda(o, p); o.selection = "nsga2".to_string() },
+            tournament,
+            |o, p| { mu_plus_lambda(o, p); o.crossover = "mu_lambda_elitist".to_string(); p.crossover_type = CrossoverType::MultiPointCrossover; p.multi_point_n = 10 },
+        ];
+
+        for change in cases {
+            let mut operators = OperatorConfig::one_plus_four("single");
+            let mut params = CgpParameters::for_tests(10, 2, 1, 4);
+            change(&mut operators, &mut params);
+
+            check_operator_compatibility(&operators, &params).unwrap();
+        }
+    }
+
+    #[test]
+    fn shipped_experiments_are_compatible() {
+        for entry in fs::read_dir(Path::new("experiments")).unwrap() {
+            let path = entry.unwrap().path();
+            let content = fs::read_to_string(&path).unwrap();
+
+            // batch files name their experiment; every job of the grid is validated
+            if content.lines().any(|line| line.starts_with("experiment = ")) {
+                BatchConfig::load(&path).and_then(|batch| batch.jobs())
+                    .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            } else {
+                let config = ExperimentConfig::load(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+                check_operator_compatibility(&config.operators, &config.cgp).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// x^4 + x^3 + x^2 + x on integers
+fn make_label(inputs: &Vec<Vec<i64>>) -> Vec<Vec<i64>> {
+    let mut labels: Vec<i64> = vec![];
+    for d in inputs {
+        labels.push(d[0].pow(4) + d[0].pow(3) + d[0].pow(2) + d[0]);
+    }
+
+    return vec![labels];
+}
+
+
+pub fn get_dataset() -> (Vec<Vec<i64>>, Vec<Vec<i64>>) {
+    let mut data = vec![];
+
+    for x in -10..=10 {
+        let elem: Vec<i64> = vec![x];
+
+        data.push(elem);
+    }
+
+    let labels = make_label(&data);
+
+    return (data, labels);
+}
+
+pub fn get_eval_dataset() -> (Vec<Vec<i64>>, Vec<Vec<i64>>) {
+    let mut data = vec![];
+
+    for x in -50..=50 {
+        let elem: Vec<i64> = vec![x];
+
+        data.push(elem);
+    }
+
+    let labels = make_label(&data);
+
+    return (data, labels);
+}
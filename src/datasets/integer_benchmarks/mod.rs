@@ -0,0 +1,2 @@
+pub mod koza_1;
+pub mod sum_of_squares;
@@ -0,0 +1,38 @@
+// x^2 + y^2 on integers
+fn make_label(inputs: &Vec<Vec<i64>>) -> Vec<Vec<i64>> {
+    let mut labels: Vec<i64> = vec![];
+    for d in inputs {
+        labels.push(d[0] * d[0] + d[1] * d[1]);
+    }
+
+    return vec![labels];
+}
+
+
+fn grid(range: std::ops::RangeInclusive<i64>) -> Vec<Vec<i64>> {
+    let mut data = vec![];
+
+    for x in range.clone() {
+        for y in range.clone() {
+            let elem: Vec<i64> = vec![x, y];
+
+            data.push(elem);
+        }
+    }
+
+    return data;
+}
+
+pub fn get_dataset() -> (Vec<Vec<i64>>, Vec<Vec<i64>>) {
+    let data = grid(-5..=5);
+    let labels = make_label(&data);
+
+    return (data, labels);
+}
+
+pub fn get_eval_dataset() -> (Vec<Vec<i64>>, Vec<Vec<i64>>) {
+    let data = grid(-20..=20);
+    let labels = make_label(&data);
+
+    return (data, labels);
+}
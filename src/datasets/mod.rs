@@ -1,2 +1,3 @@
 pub mod boolean_datasets;
-pub mod regression_benchmarks;
\ No newline at end of file
+pub mod regression_benchmarks;
+pub mod integer_benchmarks;
\ No newline at end of file
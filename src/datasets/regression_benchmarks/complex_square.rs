@@ -0,0 +1,46 @@
+use num::Float;
+
+// two coupled equations, the square of the complex number x0 + i * x1:
+// y0 = x0^2 - x1^2, y1 = 2 * x0 * x1
+// one label vector per output node
+fn make_label<T: Float>(inputs: &Vec<Vec<T>>) -> Vec<Vec<T>> {
+    let mut real: Vec<T> = vec![];
+    let mut imaginary: Vec<T> = vec![];
+    for d in inputs {
+        real.push(d[0] * d[0] - d[1] * d[1]);
+        imaginary.push((d[0] + d[0]) * d[1]);
+    }
+
+    return vec![real, imaginary];
+}
+
+
+// grid on [-1, 1]^2 with `steps` points per unit
+fn grid<T: Float>(steps: i32) -> Vec<Vec<T>> {
+    let mut data = vec![];
+    let steps_per_unit = T::from(steps).unwrap();
+
+    for i in -steps..=steps {
+        for j in -steps..=steps {
+            let elem: Vec<T> = vec![T::from(i).unwrap() / steps_per_unit, T::from(j).unwrap() / steps_per_unit];
+
+            data.push(elem);
+        }
+    }
+
+    return data;
+}
+
+pub fn get_dataset<T: Float>() -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+    let data = grid(5);
+    let labels = make_label(&data);
+
+    return (data, labels);
+}
+
+pub fn get_eval_dataset<T: Float>() -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+    let data = grid(10);
+    let labels = make_label(&data);
+
+    return (data, labels);
+}
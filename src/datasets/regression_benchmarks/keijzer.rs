@@ -1,21 +1,21 @@
+use num::Float;
 use crate::utils::utility_funcs::get_float_iterator;
 
-fn make_label(inputs: &Vec<Vec<f32>>) -> Vec<Vec<f32>> {
-    let mut labels: Vec<f32> = vec![];
+fn make_label<T: Float>(inputs: &Vec<Vec<T>>) -> Vec<Vec<T>> {
+    let mut labels: Vec<T> = vec![];
     for d in inputs {
-        labels.push(d.iter().map(|x| 1. / x).sum::<f32>());
+        labels.push(d.iter().fold(T::zero(), |sum, x| sum + T::one() / *x));
     }
 
     return vec![labels];
 }
 
 
-pub fn get_dataset() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
+pub fn get_dataset<T: Float>() -> (Vec<Vec<T>>, Vec<Vec<T>>) {
     let mut data = vec![];
 
-    for x in get_float_iterator(1., 50., 1.) {
-        let mut elem: Vec<f32> = vec![];
-        elem.push(x);
+    for x in get_float_iterator(T::one(), T::from(50.).unwrap(), T::one()) {
+        let elem: Vec<T> = vec![x];
 
         data.push(elem);
     }
@@ -25,12 +25,11 @@ pub fn get_dataset() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
     return (data, labels);
 }
 
-pub fn get_eval_dataset() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
+pub fn get_eval_dataset<T: Float>() -> (Vec<Vec<T>>, Vec<Vec<T>>) {
     let mut data = vec![];
 
-    for x in get_float_iterator(1., 120., 1.) {
-        let mut elem: Vec<f32> = vec![];
-        elem.push(x);
+    for x in get_float_iterator(T::one(), T::from(120.).unwrap(), T::one()) {
+        let elem: Vec<T> = vec![x];
 
         data.push(elem);
     }
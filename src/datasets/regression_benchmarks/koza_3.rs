@@ -1,26 +1,28 @@
+use num::Float;
 use rand::distributions::{Distribution, Uniform};
+use rand::distributions::uniform::SampleUniform;
+use rand_chacha::ChaCha8Rng;
 
 
 
-fn make_label(inputs: &Vec<Vec<f32>>) -> Vec<Vec<f32>>{
-    let mut labels: Vec<f32> = vec![];
+fn make_label<T: Float>(inputs: &Vec<Vec<T>>) -> Vec<Vec<T>>{
+    let mut labels: Vec<T> = vec![];
+    let (two, four, six) = (T::from(2.0).unwrap(), T::from(4.0).unwrap(), T::from(6.0).unwrap());
     for d in inputs {
-        labels.push(d[0].powf(6.0)  - 2.0 * d[0].powf(4.0) + d[0].powf(2.0));
+        labels.push(d[0].powf(six) - two * d[0].powf(four) + d[0].powf(two));
     }
 
     return vec![labels];
 }
 
 
-pub fn get_dataset() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
+pub fn get_dataset<T: Float + SampleUniform>(rng: &mut ChaCha8Rng) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
     let mut data = vec![];
 
-    let between = Uniform::new(-1.0, 1.0);
-    let mut rng = rand::thread_rng();
+    let between = Uniform::new(T::from(-1.0).unwrap(), T::from(1.0).unwrap());
 
     for _ in 0..20 {
-        let mut elem: Vec<f32> = vec![];
-        elem.push(between.sample(&mut rng));
+        let elem: Vec<T> = vec![between.sample(rng)];
 
         data.push(elem);
     }
@@ -31,6 +33,6 @@ pub fn get_dataset() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
     return (data, labels);
 }
 
-pub fn get_eval_dataset() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
-    return get_dataset();
+pub fn get_eval_dataset<T: Float + SampleUniform>(rng: &mut ChaCha8Rng) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+    return get_dataset(rng);
 }
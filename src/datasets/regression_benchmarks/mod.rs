@@ -1,4 +1,5 @@
 pub mod keijzer;
 pub mod koza_3;
 pub mod nguyen_7;
-pub mod pagie_1;
\ No newline at end of file
+pub mod pagie_1;
+pub mod complex_square;
\ No newline at end of file
@@ -1,24 +1,25 @@
+use num::Float;
 use crate::utils::utility_funcs::get_float_iterator;
 
 
-fn make_label(inputs: &Vec<Vec<f32>>) -> Vec<Vec<f32>> {
-    let mut labels: Vec<f32> = vec![];
+fn make_label<T: Float>(inputs: &Vec<Vec<T>>) -> Vec<Vec<T>> {
+    let mut labels: Vec<T> = vec![];
+    let minus_four = T::from(-4.).unwrap();
     for d in inputs {
-        labels.push(1. / (1. + d[0].powf(-4.)) + 1. / (1. + d[1].powf(-4.)));
+        labels.push(T::one() / (T::one() + d[0].powf(minus_four)) + T::one() / (T::one() + d[1].powf(minus_four)));
     }
 
     return vec![labels];
 }
 
 
-pub fn get_dataset() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
+pub fn get_dataset<T: Float>() -> (Vec<Vec<T>>, Vec<Vec<T>>) {
     let mut data = vec![];
+    let (start, end, step) = (T::from(-5.).unwrap(), T::from(5.).unwrap(), T::from(0.4).unwrap());
 
-    for x in get_float_iterator(-5., 5., 0.4) {
-        for y in get_float_iterator(-5., 5., 0.4) {
-            let mut elem: Vec<f32> = vec![];
-            elem.push(x);
-            elem.push(y);
+    for x in get_float_iterator(start, end, step) {
+        for y in get_float_iterator(start, end, step) {
+            let elem: Vec<T> = vec![x, y];
 
             data.push(elem);
         }
@@ -31,7 +32,7 @@ pub fn get_dataset() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
     return (data, labels);
 }
 
-pub fn get_eval_dataset() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
+pub fn get_eval_dataset<T: Float>() -> (Vec<Vec<T>>, Vec<Vec<T>>) {
     let (data, labels) = get_dataset();
 
     return (data, labels);
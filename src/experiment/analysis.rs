@@ -0,0 +1,389 @@
+//! Statistical comparison of finished runs, i.e. of the mutation operators of the paper.
+//! Every directory with `run_{id}_iteration.txt` files is one configuration. Per configuration,
+//! the success rate and the median and quartiles of the iterations (or fitness evaluations, see
+//! `Measure`) to success are reported.
+//! Configurations of the same dataset are compared pairwise with two-sided Mann-Whitney U tests
+//! (normal approximation with tie correction), the Vargha-Delaney effect size A12 and Holm's
+//! correction over all pairs of the dataset.
+//!
+//! For the tests, failed runs count as worse than every successful run (and as equal to each
+//! other), so a configuration that fails more often loses against one that succeeds slowly.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use statrs::distribution::{ContinuousCDF, Normal};
+use statrs::statistics::{Data, OrderStatistics, RankTieBreaker};
+
+/// What the runs are compared by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Measure {
+    Iterations,
+    // forward passes; children that were not evaluated because their active genotype did not
+    // change are not counted
+    FitnessEvaluations,
+}
+
+impl FromStr for Measure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "iterations" => Ok(Measure::Iterations),
+            "evaluations" => Ok(Measure::FitnessEvaluations),
+            _ => Err(format!("unknown measure '{}', expected 'iterations' or 'evaluations'", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Measure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Measure::Iterations => write!(f, "iterations"),
+            Measure::FitnessEvaluations => write!(f, "fitness evaluations"),
+        }
+    }
+}
+
+/// Outcome of a single run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunOutcome {
+    pub run_id: usize,
+    pub iterations: usize,
+    // missing in the iteration files of older runs
+    pub fitness_evaluations: Option<usize>,
+    pub success: bool,
+}
+
+impl RunOutcome {
+    fn value(&self, measure: Measure) -> f64 {
+        match measure {
+            Measure::Iterations => self.iterations as f64,
+            Measure::FitnessEvaluations => self.fitness_evaluations.expect("checked by collect_configurations") as f64,
+        }
+    }
+
+    /// Iterations or evaluations to success; failed runs are worse than every successful run.
+    fn cost(&self, measure: Measure) -> f64 {
+        // not infinity; statrs does not detect ties of infinite values
+        if self.success { self.value(measure) } else { f64::MAX }
+    }
+}
+
+/// Runs of one output directory.
+pub struct Configuration {
+    pub name: String,
+    // runs of configurations in the same group are compared; i.e. the dataset
+    pub group: String,
+    pub runs: Vec<RunOutcome>,
+}
+
+pub struct MannWhitney {
+    pub u: f64,
+    pub p_value: f64,
+    // P(a run of A needs fewer iterations than a run of B) + 1/2 P(tie)
+    pub a12: f64,
+}
+
+/// Parses an iteration file. Returns `None` for a run that has not finished yet.
+/// A run succeeded if it was stopped by its target fitness. Older files do not name the stopping
+/// criterion; their runs succeeded if they ended before `max_iterations`.
+pub fn parse_iteration_file(run_id: usize, content: &str, max_iterations: usize) -> Result<Option<RunOutcome>, String> {
+    let mut iterations: Option<usize> = None;
+    let mut fitness_evaluations: Option<usize> = None;
+    let mut stop_reason: Option<&str> = None;
+
+    for line in content.lines() {
+        if let Some(value) = line.strip_prefix("End at iteration: ") {
+            iterations = Some(value.trim().parse::<usize>()
+                .map_err(|e| format!("invalid line '{}': {}", line, e))?);
+        } else if let Some(value) = line.strip_prefix("Fitness evaluations: ") {
+            fitness_evaluations = Some(value.trim().parse::<usize>()
+                .map_err(|e| format!("invalid line '{}': {}", line, e))?);
+        } else if let Some(value) = line.strip_prefix("Stopped by: ") {
+            stop_reason = Some(value.trim());
+        }
+    }
+
+    let Some(iterations) = iterations else {
+        return Ok(None);
+    };
+    let success = match stop_reason {
+        Some(reason) => reason.starts_with("target fitness"),
+        None => iterations < max_iterations,
+    };
+    return Ok(Some(RunOutcome { run_id, iterations, fitness_evaluations, success }));
+}
+
+/// Collects the configurations below `roots`, sorted by name.
+/// Returns the configurations and the number of unfinished runs, which are skipped.
+/// Fails if a run has no value for `measure`.
+pub fn collect_configurations(roots: &[PathBuf], max_iterations: usize, measure: Measure) -> Result<(Vec<Configuration>, usize), String> {
+    let mut configurations: BTreeMap<String, Configuration> = BTreeMap::new();
+    let mut unfinished = 0;
+
+    for root in roots {
+        let mut files: Vec<PathBuf> = vec![];
+        find_iteration_files(root, &mut files)?;
+
+        for file in files {
+            let run_id = iteration_file_run_id(&file).unwrap();
+            let content = fs::read_to_string(&file)
+                .map_err(|e| format!("cannot read {}: {}", file.display(), e))?;
+            let Some(outcome) = parse_iteration_file(run_id, &content, max_iterations)
+                .map_err(|e| format!("{}: {}", file.display(), e))? else {
+                unfinished += 1;
+                continue;
+            };
+            if measure == Measure::FitnessEvaluations && outcome.fitness_evaluations.is_none() {
+                return Err(format!("{}: the number of fitness evaluations is missing (written since skipped evaluations are counted); compare by iterations instead", file.display()));
+            }
+
+            let directory = file.parent().unwrap();
+            let mut name = directory.strip_prefix(root).unwrap().to_path_buf();
+            if roots.len() > 1 {
+                name = root.join(name);
+            }
+            let name = name.display().to_string();
+            configurations.entry(name.clone())
+                .or_insert_with(|| Configuration { group: group_of(&name), name, runs: vec![] })
+                .runs
+                .push(outcome);
+        }
+    }
+
+    let mut configurations: Vec<Configuration> = configurations.into_values().collect();
+    for configuration in &mut configurations {
+        configuration.runs.sort_by_key(|run| run.run_id);
+    }
+    return Ok((configurations, unfinished));
+}
+
+fn find_iteration_files(directory: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(directory)
+        .map_err(|e| format!("cannot read {}: {}", directory.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            find_iteration_files(&path, files)?;
+        } else if iteration_file_run_id(&path).is_some() {
+            files.push(path);
+        }
+    }
+    return Ok(());
+}
+
+fn iteration_file_run_id(path: &Path) -> Option<usize> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("run_")?
+        .strip_suffix("_iteration.txt")?
+        .parse()
+        .ok()
+}
+
+/// Everything up to the `dataset_{i}` directory; configurations of different datasets are not
+/// compared.
+fn group_of(name: &str) -> String {
+    let mut group = PathBuf::new();
+    for component in Path::new(name).components() {
+        group.push(component);
+        if component.as_os_str().to_string_lossy().starts_with("dataset_") {
+            return group.display().to_string();
+        }
+    }
+    return String::new();
+}
+
+/// Median, lower and upper quartile of `measure` of the successful runs.
+pub fn success_quartiles(runs: &[RunOutcome], measure: Measure) -> Option<(f64, f64, f64)> {
+    let values: Vec<f64> = runs.iter()
+        .filter(|run| run.success)
+        .map(|run| run.value(measure))
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    let mut data = Data::new(values);
+    return Some((data.median(), data.lower_quartile(), data.upper_quartile()));
+}
+
+/// Two-sided Mann-Whitney U test of the costs of `a` and `b`; `u` is the statistic of `a`.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> MannWhitney {
+    let n1 = a.len() as f64;
+    let n2 = b.len() as f64;
+    let n = n1 + n2;
+
+    let mut values: Vec<f64> = a.iter().chain(b.iter()).cloned().collect();
+    let ranks = Data::new(values.clone()).ranks(RankTieBreaker::Average);
+    let rank_sum_a: f64 = ranks[..a.len()].iter().sum();
+    let u = rank_sum_a - n1 * (n1 + 1.) / 2.;
+
+    // tie correction of the variance
+    values.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    let mut ties = 0.;
+    for group in values.chunk_by(|x, y| x == y) {
+        let t = group.len() as f64;
+        ties += t * t * t - t;
+    }
+    let variance = n1 * n2 / 12. * ((n + 1.) - ties / (n * (n - 1.)));
+
+    let mean = n1 * n2 / 2.;
+    let p_value = if variance > 0. {
+        // continuity correction
+        let z = ((u - mean).abs() - 0.5).max(0.) / variance.sqrt();
+        let normal = Normal::new(0., 1.).unwrap();
+        (2. * (1. - normal.cdf(z))).min(1.)
+    } else {
+        1.
+    };
+
+    return MannWhitney {
+        u,
+        p_value,
+        // U counts the pairs in which `a` has the higher cost
+        a12: 1. - u / (n1 * n2),
+    };
+}
+
+/// Holm-adjusted p-values, in the order of `p_values`.
+pub fn holm_correction(p_values: &[f64]) -> Vec<f64> {
+    let m = p_values.len();
+    let mut order: Vec<usize> = (0..m).collect();
+    order.sort_by(|x, y| p_values[*x].partial_cmp(&p_values[*y]).unwrap());
+
+    let mut adjusted = vec![0.; m];
+    let mut running_max: f64 = 0.;
+    for (i, id) in order.into_iter().enumerate() {
+        running_max = running_max.max(((m - i) as f64 * p_values[id]).min(1.));
+        adjusted[id] = running_max;
+    }
+    return adjusted;
+}
+
+/// Magnitude of A12 after Vargha and Delaney.
+pub fn a12_magnitude(a12: f64) -> &'static str {
+    let distance = (a12 - 0.5).abs();
+    if distance < 0.06 {
+        "negligible"
+    } else if distance < 0.14 {
+        "small"
+    } else if distance < 0.21 {
+        "medium"
+    } else {
+        "large"
+    }
+}
+
+/// Markdown report: per group, a table of the configurations and a table of the pairwise tests.
+pub fn report(configurations: &[Configuration], alpha: f64, measure: Measure) -> String {
+    let mut groups: BTreeMap<&str, Vec<&Configuration>> = BTreeMap::new();
+    for configuration in configurations {
+        groups.entry(&configuration.group).or_default().push(configuration);
+    }
+
+    let mut out = String::new();
+    for (group, configurations) in groups {
+        let short_name = |configuration: &Configuration| -> String {
+            let name = Path::new(&configuration.name);
+            name.strip_prefix(group).unwrap_or(name).display().to_string()
+        };
+        if !group.is_empty() {
+            writeln!(out, "## {}\n", group).unwrap();
+        }
+
+        writeln!(out, "| Configuration | Runs | Successes | Success rate | Median {} | Q1 | Q3 |", measure).unwrap();
+        writeln!(out, "|---|---:|---:|---:|---:|---:|---:|").unwrap();
+        for configuration in &configurations {
+            let runs = &configuration.runs;
+            let successes = runs.iter().filter(|run| run.success).count();
+            let quartiles = match success_quartiles(runs, measure) {
+                Some((median, q1, q3)) => format!("{:.1} | {:.1} | {:.1}", median, q1, q3),
+                None => "- | - | -".to_string(),
+            };
+            writeln!(out, "| {} | {} | {} | {:.1}% | {} |", short_name(configuration), runs.len(), successes,
+                     100. * successes as f64 / runs.len() as f64, quartiles).unwrap();
+        }
+        writeln!(out).unwrap();
+
+        let mut pairs: Vec<(&Configuration, &Configuration, MannWhitney)> = vec![];
+        for (i, a) in configurations.iter().enumerate() {
+            for b in &configurations[i + 1..] {
+                let costs = |c: &Configuration| c.runs.iter().map(|run| run.cost(measure)).collect::<Vec<f64>>();
+                pairs.push((a, b, mann_whitney_u(&costs(a), &costs(b))));
+            }
+        }
+        if pairs.is_empty() {
+            continue;
+        }
+        let adjusted = holm_correction(&pairs.iter().map(|(_, _, test)| test.p_value).collect::<Vec<f64>>());
+
+        writeln!(out, "| A | B | U | p | p (Holm) | A12 | Effect | p (Holm) < {} |", alpha).unwrap();
+        writeln!(out, "|---|---|---:|---:|---:|---:|---|---|").unwrap();
+        for ((a, b, test), p_holm) in pairs.iter().zip(adjusted) {
+            writeln!(out, "| {} | {} | {:.1} | {:.4} | {:.4} | {:.3} | {} | {} |",
+                     short_name(a), short_name(b), test.u, test.p_value, p_holm, test.a12,
+                     a12_magnitude(test.a12), if p_holm < alpha { "yes" } else { "no" }).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    return out;
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_iteration_files() {
+        let finished = "Iteration: 0, Fitness: 0.5\nStopped by: target fitness (0.0001)\nFitness evaluations: 3001\nSkipped evaluations: 1936\nEnd at iteration: 1234";
+        let stopped = "Iteration: 0, Fitness: 0.5\nStopped by: max generations (10)\nEnd at iteration: 10";
+        let legacy = "Iteration: 0, Fitness: 0.5\nEnd at iteration: 99\n";
+
+        assert_eq!(Some(RunOutcome { run_id: 1, iterations: 1234, fitness_evaluations: Some(3001), success: true }),
+                   parse_iteration_file(1, finished, 500_000).unwrap());
+        assert_eq!(Some(RunOutcome { run_id: 1, iterations: 10, fitness_evaluations: None, success: false }),
+                   parse_iteration_file(1, stopped, 500_000).unwrap());
+        assert!(parse_iteration_file(1, legacy, 500_000).unwrap().unwrap().success);
+        assert!(!parse_iteration_file(1, legacy, 99).unwrap().unwrap().success);
+        assert_eq!(None, parse_iteration_file(1, "Iteration: 0, Fitness: 0.5\n", 500_000).unwrap());
+    }
+
+    #[test]
+    fn groups_by_dataset() {
+        assert_eq!("dataset_2", group_of("dataset_2/single/number_nodes_500"));
+        assert_eq!("out/dataset_0", group_of("out/dataset_0/point/x"));
+        assert_eq!("", group_of("single/number_nodes_500"));
+    }
+
+    #[test]
+    fn mann_whitney_u_matches_reference() {
+        // scipy.stats.mannwhitneyu(a, b, method="asymptotic") -> U = 1.5, p = 0.0278
+        let a = [1., 2., 3., 4., 5.];
+        let b = [4., 6., 7., 8., 9.];
+
+        let test = mann_whitney_u(&a, &b);
+
+        assert_eq!(1.5, test.u);
+        assert!((test.p_value - 0.0278).abs() < 0.0001, "{}", test.p_value);
+        assert_eq!(0.94, test.a12);
+    }
+
+    #[test]
+    fn mann_whitney_u_of_identical_samples() {
+        let test = mann_whitney_u(&[f64::MAX; 4], &[f64::MAX; 3]);
+
+        assert_eq!(1., test.p_value);
+        assert_eq!(0.5, test.a12);
+    }
+
+    #[test]
+    fn holm_correction_is_monotone() {
+        let adjusted = holm_correction(&[0.01, 0.04, 0.03, 0.5]);
+
+        assert_eq!(vec![0.04, 0.09, 0.09, 0.5], adjusted.iter().map(|p| (p * 1e6).round() / 1e6).collect::<Vec<f64>>());
+    }
+}
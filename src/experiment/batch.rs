@@ -0,0 +1,492 @@
+//! Sweeps over a parameter grid, i.e. the experiments of the paper.
+//! A batch file names an experiment file, the grid and the number of repetitions:
+//!
+//! ```toml
+//! experiment = "multiply_single.toml"  # relative to the batch file
+//! repetitions = 10
+//! workers = 4                          # optional; default: number of CPUs
+//! results = "batch_results.csv"        # optional
+//!
+//! [grid]
+//! dataset = [0, 1, 2, 3]
+//! "operators.mutation_type" = ["single", "multi"]
+//! "cgp.multi_n_number_mutations" = [3, 5]
+//! ```
+//!
+//! Grid keys are dotted paths into the experiment file. Every combination of the grid is run
+//! `repetitions` times with the run ids `run_id`, `run_id + 1`, ... of the experiment. If the
+//! experiment has a seed, repetition `r` uses `seed + r`; otherwise every run draws its own seed.
+//!
+//! Every run is a process of its own (`--config`), so a crashing run does not end the sweep.
+//! A finished run writes a `RunSummary`; runs with a summary are skipped and runs with a
+//! checkpoint are resumed, so an interrupted sweep continues when it is started again.
+//! In the end, the summaries of all runs are collected in one CSV file.
+
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use crate::components::cgp_components::fitness_cache::CacheStatistics;
+use crate::experiment::experiment_config::ExperimentConfig;
+use crate::utils::checkpoint;
+use crate::utils::runner::RunResult;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchConfig {
+    pub experiment: PathBuf,
+    pub repetitions: usize,
+    // 0: one worker per CPU
+    #[serde(default)]
+    pub workers: usize,
+    #[serde(default = "default_results")]
+    pub results: PathBuf,
+    #[serde(default)]
+    pub grid: Table,
+}
+
+fn default_results() -> PathBuf {
+    PathBuf::from("batch_results.csv")
+}
+
+/// One run of a batch. `grid_values` are the values of the grid keys, in the order of the keys.
+pub struct BatchJob {
+    pub config: ExperimentConfig,
+    pub grid_values: Vec<Value>,
+}
+
+/// Written at the end of every finished run; marks the run as done for `batch`.
+#[derive(Serialize, Deserialize)]
+pub struct RunSummary {
+    pub run_id: usize,
+    pub seed: u64,
+    pub generations: usize,
+    pub fitness_evaluations: usize,
+    // summaries of older runs have no skipped evaluations
+    #[serde(default)]
+    pub skipped_evaluations: usize,
+    pub best_fitness: f32,
+    // best fitness on the eval dataset; regression only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eval_fitness: Option<f32>,
+    // error of every output node of the best chromosome on the training and the eval dataset;
+    // regression only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_fitness_per_output: Option<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eval_fitness_per_output: Option<Vec<f32>>,
+    // whether every output of the best chromosome is defined and bounded on the input domain;
+    // runs with an interval analysis only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_safe: Option<bool>,
+    pub stop_reason: String,
+    pub elapsed_seconds: f64,
+    // runs without a fitness cache have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fitness_cache: Option<CacheStatistics>,
+}
+
+impl RunSummary {
+    pub fn new(config: &ExperimentConfig, result: &RunResult, eval_fitness: Option<f32>) -> Self {
+        Self {
+            run_id: config.run_id,
+            seed: config.seed.expect("seed is resolved before a run"),
+            generations: result.generations,
+            fitness_evaluations: result.fitness_evaluations,
+            skipped_evaluations: result.skipped_evaluations,
+            best_fitness: result.best_fitness,
+            eval_fitness,
+            best_fitness_per_output: None,
+            eval_fitness_per_output: None,
+            interval_safe: None,
+            stop_reason: result.stop_reason.as_ref().map_or("none".to_string(), |reason| reason.to_string()),
+            elapsed_seconds: result.elapsed.as_secs_f64(),
+            fitness_cache: None,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = toml::to_string(self).expect("run summary is always serializable");
+        fs::write(path, content)
+            .map_err(|e| format!("cannot write run summary {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read run summary {}: {}", path.display(), e))?;
+        toml::from_str(&content)
+            .map_err(|e| format!("cannot parse run summary {}: {}", path.display(), e))
+    }
+}
+
+enum JobStatus {
+    Finished,
+    Interrupted,
+    Failed(String),
+}
+
+impl BatchConfig {
+    /// Reads a batch file; `experiment` is resolved relative to the batch file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read batch file {}: {}", path.display(), e))?;
+        let mut config: BatchConfig = toml::from_str(&content)
+            .map_err(|e| format!("cannot parse batch file {}: {}", path.display(), e))?;
+
+        if let Some(dir) = path.parent() {
+            config.experiment = dir.join(&config.experiment);
+        }
+        return Ok(config);
+    }
+
+    /// Expands the grid; every job is validated.
+    pub fn jobs(&self) -> Result<Vec<BatchJob>, String> {
+        let content = fs::read_to_string(&self.experiment)
+            .map_err(|e| format!("cannot read experiment file {}: {}", self.experiment.display(), e))?;
+        let experiment: Table = toml::from_str(&content)
+            .map_err(|e| format!("cannot parse experiment file {}: {}", self.experiment.display(), e))?;
+
+        return expand_grid(&experiment, &self.grid, self.repetitions);
+    }
+
+    /// Runs all jobs that are not finished yet with `executable --config` (or `--resume`) on
+    /// `workers` processes and writes the results table.
+    /// Returns an error if a job failed; the results table contains all finished jobs anyway.
+    pub fn run(&self, executable: &Path) -> Result<(), String> {
+        let jobs = self.jobs()?;
+        let pending: Vec<&BatchJob> = jobs.iter()
+            .filter(|job| !job.config.summary_path().exists())
+            .collect();
+        let workers = match self.workers {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        println!("Batch: {} runs, {} finished, {} to run on {} workers",
+                 jobs.len(), jobs.len() - pending.len(), pending.len(), workers);
+
+        let next_job = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let statuses: Mutex<Vec<(usize, JobStatus)>> = Mutex::new(vec![]);
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    // a SIGINT reaches all runs; they write a checkpoint and stop
+                    if checkpoint::is_interrupted() {
+                        return;
+                    }
+                    let id = next_job.fetch_add(1, Ordering::SeqCst);
+                    let Some(job) = pending.get(id) else {
+                        return;
+                    };
+
+                    let status = run_job(job, executable);
+                    let count = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    println!("[{}/{}] {} run {}: {}", count, pending.len(),
+                             job.config.output_path().display(), job.config.run_id, status);
+                    statuses.lock().unwrap().push((id, status));
+                });
+            }
+        });
+
+        self.write_results(&jobs)?;
+
+        let statuses = statuses.into_inner().unwrap();
+        let interrupted = pending.len() - statuses.iter()
+            .filter(|(_, status)| !matches!(status, JobStatus::Interrupted))
+            .count();
+        let failed = statuses.iter()
+            .filter(|(_, status)| matches!(status, JobStatus::Failed(_)))
+            .count();
+        if interrupted > 0 {
+            println!("{} runs were interrupted or not started; start the batch again to continue them", interrupted);
+        }
+        if failed > 0 {
+            return Err(format!("{} of {} runs failed; see run_{{id}}_log.txt next to their results", failed, pending.len()));
+        }
+        return Ok(());
+    }
+
+    /// One row per finished job: the grid values, then the `RunSummary`.
+    fn write_results(&self, jobs: &[BatchJob]) -> Result<(), String> {
+        let mut header: Vec<String> = self.grid.keys().cloned().collect();
+        header.extend(["run_id", "seed", "generations", "fitness_evaluations", "skipped_evaluations",
+                       "best_fitness", "eval_fitness", "stop_reason", "elapsed_seconds", "cache_hit_rate",
+                       "output_path"].map(String::from));
+
+        let mut rows: Vec<Vec<String>> = vec![header];
+        for job in jobs {
+            let path = job.config.summary_path();
+            if !path.exists() {
+                continue;
+            }
+            let summary = RunSummary::load(&path)?;
+
+            let mut row: Vec<String> = job.grid_values.iter().map(value_to_string).collect();
+            row.extend([
+                summary.run_id.to_string(),
+                summary.seed.to_string(),
+                summary.generations.to_string(),
+                summary.fitness_evaluations.to_string(),
+                summary.skipped_evaluations.to_string(),
+                summary.best_fitness.to_string(),
+                summary.eval_fitness.map_or(String::new(), |fitness| fitness.to_string()),
+                summary.stop_reason,
+                summary.elapsed_seconds.to_string(),
+                summary.fitness_cache.map_or(String::new(), |cache| cache.hit_rate().to_string()),
+                job.config.output_path().display().to_string(),
+            ]);
+            rows.push(row);
+        }
+
+        let content: String = rows.iter()
+            .map(|row| row.iter().map(|field| csv_field(field)).collect::<Vec<String>>().join(",") + "\n")
+            .collect();
+        fs::write(&self.results, content)
+            .map_err(|e| format!("cannot write results {}: {}", self.results.display(), e))?;
+        println!("Results of {} runs written to {}", rows.len() - 1, self.results.display());
+        return Ok(());
+    }
+}
+
+impl std::fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobStatus::Finished => write!(f, "finished"),
+            JobStatus::Interrupted => write!(f, "interrupted"),
+            JobStatus::Failed(error) => write!(f, "failed ({})", error),
+        }
+    }
+}
+
+/// Starts the job, or resumes it if it has a checkpoint, and waits for it. The output of the
+/// process is written to `run_{id}_log.txt`.
+fn run_job(job: &BatchJob, executable: &Path) -> JobStatus {
+    let config = &job.config;
+    let output_path = config.output_path();
+    let start = || -> Result<std::process::ExitStatus, String> {
+        fs::create_dir_all(&output_path).map_err(|e| e.to_string())?;
+
+        let mut command = Command::new(executable);
+        let checkpoint_path = config.checkpoint_path();
+        if checkpoint_path.exists() {
+            command.arg("--resume").arg(checkpoint_path);
+        } else {
+            let config_path = output_path.join(format!("run_{}_config.toml", config.run_id));
+            config.save(&config_path)?;
+            command.arg("--config").arg(config_path);
+        }
+
+        let log = File::options()
+            .create(true)
+            .append(true)
+            .open(output_path.join(format!("run_{}_log.txt", config.run_id)))
+            .map_err(|e| e.to_string())?;
+        let log_err = log.try_clone().map_err(|e| e.to_string())?;
+        command.stdin(Stdio::null())
+            .stdout(log)
+            .stderr(log_err)
+            .status()
+            .map_err(|e| format!("cannot start {}: {}", executable.display(), e))
+    };
+
+    match start() {
+        Err(error) => JobStatus::Failed(error),
+        Ok(status) if !status.success() => JobStatus::Failed(status.to_string()),
+        // an interrupted run exits normally, but without a summary
+        Ok(_) if !config.summary_path().exists() => JobStatus::Interrupted,
+        Ok(_) => JobStatus::Finished,
+    }
+}
+
+/// All combinations of the grid, each repeated `repetitions` times. The last grid key (in
+/// alphabetical order) changes fastest.
+pub fn expand_grid(experiment: &Table, grid: &Table, repetitions: usize) -> Result<Vec<BatchJob>, String> {
+    if repetitions == 0 {
+        return Err("repetitions must be > 0".to_string());
+    }
+    let mut axes: Vec<(&String, &Vec<Value>)> = Vec::with_capacity(grid.len());
+    for (key, values) in grid {
+        if key == "run_id" || key == "seed" {
+            return Err(format!("grid key {} is set by the batch", key));
+        }
+        match values {
+            Value::Array(values) if !values.is_empty() => axes.push((key, values)),
+            _ => return Err(format!("grid key {} must be a non-empty array", key)),
+        }
+    }
+
+    let first_run_id = experiment.get("run_id")
+        .and_then(Value::as_integer)
+        .ok_or("experiment needs an integer run_id")?;
+    let seed = match experiment.get("seed") {
+        None => None,
+        Some(seed) => Some(seed.as_integer().ok_or("seed must be an integer")?),
+    };
+
+    let nbr_combinations: usize = axes.iter().map(|(_, values)| values.len()).product();
+    let mut jobs: Vec<BatchJob> = Vec::with_capacity(nbr_combinations * repetitions);
+    let mut outputs: HashSet<PathBuf> = HashSet::new();
+    for combination in 0..nbr_combinations {
+        let mut grid_values: Vec<Value> = Vec::with_capacity(axes.len());
+        let mut remainder = combination;
+        for (_, values) in axes.iter().rev() {
+            grid_values.push(values[remainder % values.len()].clone());
+            remainder /= values.len();
+        }
+        grid_values.reverse();
+
+        let mut table = experiment.clone();
+        for ((key, _), value) in axes.iter().zip(grid_values.iter()) {
+            set_value(&mut table, key, value.clone())?;
+        }
+
+        for repetition in 0..repetitions as i64 {
+            let describe = || format!("grid {:?}, repetition {}", grid_values.iter().map(value_to_string).collect::<Vec<String>>(), repetition);
+
+            table.insert("run_id".to_string(), Value::Integer(first_run_id + repetition));
+            if let Some(seed) = seed {
+                let seed = seed.checked_add(repetition)
+                    .ok_or_else(|| format!("{}: seed overflows", describe()))?;
+                table.insert("seed".to_string(), Value::Integer(seed));
+            }
+
+            let config: ExperimentConfig = table.clone()
+                .try_into()
+                .map_err(|e| format!("{}: {}", describe(), e))?;
+            config.validate().map_err(|e| format!("{}: {}", describe(), e))?;
+
+            // the output path does not contain every parameter
+            if !outputs.insert(config.summary_path()) {
+                return Err(format!("{}: writes to {} like another run; add output_dir to the grid",
+                                   describe(), config.summary_path().display()));
+            }
+            jobs.push(BatchJob { config, grid_values: grid_values.clone() });
+        }
+    }
+    return Ok(jobs);
+}
+
+/// Sets `key`, i.e. "cgp.graph_width", to `value`.
+fn set_value(table: &mut Table, key: &str, value: Value) -> Result<(), String> {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let name = parts.pop().unwrap();
+
+    let mut current = table;
+    for part in parts {
+        current = current.entry(part)
+            .or_insert_with(|| Value::Table(Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| format!("grid key {}: {} is not a table", key, part))?;
+    }
+    current.insert(name.to_string(), value);
+    return Ok(());
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPERIMENT: &str = r#"
+        run_id = 10
+        seed = 100
+        dataset_type = "bool"
+        dataset = 3
+        max_iterations = 1000
+        output_dir = "out"
+
+        [operators]
+        mutation_type = "single"
+
+        [cgp]
+        cgp_type = "Standard"
+        graph_width = 50
+        elitists = 1
+        population_size = 4
+        fitness_threshold = 0.0001
+    "#;
+
+    fn jobs(grid: &str, repetitions: usize) -> Result<Vec<BatchJob>, String> {
+        expand_grid(&toml::from_str(EXPERIMENT).unwrap(), &toml::from_str(grid).unwrap(), repetitions)
+    }
+
+    #[test]
+    fn expands_all_combinations() {
+        let jobs = jobs(r#"
+            dataset = [0, 1, 2]
+            "cgp.graph_width" = [50, 100]
+        "#, 2).unwrap();
+
+        assert_eq!(12, jobs.len());
+        let configs: Vec<(usize, usize, usize)> = jobs.iter()
+            .map(|job| (job.config.cgp.graph_width, job.config.dataset, job.config.run_id))
+            .collect();
+        assert_eq!((50, 0, 10), configs[0]);
+        assert_eq!((50, 0, 11), configs[1]);
+        assert_eq!((50, 1, 10), configs[2]);
+        assert_eq!((100, 2, 11), configs[11]);
+        assert_eq!(vec![Value::Integer(100), Value::Integer(2)], jobs[11].grid_values);
+    }
+
+    #[test]
+    fn repetitions_get_consecutive_seeds() {
+        let jobs = jobs(r#"dataset = [0, 1]"#, 3).unwrap();
+
+        let seeds: Vec<Option<u64>> = jobs.iter().map(|job| job.config.seed).collect();
+        assert_eq!(vec![Some(100), Some(101), Some(102), Some(100), Some(101), Some(102)], seeds);
+    }
+
+    #[test]
+    fn empty_grid_repeats_the_experiment() {
+        assert_eq!(4, jobs("", 4).unwrap().len());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let error = jobs(r#""cgp.graph_widht" = [50]"#, 1).err().unwrap();
+
+        assert!(error.contains("graph_widht"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_invalid_combinations() {
+        let error = jobs(r#""operators.mutation_type" = ["single", "point"]"#, 1).err().unwrap();
+
+        assert!(error.contains("mutation_rate"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_runs_with_the_same_output() {
+        let error = jobs(r#""cgp.population_size" = [4, 8]"#, 1).err().unwrap();
+
+        assert!(error.contains("output_dir"), "{}", error);
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        assert_eq!("single", csv_field("single"));
+        assert_eq!("\"a,b\"", csv_field("a,b"));
+        assert_eq!("\"say \"\"hi\"\"\"", csv_field("say \"hi\""));
+    }
+}
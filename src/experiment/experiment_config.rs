@@ -0,0 +1,695 @@
+//! Self-describing experiment files.
+//! An `ExperimentConfig` contains every `CgpParameters` field plus the operator choices, the
+//! dataset, the iteration budget and the output directory of a single run.
+//! Experiment files are written in TOML, i.e.:
+//!
+//! ```toml
+//! run_id = 0
+//! seed = 42
+//! dataset_type = "bool"
+//! dataset = 3
+//! max_iterations = 500000
+//! stagnation_generations = 100000
+//! observers = ["stdout", "csv"]
+//! output_dir = "Experiments_Output_boolean"
+//!
+//! [operators]
+//! mutation_type = "single"
+//! selection = "one_plus_four"
+//!
+//! [cgp]
+//! cgp_type = "Standard"
+//! graph_width = 500
+//! elitists = 1
+//! population_size = 4
+//! fitness_threshold = 0.0001
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::components::cgp_components::constant_tuning;
+use crate::components::cgp_components::constant_tuning::ConstantOptimizer;
+use crate::components::cgp_components::interval_analysis;
+use crate::components::cgp_components::interval_analysis::INTERVAL_ACTIONS;
+use crate::components::cgp_components::objectives::Objective;
+use crate::components::operator_registry;
+use crate::function_set::constant;
+use crate::function_set::f64_function_set::get_f64_function_set;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::function_set::integer_function_set::get_integer_function_set;
+use crate::function_set::regression_function_set::get_regression_function_set;
+use crate::global_params::CgpParameters;
+use crate::utils::fitness_metrics;
+use crate::utils::numeric::Numeric;
+use crate::utils::observer_sinks;
+use crate::utils::runner::StoppingCriterion;
+
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub enum DatasetType {
+    #[serde(rename = "bool")]
+    Bool,
+    #[serde(rename = "f32")]
+    F32,
+    #[serde(rename = "f64")]
+    F64,
+    #[serde(rename = "i64")]
+    I64,
+}
+
+impl DatasetType {
+    /// Number of datasets of the type; see `datasets`. `f32` and `f64` share the regression
+    /// benchmarks.
+    pub fn nbr_datasets(&self) -> usize {
+        match self {
+            DatasetType::Bool => 4,
+            DatasetType::F32 | DatasetType::F64 => 5,
+            DatasetType::I64 => 2,
+        }
+    }
+}
+
+impl Display for DatasetType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatasetType::Bool => write!(f, "bool"),
+            DatasetType::F32 => write!(f, "f32"),
+            DatasetType::F64 => write!(f, "f64"),
+            DatasetType::I64 => write!(f, "i64"),
+        }
+    }
+}
+
+/// Names of the operators; see `operator_registry` for the allowed values.
+/// Everything but `mutation_type` defaults to the (1+4)-ES of the paper.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OperatorConfig {
+    pub mutation_type: String,
+    #[serde(default = "default_graph_operator")]
+    pub node_mutation: String,
+    #[serde(default = "default_graph_operator")]
+    pub active_nodes: String,
+    #[serde(default = "default_none_operator")]
+    pub reorder: String,
+    #[serde(default = "default_none_operator")]
+    pub crossover: String,
+    #[serde(default = "default_one_plus_four_operator")]
+    pub evaluation: String,
+    #[serde(default = "default_one_plus_four_operator")]
+    pub selection: String,
+    // secondary objectives of the "nsga2" selection next to the fitness, see
+    // `objectives::SECONDARY_OBJECTIVES`; defaults to all of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objectives: Option<Vec<String>>,
+}
+
+fn default_graph_operator() -> String {
+    "standard".to_string()
+}
+
+fn default_none_operator() -> String {
+    "none".to_string()
+}
+
+fn default_one_plus_four_operator() -> String {
+    "one_plus_four".to_string()
+}
+
+impl OperatorConfig {
+    /// The (1+4)-ES of the paper with the given chromosome mutation.
+    pub fn one_plus_four(mutation_type: &str) -> Self {
+        Self {
+            mutation_type: mutation_type.to_string(),
+            node_mutation: default_graph_operator(),
+            active_nodes: default_graph_operator(),
+            reorder: default_none_operator(),
+            crossover: default_none_operator(),
+            evaluation: default_one_plus_four_operator(),
+            selection: default_one_plus_four_operator(),
+            objectives: None,
+        }
+    }
+
+    /// The secondary objectives of the "nsga2" selection.
+    pub fn objectives(&self) -> Result<Vec<Objective>, String> {
+        return match &self.objectives {
+            Some(names) => names.iter().map(|name| Objective::from_name(name)).collect(),
+            None => Ok(vec![Objective::ActiveNodes, Objective::Depth]),
+        };
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentConfig {
+    pub run_id: usize,
+    // Seed of the only RNG of a run; drawn at random if left out. The resolved config of a run
+    // always contains the seed, so every run can be repeated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    pub dataset_type: DatasetType,
+    // For Boolean:
+    //  0: Parity, 1: Encode, 2: Decode, 3: Multiply
+    // for symbolic regression (f32 and f64):
+    //  0: keijzer_6, 1: koza_3, 2: nguyen_7, 3: pagie_1, 4: complex_square (two outputs)
+    // for integer problems (i64):
+    //  0: koza_1, 1: sum_of_squares
+    pub dataset: usize,
+    pub max_iterations: usize,
+    // optional stopping criteria in addition to `max_iterations` and `cgp.fitness_threshold`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fitness_evaluations: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_wall_clock_seconds: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stagnation_generations: Option<usize>,
+    // generations between two checkpoints; 0 only writes a checkpoint when interrupted
+    #[serde(default = "default_checkpoint_interval")]
+    pub checkpoint_interval: usize,
+    // loggers of the run, see `observer_sinks`; the iteration and mutation files are always written
+    #[serde(default = "default_observers")]
+    pub observers: Vec<String>,
+    // entries of the fitness cache of the active subgraphs; 0 disables the cache
+    #[serde(default)]
+    pub fitness_cache_size: usize,
+    // Boolean datasets only: evaluates packed truth tables, 64 rows per word operation; the
+    // results are the same
+    #[serde(default)]
+    pub bit_parallel: bool,
+    // threads that evaluate the children of a generation; the results do not depend on it
+    #[serde(default = "default_threads")]
+    pub threads: usize,
+    // evaluates a mutant from the node values of its parent; the results are the same
+    #[serde(default)]
+    pub incremental_evaluation: bool,
+    // evaluator of the chromosomes, see `operator_registry::EVALUATORS`; the results are the same
+    #[serde(default = "default_evaluator")]
+    pub evaluator: String,
+    // compares the outputs of a chromosome to the labels, see `fitness_metrics`; defaults to the
+    // fitness of the paper, "sae" for regression and "hamming" for Boolean datasets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fitness_metric: Option<String>,
+    // weight of every output node of the "weighted_hamming" metric
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fitness_weights: Option<Vec<f64>>,
+    // f32 and f64 regression only: fits `intercept + slope * output` of every output node to the
+    // labels before the metric is applied, see `linear_scaling`
+    #[serde(default)]
+    pub linear_scaling: bool,
+    // f32 and f64 regression with cgp.constants only: tunes the constants of the best chromosome
+    // with a gradient-based optimizer, see `constant_tuning`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constant_tuning: Option<ConstantTuningConfig>,
+    // f32 and f64 regression only: interval analysis of the programs over the input domain, see
+    // `interval_analysis`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_analysis: Option<IntervalAnalysisConfig>,
+    pub output_dir: String,
+    pub operators: OperatorConfig,
+    pub cgp: CgpParameters,
+}
+
+/// Tuning of the constants of the best elitist, see `TuneBestConstants`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConstantTuningConfig {
+    // generations between two tunings
+    pub interval: usize,
+    // see `constant_tuning::CONSTANT_OPTIMIZERS` for allowed values
+    #[serde(default = "default_constant_optimizer")]
+    pub optimizer: String,
+    // optimizer steps per tuning
+    #[serde(default = "default_constant_tuning_steps")]
+    pub steps: usize,
+    // Adam only
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f64,
+}
+
+fn default_constant_optimizer() -> String {
+    "lm".to_string()
+}
+
+fn default_constant_tuning_steps() -> usize {
+    10
+}
+
+fn default_learning_rate() -> f64 {
+    0.01
+}
+
+impl ConstantTuningConfig {
+    pub fn optimizer(&self) -> Result<ConstantOptimizer, String> {
+        return ConstantOptimizer::from_name(&self.optimizer, self.learning_rate);
+    }
+}
+
+/// Interval analysis of the programs; see `interval_analysis`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IntervalAnalysisConfig {
+    // one [min, max] per input; defaults to the range of the inputs of the training and eval data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_domain: Option<Vec<[f64; 2]>>,
+    // "report" analyzes the best chromosome of the run; "reject" gives every chromosome with an
+    // undefined or unbounded output the worst fitness, f32::MAX, and "penalize" adds `penalty` to
+    // its fitness
+    #[serde(default = "default_interval_action")]
+    pub action: String,
+    #[serde(default)]
+    pub penalty: f32,
+}
+
+fn default_interval_action() -> String {
+    "report".to_string()
+}
+
+impl IntervalAnalysisConfig {
+    /// The penalty of the chromosomes with an undefined or unbounded output; `None` for "report".
+    pub fn penalty(&self) -> Option<f32> {
+        match self.action.as_str() {
+            "reject" => Some(f32::MAX),
+            "penalize" => Some(self.penalty),
+            _ => None,
+        }
+    }
+}
+
+fn default_checkpoint_interval() -> usize {
+    10_000
+}
+
+fn default_observers() -> Vec<String> {
+    vec!["stdout".to_string()]
+}
+
+fn default_threads() -> usize {
+    1
+}
+
+fn default_evaluator() -> String {
+    "arena".to_string()
+}
+
+impl ExperimentConfig {
+    /// Reads and validates an experiment file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read experiment file {}: {}", path.display(), e))?;
+        let config: ExperimentConfig = toml::from_str(&content)
+            .map_err(|e| format!("cannot parse experiment file {}: {}", path.display(), e))?;
+
+        config.validate()?;
+        return Ok(config);
+    }
+
+    /// Writes the experiment in the same format `load` expects.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_toml())
+            .map_err(|e| format!("cannot write experiment file {}: {}", path.display(), e))
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("experiment config is always serializable")
+    }
+
+    /// Checks everything that can be checked without loading the dataset.
+    pub fn validate(&self) -> Result<(), String> {
+        let params = &self.cgp;
+
+        // TOML integers are signed 64 bit
+        if let Some(seed) = self.seed {
+            if seed > i64::MAX as u64 {
+                return Err(format!("seed must be <= {}, got {}", i64::MAX, seed));
+            }
+        }
+        if self.dataset >= self.dataset_type.nbr_datasets() {
+            return Err(format!("dataset must be in [0, {}] for dataset_type {}, got {}",
+                               self.dataset_type.nbr_datasets() - 1, self.dataset_type, self.dataset));
+        }
+        if self.max_iterations == 0 {
+            return Err("max_iterations must be > 0".to_string());
+        }
+        if self.max_fitness_evaluations == Some(0) {
+            return Err("max_fitness_evaluations must be > 0".to_string());
+        }
+        if let Some(seconds) = self.max_wall_clock_seconds {
+            if seconds.is_nan() || seconds <= 0. {
+                return Err(format!("max_wall_clock_seconds must be > 0, got {}", seconds));
+            }
+        }
+        if self.stagnation_generations == Some(0) {
+            return Err("stagnation_generations must be > 0".to_string());
+        }
+        for (i, name) in self.observers.iter().enumerate() {
+            if !observer_sinks::OBSERVERS.contains(&name.as_str()) {
+                return Err(format!("unknown observer '{}', expected one of {:?}", name, observer_sinks::OBSERVERS));
+            }
+            if self.observers[..i].contains(name) {
+                return Err(format!("observer '{}' is given twice", name));
+            }
+        }
+        if self.bit_parallel && self.dataset_type != DatasetType::Bool {
+            return Err(format!("bit_parallel needs a Boolean dataset, got dataset_type {}", self.dataset_type));
+        }
+        if self.threads == 0 {
+            return Err("threads must be > 0".to_string());
+        }
+        if !operator_registry::EVALUATORS.contains(&self.evaluator.as_str()) {
+            return Err(format!("unknown evaluator '{}', expected one of {:?}", self.evaluator, operator_registry::EVALUATORS));
+        }
+        // the incremental evaluator replaces the evaluator
+        if self.incremental_evaluation && self.evaluator != "arena" {
+            return Err(format!("incremental_evaluation needs evaluator 'arena', got '{}'", self.evaluator));
+        }
+        // the number of weights is checked with the dataset, see `BooleanMetric::from_name`
+        let metrics: &[&str] = match self.dataset_type {
+            DatasetType::Bool => &fitness_metrics::BOOLEAN_METRICS,
+            _ => &fitness_metrics::REGRESSION_METRICS,
+        };
+        if !metrics.contains(&self.fitness_metric_name()) {
+            return Err(format!("unknown fitness metric '{}' for dataset_type {}, expected one of {:?}",
+                               self.fitness_metric_name(), self.dataset_type, metrics));
+        }
+        if self.fitness_weights.is_some() != (self.fitness_metric_name() == "weighted_hamming") {
+            return Err("fitness_weights are needed by and only used by the weighted_hamming metric".to_string());
+        }
+        if self.linear_scaling && !matches!(self.dataset_type, DatasetType::F32 | DatasetType::F64) {
+            return Err(format!("linear_scaling needs dataset_type f32 or f64, got {}", self.dataset_type));
+        }
+        if self.output_dir.is_empty() {
+            return Err("output_dir must not be empty".to_string());
+        }
+        if params.graph_width == 0 {
+            return Err("cgp.graph_width must be > 0".to_string());
+        }
+        if params.population_size == 0 {
+            return Err("cgp.population_size must be > 0".to_string());
+        }
+        if params.fitness_threshold.is_nan() || params.fitness_threshold < 0. {
+            return Err(format!("cgp.fitness_threshold must be >= 0, got {}", params.fitness_threshold));
+        }
+        if !(0. ..=1.).contains(&params.crossover_rate) {
+            return Err(format!("cgp.crossover_rate must be in [0, 1], got {}", params.crossover_rate));
+        }
+        if let Some(constants) = &params.constants {
+            if self.dataset_type == DatasetType::Bool {
+                return Err("cgp.constants need a numeric dataset_type, got bool".to_string());
+            }
+            if !constants.min.is_finite() || !constants.max.is_finite() || constants.min > constants.max {
+                return Err(format!("cgp.constants need finite min <= max, got [{}, {}]", constants.min, constants.max));
+            }
+            if !constants.sigma.is_finite() || constants.sigma <= 0. {
+                return Err(format!("cgp.constants.sigma must be > 0, got {}", constants.sigma));
+            }
+        }
+        if let Some(tuning) = &self.constant_tuning {
+            // the function set is checked in check_function_set
+            if params.constants.is_none() {
+                return Err("constant_tuning needs cgp.constants".to_string());
+            }
+            if tuning.interval == 0 || tuning.steps == 0 {
+                return Err("constant_tuning.interval and constant_tuning.steps must be > 0".to_string());
+            }
+            if !tuning.learning_rate.is_finite() || tuning.learning_rate <= 0. {
+                return Err(format!("constant_tuning.learning_rate must be > 0, got {}", tuning.learning_rate));
+            }
+            tuning.optimizer()?;
+        }
+        if let Some(analysis) = &self.interval_analysis {
+            // the function set is checked in check_function_set
+            if self.dataset_type == DatasetType::Bool {
+                return Err("interval_analysis needs a numeric dataset_type, got bool".to_string());
+            }
+            if !INTERVAL_ACTIONS.contains(&analysis.action.as_str()) {
+                return Err(format!("unknown interval_analysis.action '{}', expected one of {:?}", analysis.action, INTERVAL_ACTIONS));
+            }
+            if analysis.action == "penalize" && !(analysis.penalty.is_finite() && analysis.penalty > 0.) {
+                return Err(format!("interval_analysis.action 'penalize' needs a penalty > 0, got {}", analysis.penalty));
+            }
+            // the number of inputs is checked with the dataset
+            for [min, max] in analysis.input_domain.iter().flatten() {
+                if !min.is_finite() || !max.is_finite() || min > max {
+                    return Err(format!("interval_analysis.input_domain needs finite min <= max, got [{}, {}]", min, max));
+                }
+            }
+        }
+        match self.dataset_type {
+            // constants, constant tuning and interval analysis are rejected for bool above
+            DatasetType::Bool => {}
+            DatasetType::F32 => self.check_function_set(get_regression_function_set())?,
+            DatasetType::F64 => self.check_function_set(get_f64_function_set())?,
+            DatasetType::I64 => self.check_function_set(get_integer_function_set())?,
+        }
+
+        operator_registry::check_operator_compatibility(&self.operators, params)?;
+
+        match self.operators.mutation_type.as_str() {
+            "point" if params.mutation_rate.is_nan() || params.mutation_rate <= 0. || params.mutation_rate > 1. => {
+                return Err(format!("point mutation needs cgp.mutation_rate in (0, 1], got {}", params.mutation_rate));
+            }
+            "multi" if params.multi_n_number_mutations == 0 => {
+                return Err("multi mutation needs cgp.multi_n_number_mutations > 0".to_string());
+            }
+            "split" if !(0. ..=1.).contains(&params.split_mutation_rate_active)
+                || !(0. ..=1.).contains(&params.split_mutation_rate_inactive) => {
+                return Err("split mutation needs both split mutation rates in [0, 1]".to_string());
+            }
+            // unknown names are reported by check_operator_compatibility
+            _ => {}
+        }
+
+        return Ok(());
+    }
+
+    /// Checks that the functions of the run support the features that are enabled; the function
+    /// set is extended by the constant function if `cgp.constants` is set, as in a run.
+    fn check_function_set<T: Numeric>(&self, function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> Result<(), String> {
+        let function_set = if self.cgp.constants.is_some() { constant::with_constant(function_set)? } else { function_set };
+        constant::check_constant_functions(&function_set)?;
+        if self.constant_tuning.is_some() {
+            constant_tuning::check_dual_functions(&function_set)
+                .map_err(|e| format!("constant_tuning needs a function set that supports dual numbers: {}", e))?;
+        }
+        if self.interval_analysis.is_some() {
+            interval_analysis::check_interval_functions(&function_set)
+                .map_err(|e| format!("interval_analysis needs a function set that supports intervals: {}", e))?;
+        }
+        return Ok(());
+    }
+
+    /// Name of the fitness metric; the default of the dataset type if none is given.
+    pub fn fitness_metric_name(&self) -> &str {
+        match (&self.fitness_metric, &self.dataset_type) {
+            (Some(name), _) => name,
+            (None, DatasetType::Bool) => "hamming",
+            (None, _) => "sae",
+        }
+    }
+
+    /// Draws a seed if none is given and returns it.
+    pub fn resolve_seed(&mut self) -> u64 {
+        let seed = *self.seed.get_or_insert_with(|| rand::thread_rng().gen_range(0..=i64::MAX as u64));
+        return seed;
+    }
+
+    /// All stopping criteria of the experiment; the target fitness is `cgp.fitness_threshold`.
+    /// A run can always be interrupted, see `checkpoint::install_signal_handler`.
+    pub fn stopping_criteria(&self) -> Vec<StoppingCriterion> {
+        let mut criteria = vec![
+            StoppingCriterion::MaxGenerations(self.max_iterations),
+            StoppingCriterion::TargetFitness(self.cgp.fitness_threshold),
+            StoppingCriterion::Interrupted,
+        ];
+        if let Some(max) = self.max_fitness_evaluations {
+            criteria.push(StoppingCriterion::MaxFitnessEvaluations(max));
+        }
+        if let Some(seconds) = self.max_wall_clock_seconds {
+            criteria.push(StoppingCriterion::WallClock(Duration::from_secs_f32(seconds)));
+        }
+        if let Some(window) = self.stagnation_generations {
+            criteria.push(StoppingCriterion::Stagnation(window));
+        }
+        return criteria;
+    }
+
+    /// Directory the results of the run are written to.
+    pub fn output_path(&self) -> PathBuf {
+        let params = &self.cgp;
+        Path::new(&self.output_dir)
+            .join(format!("dataset_{}", self.dataset))
+            .join(&self.operators.mutation_type)
+            .join(format!("number_nodes_{}_prob_active_{}_inactive_{}_multi_{}_point_{}", params.graph_width, params.split_mutation_rate_active, params.split_mutation_rate_inactive, params.multi_n_number_mutations, params.mutation_rate))
+    }
+
+    pub fn checkpoint_path(&self) -> PathBuf {
+        self.output_path().join(format!("run_{}_checkpoint.bin", self.run_id))
+    }
+
+    /// The `RunSummary` of a finished run; see `batch`.
+    pub fn summary_path(&self) -> PathBuf {
+        self.output_path().join(format!("run_{}_summary.toml", self.run_id))
+    }
+
+    /// Returns the `CgpParameters` for the given dataset and function set.
+    /// Dimensions that are left out (i.e. 0) in the experiment file are filled in; dimensions that
+    /// are given must match.
+    pub fn resolve_cgp_parameters(&self,
+                                  nbr_inputs: usize,
+                                  nbr_outputs: usize,
+                                  number_functions: usize) -> Result<CgpParameters, String> {
+        let mut params = self.cgp.clone();
+
+        for (name, given, actual) in [("nbr_inputs", &mut params.nbr_inputs, nbr_inputs),
+                                      ("nbr_outputs", &mut params.nbr_outputs, nbr_outputs),
+                                      ("number_functions", &mut params.number_functions, number_functions)] {
+            if *given == 0 {
+                *given = actual;
+            } else if *given != actual {
+                return Err(format!("cgp.{} is {}, but dataset {} ({}) needs {}",
+                                   name, given, self.dataset, self.dataset_type, actual));
+            }
+        }
+
+        return Ok(params);
+    }
+}
+
+impl Display for ExperimentConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "############ Experiment ############")?;
+        write!(f, "{}", self.to_toml())?;
+        writeln!(f, "####################################")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::cgp_components::cgp_types::CGPType;
+    use crate::components::evo_operators_for_population::crossover_operators::crossover_types::CrossoverType;
+
+    const MINIMAL: &str = r#"
+run_id = 3
+dataset_type = "bool"
+dataset = 1
+max_iterations = 1000
+output_dir = "out"
+
+[operators]
+mutation_type = "single"
+
+[cgp]
+cgp_type = "Standard"
+graph_width = 50
+elitists = 1
+population_size = 4
+fitness_threshold = 0.0001
+"#;
+
+    fn parse(content: &str) -> Result<ExperimentConfig, String> {
+        return toml::from_str(content).map_err(|e| e.to_string());
+    }
+
+    #[test]
+    fn left_out_fields_get_their_defaults() {
+        let config = parse(MINIMAL).unwrap();
+        config.validate().unwrap();
+
+        assert_eq!(config.seed, None);
+        assert_eq!(config.max_fitness_evaluations, None);
+        assert_eq!(config.max_wall_clock_seconds, None);
+        assert_eq!(config.stagnation_generations, None);
+        assert_eq!(config.checkpoint_interval, 10_000);
+        assert_eq!(config.observers, vec!["stdout".to_string()]);
+        assert_eq!(config.fitness_cache_size, 0);
+        assert!(!config.bit_parallel);
+        assert_eq!(config.threads, 1);
+        assert!(!config.incremental_evaluation);
+        assert_eq!(config.evaluator, "arena");
+        assert_eq!(config.fitness_metric, None);
+        assert_eq!(config.fitness_metric_name(), "hamming");
+        assert!(!config.linear_scaling);
+        assert!(config.constant_tuning.is_none());
+        assert!(config.interval_analysis.is_none());
+
+        let operators = &config.operators;
+        assert_eq!(operators.node_mutation, "standard");
+        assert_eq!(operators.active_nodes, "standard");
+        assert_eq!(operators.reorder, "none");
+        assert_eq!(operators.crossover, "none");
+        assert_eq!(operators.evaluation, "one_plus_four");
+        assert_eq!(operators.selection, "one_plus_four");
+        assert_eq!(operators.objectives, None);
+
+        let params = &config.cgp;
+        assert!(matches!(params.cgp_type, CGPType::Standard));
+        assert!(matches!(params.crossover_type, CrossoverType::NoCrossover));
+        assert_eq!((params.nbr_inputs, params.nbr_outputs, params.number_functions), (0, 0, 0));
+        assert_eq!(params.mutation_rate, 0.);
+        assert_eq!(params.multi_n_number_mutations, 0);
+        assert_eq!(params.constants, None);
+    }
+
+    #[test]
+    fn optional_sections_get_their_defaults() {
+        let content = MINIMAL.replace("output_dir = \"out\"",
+                                      "output_dir = \"out\"\n[constant_tuning]\ninterval = 5\n[interval_analysis]\n");
+        let config = parse(&content).unwrap();
+
+        let tuning = config.constant_tuning.unwrap();
+        assert_eq!((tuning.interval, tuning.optimizer.as_str(), tuning.steps, tuning.learning_rate), (5, "lm", 10, 0.01));
+        let analysis = config.interval_analysis.unwrap();
+        assert_eq!(analysis.input_domain, None);
+        assert_eq!(analysis.action, "report");
+        assert_eq!(analysis.penalty(), None);
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        for (old, new) in [("run_id = 3", "run_id = 3\nmax_iteration = 10"),
+                           ("mutation_type = \"single\"", "mutation_type = \"single\"\nmutation = \"point\""),
+                           ("graph_width = 50", "graph_width = 50\nnodes = 50"),
+                           ("output_dir = \"out\"", "output_dir = \"out\"\n[constant_tuning]\ninterval = 5\nrate = 0.1\n"),
+                           ("output_dir = \"out\"", "output_dir = \"out\"\n[interval_analysis]\ndomain = []\n")] {
+            let content = MINIMAL.replace(old, new);
+            let error = parse(&content).err().expect("unknown field is accepted");
+            assert!(error.contains("unknown field"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn resolved_seed_is_kept() {
+        let mut config = parse(MINIMAL).unwrap();
+        let seed = config.resolve_seed();
+
+        assert!(seed <= i64::MAX as u64);
+        assert_eq!(config.seed, Some(seed));
+        assert_eq!(config.resolve_seed(), seed);
+        // the resolved config of a run repeats it
+        let resolved = parse(&config.to_toml()).unwrap();
+        resolved.validate().unwrap();
+        assert_eq!(resolved.seed, Some(seed));
+
+        let mut config = parse(&format!("seed = 42\n{}", MINIMAL)).unwrap();
+        assert_eq!(config.resolve_seed(), 42);
+    }
+
+    #[test]
+    fn output_layout() {
+        let content = MINIMAL.replace("mutation_type = \"single\"", "mutation_type = \"split\"")
+            .replace("graph_width = 50", "graph_width = 50\nsplit_mutation_rate_active = 0.1\nsplit_mutation_rate_inactive = 0.5");
+        let config = parse(&content).unwrap();
+
+        let output_path = Path::new("out/dataset_1/split/number_nodes_50_prob_active_0.1_inactive_0.5_multi_0_point_0");
+        assert_eq!(config.output_path(), output_path);
+        assert_eq!(config.summary_path(), output_path.join("run_3_summary.toml"));
+        assert_eq!(config.checkpoint_path(), output_path.join("run_3_checkpoint.bin"));
+    }
+}
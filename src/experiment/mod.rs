@@ -0,0 +1,3 @@
+pub mod experiment_config;
+pub mod batch;
+pub mod analysis;
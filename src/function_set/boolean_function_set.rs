@@ -1,16 +1,16 @@
-use std::rc::Rc;
+use std::sync::Arc;
 use crate::function_set::function_trait::FunctionTrait;
 
 
-pub fn get_boolean_function_set() -> Rc<Vec<Box<dyn FunctionTrait<bool>>>> {
-    let mut function_set: Vec<Box<dyn FunctionTrait<bool>>> = Vec::new();
+pub fn get_boolean_function_set() -> Arc<Vec<Box<dyn FunctionTrait<bool>>>> {
+    let function_set: Vec<Box<dyn FunctionTrait<bool>>> = vec![
+        Box::new(BoolAND),
+        Box::new(BoolOR),
+        Box::new(BoolNAND),
+        Box::new(BoolNOR),
+    ];
 
-    function_set.push(Box::new(BoolAND));
-    function_set.push(Box::new(BoolOR));
-    function_set.push(Box::new(BoolNAND));
-    function_set.push(Box::new(BoolNOR));
-
-    return Rc::new(function_set);
+    return Arc::new(function_set);
 }
 
 
@@ -23,68 +23,80 @@ pub struct BoolNAND;
 pub struct BoolNOR;
 
 impl FunctionTrait<bool> for BoolAND {
+    fn get_name(&self) -> &'static str {
+        return "and";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 2;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<bool>]) -> Vec<bool> {
+    fn execute_function_into(&self, inputs: &[&Vec<bool>], output: &mut Vec<bool>) {
         let input0 = inputs[0];
         let input1 = inputs[1];
-        return input0
+        output.extend(input0
             .iter()
             .zip(input1.iter())
-            .map(|(a, b)| *a & *b)
-            .collect();
+            .map(|(a, b)| *a & *b));
     }
 }
 
 impl FunctionTrait<bool> for BoolOR {
+    fn get_name(&self) -> &'static str {
+        return "or";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 2;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<bool>]) -> Vec<bool> {
+    fn execute_function_into(&self, inputs: &[&Vec<bool>], output: &mut Vec<bool>) {
         let input0 = inputs[0];
         let input1 = inputs[1];
-        return input0
+        output.extend(input0
             .iter()
             .zip(input1.iter())
-            .map(|(a, b)| *a | *b)
-            .collect();
+            .map(|(a, b)| *a | *b));
     }
 }
 
 impl FunctionTrait<bool> for BoolNAND {
 
+    fn get_name(&self) -> &'static str {
+        return "nand";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 2;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<bool>]) -> Vec<bool> {
+    fn execute_function_into(&self, inputs: &[&Vec<bool>], output: &mut Vec<bool>) {
         let input0 = inputs[0];
         let input1 = inputs[1];
-        return input0
+        output.extend(input0
             .iter()
             .zip(input1.iter())
-            .map(|(a, b)| !(*a & *b))
-            .collect();
+            .map(|(a, b)| !(*a & *b)));
     }
 }
 
 impl FunctionTrait<bool> for BoolNOR {
 
+    fn get_name(&self) -> &'static str {
+        return "nor";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 2;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<bool>]) -> Vec<bool> {
+    fn execute_function_into(&self, inputs: &[&Vec<bool>], output: &mut Vec<bool>) {
         let input0 = inputs[0];
         let input1 = inputs[1];
-        return input0
+        output.extend(input0
             .iter()
             .zip(input1.iter())
-            .map(|(a, b)| !(*a | *b))
-            .collect();
+            .map(|(a, b)| !(*a | *b)));
     }
 }
 
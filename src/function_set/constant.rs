@@ -0,0 +1,47 @@
+//! The constant function of the numeric function sets. A node with it outputs its constant gene,
+//! see `CGPNode::constant`; the genes are only drawn and mutated if `CgpParameters::constants` is
+//! set.
+
+use std::sync::Arc;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::numeric::Numeric;
+
+pub struct Constant;
+
+impl<T: Numeric> FunctionTrait<T> for Constant {
+    fn get_name(&self) -> &'static str {
+        return "const";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 0;
+    }
+
+    // without inputs there are no rows to fill; the evaluators call execute_constant_into, which
+    // knows the constant gene and the number of rows
+    fn execute_function_into(&self, _inputs: &[&Vec<T>], _output: &mut Vec<T>) {}
+
+    fn execute_constant_into(&self, constant: f64, nbr_rows: usize, output: &mut Vec<T>) -> Result<(), String> {
+        output.resize(output.len() + nbr_rows, T::from_constant(constant));
+        return Ok(());
+    }
+}
+
+/// `function_set` with `Constant` as its last function; the ids of the other functions do not
+/// change. Fails if `function_set` is shared, as it is extended in place.
+#[allow(clippy::type_complexity)]
+pub fn with_constant<T: Numeric>(function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) -> Result<Arc<Vec<Box<dyn FunctionTrait<T>>>>, String> {
+    let mut function_set = Arc::try_unwrap(function_set)
+        .map_err(|_| "cannot add the constant function to a shared function set".to_string())?;
+    function_set.push(Box::new(Constant));
+    return Ok(Arc::new(function_set));
+}
+
+/// Checks that every function without inputs is a constant function, as the evaluators rely on
+/// `FunctionTrait::execute_constant_into` for them.
+pub fn check_constant_functions<T>(function_set: &[Box<dyn FunctionTrait<T>>]) -> Result<(), String> {
+    for function in function_set.iter().filter(|function| function.get_number_inputs_needed() == 0) {
+        function.execute_constant_into(0., 0, &mut Vec::new())?;
+    }
+    return Ok(());
+}
@@ -0,0 +1,300 @@
+//! Function set of `f64` regression problems; the functions of `regression_function_set` in
+//! double precision. Unlike there, `sin` and `cos` compute the sine and the cosine.
+
+use std::sync::Arc;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::dual::{self, Dual};
+use crate::utils::interval::{self, Interval};
+use float_eq::float_eq;
+
+pub fn get_f64_function_set() -> Arc<Vec<Box<dyn FunctionTrait<f64>>>> {
+    let function_set: Vec<Box<dyn FunctionTrait<f64>>> = vec![
+        Box::new(F64Add),
+        Box::new(F64Sub),
+        Box::new(F64Mul),
+        Box::new(F64Div),
+        Box::new(F64Sin),
+        Box::new(F64Cos),
+        Box::new(F64Ln),
+        Box::new(F64Exp),
+    ];
+
+    return Arc::new(function_set);
+}
+
+/// Division that returns 1 for a divisor close to 0.
+pub fn protected_div(a: f64, b: f64) -> f64 {
+    if float_eq!(b, 0.0, abs <= 0.000_1) {
+        return 1.;
+    }
+    return a / b;
+}
+
+/// Logarithm of the absolute value that returns 1 for an argument close to 0.
+pub fn protected_ln(x: f64) -> f64 {
+    if float_eq!(x, 0.0, abs <= 0.000_1) {
+        return 1.;
+    }
+    return x.abs().ln();
+}
+
+pub struct F64Add;
+
+pub struct F64Sub;
+
+pub struct F64Mul;
+
+pub struct F64Div;
+
+pub struct F64Sin;
+
+pub struct F64Cos;
+
+pub struct F64Ln;
+
+pub struct F64Exp;
+
+
+impl FunctionTrait<f64> for F64Add {
+
+    fn get_name(&self) -> &'static str {
+        return "add";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<f64>], output: &mut Vec<f64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| *a + *b));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .zip(inputs[1].iter())
+            .map(|(a, b)| a + b));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0] + inputs[1]);
+    }
+}
+
+impl FunctionTrait<f64> for F64Sub {
+
+    fn get_name(&self) -> &'static str {
+        return "sub";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<f64>], output: &mut Vec<f64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| *a - *b));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .zip(inputs[1].iter())
+            .map(|(a, b)| a - b));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0] - inputs[1]);
+    }
+}
+
+impl FunctionTrait<f64> for F64Mul {
+
+    fn get_name(&self) -> &'static str {
+        return "mul";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<f64>], output: &mut Vec<f64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| *a * *b));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .zip(inputs[1].iter())
+            .map(|(a, b)| a * b));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0] * inputs[1]);
+    }
+}
+
+impl FunctionTrait<f64> for F64Div {
+
+    fn get_name(&self) -> &'static str {
+        return "div";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<f64>], output: &mut Vec<f64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| protected_div(*a, *b)));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .zip(inputs[1].iter())
+            .map(|(a, b)| dual::protected_div(a, b)));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(interval::protected_div(inputs[0], inputs[1]));
+    }
+}
+
+impl FunctionTrait<f64> for F64Sin {
+
+    fn get_name(&self) -> &'static str {
+        return "sin";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 1;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<f64>], output: &mut Vec<f64>) {
+        let input0 = inputs[0];
+        output.extend(input0
+            .iter()
+            .map(|x| x.sin()));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .map(|x| x.sin()));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0].sin());
+    }
+}
+
+impl FunctionTrait<f64> for F64Cos {
+
+    fn get_name(&self) -> &'static str {
+        return "cos";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 1;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<f64>], output: &mut Vec<f64>) {
+        let input0 = inputs[0];
+        output.extend(input0
+            .iter()
+            .map(|x| x.cos()));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .map(|x| x.cos()));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0].cos());
+    }
+}
+
+impl FunctionTrait<f64> for F64Ln {
+
+    fn get_name(&self) -> &'static str {
+        return "ln";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 1;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<f64>], output: &mut Vec<f64>) {
+        let input0 = inputs[0];
+        output.extend(input0
+            .iter()
+            .map(|x| protected_ln(*x)));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .map(dual::protected_ln));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(interval::protected_ln(inputs[0]));
+    }
+}
+
+impl FunctionTrait<f64> for F64Exp {
+
+    fn get_name(&self) -> &'static str {
+        return "exp";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 1;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<f64>], output: &mut Vec<f64>) {
+        let input0 = inputs[0];
+        output.extend(input0
+            .iter()
+            .map(|x| x.exp()));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .map(|x| x.exp()));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0].exp());
+    }
+}
@@ -1,5 +1,40 @@
-pub trait FunctionTrait<T> {
+use crate::utils::dual::Dual;
+use crate::utils::interval::Interval;
+
+// Send + Sync, so chromosomes can be evaluated in parallel
+pub trait FunctionTrait<T>: Send + Sync {
+    // unique within a function set; used in genome files
+    fn get_name(&self) -> &'static str;
     fn get_number_inputs_needed(&self) -> usize;
-    fn execute_function(&self, inputs: &[&Vec<T>])  -> Vec<T>;
+    // appends the result to `output`, so the evaluation can reuse its buffers
+    fn execute_function_into(&self, inputs: &[&Vec<T>], output: &mut Vec<T>);
+
+    fn execute_function(&self, inputs: &[&Vec<T>]) -> Vec<T> {
+        let mut output = Vec::with_capacity(inputs.first().map_or(0, |input| input.len()));
+        self.execute_function_into(inputs, &mut output);
+        return output;
+    }
+
+    // functions of 0 inputs append the constant gene of their node to `output` for each of the
+    // `nbr_rows` rows instead, see `constant::Constant`; all other functions return an error
+    #[allow(unused_variables)]
+    fn execute_constant_into(&self, constant: f64, nbr_rows: usize, output: &mut Vec<T>) -> Result<(), String> {
+        return Err(format!("{} is not a constant function", self.get_name()));
+    }
+
+    // evaluates the function on dual numbers, which carry the derivatives with respect to the
+    // constants of a chromosome; see `constant_tuning`. Only the functions of the `f32` and `f64`
+    // function sets support it, all other functions return an error.
+    #[allow(unused_variables)]
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        return Err(format!("{} cannot be evaluated on dual numbers", self.get_name()));
+    }
 
-}
\ No newline at end of file
+    // range of the function for arguments from `inputs`; see `interval_analysis`. Only the
+    // functions of the `f32` and `f64` function sets support it, all other functions return an
+    // error.
+    #[allow(unused_variables)]
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Err(format!("{} cannot be evaluated on intervals", self.get_name()));
+    }
+}
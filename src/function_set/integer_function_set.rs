@@ -0,0 +1,211 @@
+//! Function set of `i64` problems. The arithmetic wraps around on overflow, so every function is
+//! defined for all inputs.
+
+use std::sync::Arc;
+use crate::function_set::function_trait::FunctionTrait;
+
+pub fn get_integer_function_set() -> Arc<Vec<Box<dyn FunctionTrait<i64>>>> {
+    let function_set: Vec<Box<dyn FunctionTrait<i64>>> = vec![
+        Box::new(IntegerAdd),
+        Box::new(IntegerSub),
+        Box::new(IntegerMul),
+        Box::new(IntegerDiv),
+        Box::new(IntegerMod),
+        Box::new(IntegerNeg),
+        Box::new(IntegerMin),
+        Box::new(IntegerMax),
+    ];
+
+    return Arc::new(function_set);
+}
+
+/// Division, rounded towards 0, that returns 1 for a divisor of 0.
+pub fn protected_div(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        return 1;
+    }
+    return a.wrapping_div(b);
+}
+
+/// Remainder of `protected_div`; 1 for a divisor of 0.
+pub fn protected_mod(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        return 1;
+    }
+    return a.wrapping_rem(b);
+}
+
+pub struct IntegerAdd;
+
+pub struct IntegerSub;
+
+pub struct IntegerMul;
+
+pub struct IntegerDiv;
+
+pub struct IntegerMod;
+
+pub struct IntegerNeg;
+
+pub struct IntegerMin;
+
+pub struct IntegerMax;
+
+
+impl FunctionTrait<i64> for IntegerAdd {
+
+    fn get_name(&self) -> &'static str {
+        return "add";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<i64>], output: &mut Vec<i64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| a.wrapping_add(*b)));
+    }
+}
+
+impl FunctionTrait<i64> for IntegerSub {
+
+    fn get_name(&self) -> &'static str {
+        return "sub";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<i64>], output: &mut Vec<i64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| a.wrapping_sub(*b)));
+    }
+}
+
+impl FunctionTrait<i64> for IntegerMul {
+
+    fn get_name(&self) -> &'static str {
+        return "mul";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<i64>], output: &mut Vec<i64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| a.wrapping_mul(*b)));
+    }
+}
+
+impl FunctionTrait<i64> for IntegerDiv {
+
+    fn get_name(&self) -> &'static str {
+        return "div";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<i64>], output: &mut Vec<i64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| protected_div(*a, *b)));
+    }
+}
+
+impl FunctionTrait<i64> for IntegerMod {
+
+    fn get_name(&self) -> &'static str {
+        return "mod";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<i64>], output: &mut Vec<i64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| protected_mod(*a, *b)));
+    }
+}
+
+impl FunctionTrait<i64> for IntegerNeg {
+
+    fn get_name(&self) -> &'static str {
+        return "neg";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 1;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<i64>], output: &mut Vec<i64>) {
+        let input0 = inputs[0];
+        output.extend(input0
+            .iter()
+            .map(|x| x.wrapping_neg()));
+    }
+}
+
+impl FunctionTrait<i64> for IntegerMin {
+
+    fn get_name(&self) -> &'static str {
+        return "min";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<i64>], output: &mut Vec<i64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| (*a).min(*b)));
+    }
+}
+
+impl FunctionTrait<i64> for IntegerMax {
+
+    fn get_name(&self) -> &'static str {
+        return "max";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<i64>], output: &mut Vec<i64>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| (*a).max(*b)));
+    }
+}
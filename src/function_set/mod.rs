@@ -1,5 +1,9 @@
 pub mod function_trait;
 pub mod boolean_function_set;
+pub mod packed_boolean_function_set;
 pub mod regression_function_set;
+pub mod f64_function_set;
+pub mod integer_function_set;
+pub mod constant;
 
 
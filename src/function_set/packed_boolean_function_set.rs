@@ -0,0 +1,103 @@
+//! The Boolean function set on `PackedBits`; same functions, names and order as
+//! `boolean_function_set`, so genomes of both can be exchanged.
+
+use std::sync::Arc;
+use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::packed_bits::PackedBits;
+
+
+pub fn get_packed_boolean_function_set() -> Arc<Vec<Box<dyn FunctionTrait<PackedBits>>>> {
+    let function_set: Vec<Box<dyn FunctionTrait<PackedBits>>> = vec![
+        Box::new(PackedAND),
+        Box::new(PackedOR),
+        Box::new(PackedNAND),
+        Box::new(PackedNOR),
+    ];
+
+    return Arc::new(function_set);
+}
+
+
+pub struct PackedAND;
+
+pub struct PackedOR;
+
+pub struct PackedNAND;
+
+pub struct PackedNOR;
+
+impl FunctionTrait<PackedBits> for PackedAND {
+    fn get_name(&self) -> &'static str {
+        return "and";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<PackedBits>], output: &mut Vec<PackedBits>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| *a & *b));
+    }
+}
+
+impl FunctionTrait<PackedBits> for PackedOR {
+    fn get_name(&self) -> &'static str {
+        return "or";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<PackedBits>], output: &mut Vec<PackedBits>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| *a | *b));
+    }
+}
+
+impl FunctionTrait<PackedBits> for PackedNAND {
+    fn get_name(&self) -> &'static str {
+        return "nand";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<PackedBits>], output: &mut Vec<PackedBits>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| !(*a & *b)));
+    }
+}
+
+impl FunctionTrait<PackedBits> for PackedNOR {
+    fn get_name(&self) -> &'static str {
+        return "nor";
+    }
+
+    fn get_number_inputs_needed(&self) -> usize {
+        return 2;
+    }
+
+    fn execute_function_into(&self, inputs: &[&Vec<PackedBits>], output: &mut Vec<PackedBits>) {
+        let input0 = inputs[0];
+        let input1 = inputs[1];
+        output.extend(input0
+            .iter()
+            .zip(input1.iter())
+            .map(|(a, b)| !(*a | *b)));
+    }
+}
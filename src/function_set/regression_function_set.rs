@@ -1,20 +1,38 @@
-use std::rc::Rc;
+use std::sync::Arc;
 use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::dual::{self, Dual};
+use crate::utils::interval::{self, Interval};
 use float_eq::float_eq;
 
-pub fn get_regression_function_set() -> Rc<Vec<Box<dyn FunctionTrait<f32>>>> {
-    let mut function_set: Vec<Box<dyn FunctionTrait<f32>>> = Vec::new();
+pub fn get_regression_function_set() -> Arc<Vec<Box<dyn FunctionTrait<f32>>>> {
+    let function_set: Vec<Box<dyn FunctionTrait<f32>>> = vec![
+        Box::new(RegressionAdd),
+        Box::new(RegressionSub),
+        Box::new(RegressionMul),
+        Box::new(RegressionDiv),
+        Box::new(RegressionSin),
+        Box::new(RegressionCos),
+        Box::new(RegressionLn),
+        Box::new(RegressionExp),
+    ];
+
+    return Arc::new(function_set);
+}
 
-    function_set.push(Box::new(RegressionAdd));
-    function_set.push(Box::new(RegressionSub));
-    function_set.push(Box::new(RegressionMul));
-    function_set.push(Box::new(RegressionDiv));
-    function_set.push(Box::new(RegressionSin));
-    function_set.push(Box::new(RegressionCos));
-    function_set.push(Box::new(RegressionLn));
-    function_set.push(Box::new(RegressionExp));
+/// Division that returns 1 for a divisor close to 0.
+pub fn protected_div(a: f32, b: f32) -> f32 {
+    if float_eq!(b, 0.0, abs <= 0.000_1) {
+        return 1.;
+    }
+    return a / b;
+}
 
-    return Rc::new(function_set);
+/// Logarithm of the absolute value that returns 1 for an argument close to 0.
+pub fn protected_ln(x: f32) -> f32 {
+    if float_eq!(x, 0.0, abs <= 0.000_1) {
+        return 1.;
+    }
+    return x.abs().ln();
 }
 
 pub struct RegressionAdd;
@@ -25,6 +43,8 @@ pub struct RegressionMul;
 
 pub struct RegressionDiv;
 
+// a second addition and a sine, as in the function set of the original experiments; the names
+// say what they compute, so genomes and programs do not mix them up with the f64 sin and cos
 pub struct RegressionSin;
 
 pub struct RegressionCos;
@@ -36,142 +56,247 @@ pub struct RegressionExp;
 
 impl FunctionTrait<f32> for RegressionAdd {
 
+    fn get_name(&self) -> &'static str {
+        return "add";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 2;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<f32>]) -> Vec<f32> {
+    fn execute_function_into(&self, inputs: &[&Vec<f32>], output: &mut Vec<f32>) {
         let input0 = inputs[0];
         let input1 = inputs[1];
-        return input0
+        output.extend(input0
             .iter()
             .zip(input1.iter())
-            .map(|(a, b)| *a + *b)
-            .collect();
+            .map(|(a, b)| *a + *b));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .zip(inputs[1].iter())
+            .map(|(a, b)| a + b));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0] + inputs[1]);
     }
 }
 
 impl FunctionTrait<f32> for RegressionSub {
 
+    fn get_name(&self) -> &'static str {
+        return "sub";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 2;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<f32>]) -> Vec<f32> {
+    fn execute_function_into(&self, inputs: &[&Vec<f32>], output: &mut Vec<f32>) {
         let input0 = inputs[0];
         let input1 = inputs[1];
-        return input0
+        output.extend(input0
             .iter()
             .zip(input1.iter())
-            .map(|(a, b)| *a - *b)
-            .collect();
+            .map(|(a, b)| *a - *b));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .zip(inputs[1].iter())
+            .map(|(a, b)| a - b));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0] - inputs[1]);
     }
 }
 
 impl FunctionTrait<f32> for RegressionMul {
 
+    fn get_name(&self) -> &'static str {
+        return "mul";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 2;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<f32>]) -> Vec<f32> {
+    fn execute_function_into(&self, inputs: &[&Vec<f32>], output: &mut Vec<f32>) {
         let input0 = inputs[0];
         let input1 = inputs[1];
-        return input0
+        output.extend(input0
             .iter()
             .zip(input1.iter())
-            .map(|(a, b)| *a * *b)
-            .collect();
+            .map(|(a, b)| *a * *b));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .zip(inputs[1].iter())
+            .map(|(a, b)| a * b));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0] * inputs[1]);
     }
 }
 
 impl FunctionTrait<f32> for RegressionDiv {
 
+    fn get_name(&self) -> &'static str {
+        return "div";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 2;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<f32>]) -> Vec<f32> {
+    fn execute_function_into(&self, inputs: &[&Vec<f32>], output: &mut Vec<f32>) {
         let input0 = inputs[0];
         let input1 = inputs[1];
-        return input0
+        output.extend(input0
             .iter()
             .zip(input1.iter())
-            .map(|(a, b)| {
-                if float_eq!(*b, 0.0, abs <= 0.000_1) {
-                    return 1.;
-                } else {
-                    return a / b;
-                }
-            })
-            .collect();
+            .map(|(a, b)| protected_div(*a, *b)));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .zip(inputs[1].iter())
+            .map(|(a, b)| dual::protected_div(a, b)));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(interval::protected_div(inputs[0], inputs[1]));
     }
 }
 
 impl FunctionTrait<f32> for RegressionSin {
 
+    fn get_name(&self) -> &'static str {
+        return "plus";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 2;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<f32>]) -> Vec<f32> {
+    fn execute_function_into(&self, inputs: &[&Vec<f32>], output: &mut Vec<f32>) {
         let input0 = inputs[0];
         let input1 = inputs[1];
-        return input0
+        output.extend(input0
             .iter()
             .zip(input1.iter())
-            .map(|(a, b)| *a + *b)
-            .collect();
+            .map(|(a, b)| *a + *b));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .zip(inputs[1].iter())
+            .map(|(a, b)| a + b));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0] + inputs[1]);
     }
 }
 
 impl FunctionTrait<f32> for RegressionCos {
 
+    fn get_name(&self) -> &'static str {
+        return "sin";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 1;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<f32>]) -> Vec<f32> {
+    fn execute_function_into(&self, inputs: &[&Vec<f32>], output: &mut Vec<f32>) {
         let input0 = inputs[0];
-        return input0
+        output.extend(input0
             .iter()
-            .map(|x| x.sin())
-            .collect();
+            .map(|x| x.sin()));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .map(|x| x.sin()));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0].sin());
     }
 }
 
 impl FunctionTrait<f32> for RegressionLn {
 
+    fn get_name(&self) -> &'static str {
+        return "ln";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 1;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<f32>]) -> Vec<f32> {
+    fn execute_function_into(&self, inputs: &[&Vec<f32>], output: &mut Vec<f32>) {
         let input0 = inputs[0];
-        return input0
+        output.extend(input0
+            .iter()
+            .map(|x| protected_ln(*x)));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
             .iter()
-            .map(|x| {
-                if float_eq!(*x, 0.0, abs <= 0.000_1) {
-                    return 1.;
-                } else {
-                    return x.abs().ln();
-                }
-            })
-            .collect();
+            .map(dual::protected_ln));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(interval::protected_ln(inputs[0]));
     }
 }
 
 impl FunctionTrait<f32> for RegressionExp {
 
+    fn get_name(&self) -> &'static str {
+        return "exp";
+    }
+
     fn get_number_inputs_needed(&self) -> usize {
         return 1;
     }
 
-    fn execute_function(&self, inputs: &[&Vec<f32>]) -> Vec<f32> {
+    fn execute_function_into(&self, inputs: &[&Vec<f32>], output: &mut Vec<f32>) {
         let input0 = inputs[0];
-        return input0
+        output.extend(input0
             .iter()
-            .map(|x| x.exp())
-            .collect();
+            .map(|x| x.exp()));
+    }
+
+    fn execute_dual_into(&self, inputs: &[&Vec<Dual>], output: &mut Vec<Dual>) -> Result<(), String> {
+        output.extend(inputs[0]
+            .iter()
+            .map(|x| x.exp()));
+        return Ok(());
+    }
+
+    fn execute_interval(&self, inputs: &[Interval]) -> Result<Interval, String> {
+        return Ok(inputs[0].exp());
     }
 }
@@ -1,44 +1,108 @@
 use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
 use crate::components::cgp_components::cgp_types::CGPType;
 use crate::components::evo_operators_for_population::crossover_operators::crossover_types::CrossoverType;
 
-#[derive(Clone)]
+/// Fields marked with `#[serde(default)]` may be left out of an experiment file.
+/// `nbr_inputs`, `nbr_outputs` and `number_functions` are derived from the dataset and the
+/// function set if they are left out (i.e. 0).
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CgpParameters {
     pub cgp_type: CGPType,
     pub graph_width: usize,
     pub elitists: usize,
     pub population_size: usize,  // total pop-number: #elitsts + pop-size
+    #[serde(default)]
     pub eval_after_iterations: usize,
+    #[serde(default)]
     pub nbr_inputs: usize,
+    #[serde(default)]
     pub nbr_outputs: usize,
+    #[serde(default)]
     pub mutation_rate: f32,
+    #[serde(default = "default_crossover_type")]
     pub crossover_type: CrossoverType,
+    #[serde(default)]
     pub crossover_rate: f32,
+    #[serde(default)]
     pub multi_point_n: usize,
+    #[serde(default)]
     pub tournament_size: usize,
+    #[serde(default)]
     pub number_functions: usize,
     pub fitness_threshold: f32,
+    #[serde(default)]
     pub multi_n_number_mutations: usize,
+    #[serde(default)]
     pub split_mutation_rate_active: f32,
+    #[serde(default)]
     pub split_mutation_rate_inactive: f32,
+    // evolvable constants of the computational nodes; `None` for function sets without a
+    // constant function, see `ConstantParameters`
+    #[serde(default)]
+    pub constants: Option<ConstantParameters>,
 }
 
+/// Constant genes of the computational nodes. A node whose function has no inputs (i.e.
+/// `function_set::constant::Constant`) outputs its constant gene instead of combining its
+/// connections. The genes are drawn uniformly from `[min, max]` and mutated by adding a normally
+/// distributed value with standard deviation `sigma`; they may leave the range by mutation.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConstantParameters {
+    pub min: f64,
+    pub max: f64,
+    pub sigma: f64,
+}
+
+fn default_crossover_type() -> CrossoverType {
+    CrossoverType::NoCrossover
+}
+
+#[cfg(test)]
+impl CgpParameters {
+    /// Standard CGP with a (1 + 4)-ES, no mutation and no crossover; tests override the other
+    /// fields with struct update syntax.
+    pub fn for_tests(graph_width: usize, nbr_inputs: usize, nbr_outputs: usize, number_functions: usize) -> Self {
+        return CgpParameters {
+            cgp_type: CGPType::Standard,
+            graph_width,
+            elitists: 1,
+            population_size: 4,
+            eval_after_iterations: 0,
+            nbr_inputs,
+            nbr_outputs,
+            mutation_rate: 0.,
+            crossover_type: CrossoverType::NoCrossover,
+            crossover_rate: 0.,
+            multi_point_n: 0,
+            tournament_size: 0,
+            number_functions,
+            fitness_threshold: 0.,
+            multi_n_number_mutations: 0,
+            split_mutation_rate_active: 0.,
+            split_mutation_rate_inactive: 0.,
+            constants: None,
+        };
+    }
+}
 
 impl Display for CgpParameters {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "############ Parameters ############\n")?;
-        write!(f, "CGP Type: {}\n", self.cgp_type)?;
-        write!(f, "graph_width: {}\n", self.graph_width)?;
-        write!(f, "mu: {}\n", self.elitists)?;
-        write!(f, "lambda: {}\n", self.population_size)?;
-        write!(f, "eval_after_iterations: {}\n", self.eval_after_iterations)?;
-        write!(f, "nbr_inputs: {}\n", self.nbr_inputs)?;
-        write!(f, "nbr_outputs: {}\n", self.nbr_outputs)?;
-        write!(f, "mutation_rate: {}\n", self.mutation_rate)?;
-        write!(f, "crossover_type: {}\n", self.crossover_type)?;
-        write!(f, "crossover_rate: {}\n", self.crossover_rate)?;
-        write!(f, "multi_point_n: {}\n", self.multi_point_n)?;
-        write!(f, "fitness_threshold: {}\n", self.fitness_threshold)?;
-        write!(f, "#########################\n")
+        writeln!(f, "############ Parameters ############")?;
+        writeln!(f, "CGP Type: {}", self.cgp_type)?;
+        writeln!(f, "graph_width: {}", self.graph_width)?;
+        writeln!(f, "mu: {}", self.elitists)?;
+        writeln!(f, "lambda: {}", self.population_size)?;
+        writeln!(f, "eval_after_iterations: {}", self.eval_after_iterations)?;
+        writeln!(f, "nbr_inputs: {}", self.nbr_inputs)?;
+        writeln!(f, "nbr_outputs: {}", self.nbr_outputs)?;
+        writeln!(f, "mutation_rate: {}", self.mutation_rate)?;
+        writeln!(f, "crossover_type: {}", self.crossover_type)?;
+        writeln!(f, "crossover_rate: {}", self.crossover_rate)?;
+        writeln!(f, "multi_point_n: {}", self.multi_point_n)?;
+        writeln!(f, "fitness_threshold: {}", self.fitness_threshold)?;
+        writeln!(f, "#########################")
     }
 }
\ No newline at end of file
@@ -1,8 +1,11 @@
+// explicit `return` is the style of this crate
+#![allow(clippy::needless_return)]
 pub mod function_set;
 pub mod datasets;
 pub mod utils;
 
 pub mod global_params;
 pub mod components;
+pub mod experiment;
 
 
@@ -1,21 +1,34 @@
+// explicit `return` is the style of this crate
+#![allow(clippy::needless_return)]
 #![allow(dead_code)]
 #![allow(unused_mut)]
 #![allow(unused_imports)]
 #![allow(unused_variables)]
 use std::fs;
-use std::fs::File;
-use std::path::Path;
-use std::rc::Rc;
-use clap::Parser;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use clap::{Parser, Subcommand};
+use num::Float;
+use rand::SeedableRng;
+use rand::distributions::uniform::SampleUniform;
+use rand_chacha::ChaCha8Rng;
 use std::io::{BufWriter, Write};
 use cgp_master::function_set::boolean_function_set;
+use cgp_master::function_set::packed_boolean_function_set;
 use cgp_master::function_set::regression_function_set;
+use cgp_master::function_set::f64_function_set;
+use cgp_master::function_set::integer_function_set;
+use cgp_master::function_set::constant;
 use cgp_master::components::cgp_components::cgp_node::CGPNode;
 use cgp_master::components::cgp_components::cgp_node_mutation_operators::*;
-use cgp_master::global_params::CgpParameters;
+use cgp_master::global_params::{CgpParameters, ConstantParameters};
 use cgp_master::components::cgp_components::cgp_node_types::NodeType;
 use cgp_master::components::cgp_components::cgp_types::CGPType;
+use cgp_master::components::cgp_components::chromosome::Chromosome;
 use cgp_master::components::cgp_components::chromosome_evaluator_operators::*;
+use cgp_master::components::cgp_components::program::ProgramValue;
 use cgp_master::components::cgp_components::chromosome_find_active_node_operators::*;
 use cgp_master::components::cgp_components::chromosome_mutation_operators::*;
 use cgp_master::components::cgp_components::chromosome_reorder_operators::*;
@@ -28,6 +41,7 @@ use cgp_master::components::evo_operators_for_population::evaluation_operators::
 use cgp_master::components::evo_operators_for_population::evaluation_operators::eval_population_trait::{GeneralForwardPassPopulationTrait};
 use cgp_master::components::evo_operators_for_population::general_operators::clone_parent_to_child::{CloneParentToChild, ClonePopulationTrait};
 use cgp_master::components::evo_operators_for_population::general_operators::reorder_population::{GeneralReorderPopulationTrait, ReorderPopulation};
+use cgp_master::components::evo_operators_for_population::general_operators::tune_constants::{GeneralTuneConstantsTrait, TuneBestConstants};
 use cgp_master::components::evo_operators_for_population::mutation_operators::mutate_population::EAMutateStandard;
 use cgp_master::components::evo_operators_for_population::selection_operators::elitist_selection_oneplusfour::EAElitistSelectionOnePlusFour;
 use cgp_master::components::evo_operators_for_population::mutation_operators::mutation_trait::GeneralMutatePopulationTrait;
@@ -35,17 +49,50 @@ use cgp_master::components::evo_operators_for_population::selection_operators::e
 use cgp_master::components::evo_operators_for_population::selection_operators::elitist_selection_tournament::ElitistSelectionWithTournament;
 use cgp_master::components::evo_operators_for_population::selection_operators::selection_trait::GeneralSelectionTrait;
 
-use cgp_master::utils::runner::Runner;
+use cgp_master::utils::checkpoint;
+use cgp_master::utils::fitness_metrics::{BooleanMetric, RegressionMetric};
+use cgp_master::utils::fitness_trait::FitnessFunction;
+use cgp_master::utils::linear_scaling;
+use cgp_master::utils::linear_scaling::LinearScalingMetric;
+use cgp_master::utils::checkpoint::Checkpoint;
+use cgp_master::utils::observer::{EvolutionObserver, Observers};
+use cgp_master::utils::observer_sinks;
+use cgp_master::utils::observer_sinks::{IterationFileWriter, MutationFileWriter};
+use cgp_master::utils::runner::{get_runner_parent, Evolution, RunResult, Runner, StoppingCriterion};
+use cgp_master::utils::numeric::Numeric;
+use cgp_master::utils::packed_bits::pack_dataset;
 use cgp_master::utils::utility_funcs;
 use cgp_master::datasets::boolean_datasets;
 use cgp_master::datasets::regression_benchmarks;
+use cgp_master::datasets::integer_benchmarks;
 
 use cgp_master::utils::txt_writer::*;
 use cgp_master::function_set::function_trait::FunctionTrait;
+use cgp_master::components::operator_registry::{reorder_operator_for_cgp_type, EvolutionPipeline};
+use cgp_master::components::cgp_components::interval_analysis;
+use cgp_master::components::cgp_components::interval_analysis::IntervalCheckedMetric;
+use cgp_master::utils::interval::Interval;
+use cgp_master::experiment::analysis;
+use cgp_master::experiment::analysis::Measure;
+use cgp_master::experiment::batch::{BatchConfig, RunSummary};
+use cgp_master::experiment::experiment_config::{ConstantTuningConfig, DatasetType, ExperimentConfig, IntervalAnalysisConfig, OperatorConfig};
 
 #[derive(Parser)]
 #[clap(author, version, about, name = "testname")]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Experiment file (TOML). If given, all other arguments are ignored.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Checkpoint of an interrupted run; the run continues where it stopped. All other
+    /// arguments are ignored. Must be started from the same directory as the original run.
+    #[arg(long)]
+    resume: Option<PathBuf>,
+
     #[arg(long, default_value_t = 1)]
     run_id: usize,
 
@@ -55,6 +102,7 @@ struct Args {
     #[arg(long, default_value_t = 500)]
     nbr_nodes: usize,
 
+    // bool, f32, f64 or i64
     #[arg(long, default_value = "f32")]
     dataset_type: String,
 
@@ -77,219 +125,635 @@ struct Args {
     // - spilt
     #[arg(long, default_value = "single")]
     mutation_type: String,
+
+    // Variant name of `CGPType`, i.e. "Standard", "DAG", "EReorder"
+    #[arg(long, default_value = "Standard")]
+    cgp_type: CGPType,
+
+    #[arg(long, default_value_t = 1)]
+    elitists: usize,
+
+    #[arg(long, default_value_t = 4)]
+    population_size: usize,
+
+    // see `operator_registry` for allowed values
+    #[arg(long, default_value = "one_plus_four")]
+    evaluation: String,
+
+    #[arg(long, default_value = "one_plus_four")]
+    selection: String,
+
+    // comma separated secondary objectives of the "nsga2" selection, i.e. "active_nodes,depth"
+    #[arg(long, value_delimiter = ',')]
+    objectives: Option<Vec<String>>,
+
+    #[arg(long, default_value = "none")]
+    crossover: String,
+
+    // Variant name of `CrossoverType`, i.e. "NoCrossover", "UniformCrossover"
+    #[arg(long, default_value = "NoCrossover")]
+    crossover_type: CrossoverType,
+
+    #[arg(long, default_value_t = 0.0)]
+    crossover_rate: f32,
+
+    #[arg(long, default_value_t = 0)]
+    multi_point_n: usize,
+
+    #[arg(long, default_value_t = 0)]
+    tournament_size: usize,
+
+    // Seed of the run; a random seed is drawn and saved in the config file if left out
+    #[arg(long)]
+    seed: Option<u64>,
+
+    #[arg(long, default_value_t = 500_000)]
+    max_iterations: usize,
+
+    // generations between two checkpoints; 0 only writes a checkpoint when interrupted
+    #[arg(long, default_value_t = 10_000)]
+    checkpoint_interval: usize,
+
+    // optional stopping criteria
+    #[arg(long)]
+    max_fitness_evaluations: Option<usize>,
+
+    #[arg(long)]
+    max_wall_clock_seconds: Option<f32>,
+
+    #[arg(long)]
+    stagnation_generations: Option<usize>,
+
+    // comma separated; see `observer_sinks` for allowed values
+    #[arg(long, value_delimiter = ',', default_value = "stdout")]
+    observers: Vec<String>,
+
+    // entries of the fitness cache; 0 disables the cache
+    #[arg(long, default_value_t = 0)]
+    fitness_cache_size: usize,
+
+    // Boolean datasets only: evaluates 64 rows of a truth table at once
+    #[arg(long)]
+    bit_parallel: bool,
+
+    // threads that evaluate the children of a generation; the results do not depend on it
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+
+    // recomputes only the nodes of a mutant that are downstream of its mutations
+    #[arg(long)]
+    incremental_evaluation: bool,
+
+    // evaluator of the chromosomes: "map", "arena" or "compiled"; the results are the same
+    #[arg(long, default_value = "arena")]
+    evaluator: String,
+
+    // compares the outputs to the labels; see `fitness_metrics` for allowed values. Defaults to
+    // "sae" for regression and "hamming" for Boolean datasets
+    #[arg(long)]
+    fitness_metric: Option<String>,
+
+    // comma separated weight of every output node of the "weighted_hamming" metric
+    #[arg(long, value_delimiter = ',')]
+    fitness_weights: Option<Vec<f64>>,
+
+    // f32 and f64 regression only: fits offset and scale of every output before the metric
+    #[arg(long)]
+    linear_scaling: bool,
+
+    // numeric datasets only: "min,max,sigma" of evolvable constants; adds the "const" function
+    #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+    constants: Option<Vec<f64>>,
+
+    // f32 and f64 regression with --constants only: tunes the constants of the best chromosome
+    // every N generations
+    #[arg(long)]
+    constant_tuning_interval: Option<usize>,
+
+    // "lm" (Levenberg-Marquardt) or "adam"
+    #[arg(long, default_value = "lm")]
+    constant_tuning_optimizer: String,
+
+    #[arg(long, default_value_t = 10)]
+    constant_tuning_steps: usize,
+
+    // Adam only
+    #[arg(long, default_value_t = 0.01)]
+    constant_tuning_learning_rate: f64,
+
+    // f32 and f64 regression only: interval analysis of the programs; "report", "reject" or
+    // "penalize"
+    #[arg(long)]
+    interval_analysis: Option<String>,
+
+    // comma separated "min,max" of every input; defaults to the range of the dataset
+    #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+    input_domain: Option<Vec<f64>>,
+
+    // added to the fitness of the programs with an undefined or unbounded output by "penalize"
+    #[arg(long, default_value_t = 0.0)]
+    interval_penalty: f32,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs every combination of a parameter grid; see `experiment::batch`.
+    Batch {
+        /// Batch file (TOML)
+        file: PathBuf,
+
+        /// Number of runs at the same time; overrides `workers` of the batch file
+        #[arg(long)]
+        workers: Option<usize>,
+    },
+
+    /// Compares the configurations of finished runs; see `experiment::analysis`.
+    Analyze {
+        /// Output directories, i.e. "Experiments_Output_boolean"
+        #[arg(required = true)]
+        directories: Vec<PathBuf>,
+
+        /// Iteration budget of runs whose iteration file does not name the stopping criterion
+        #[arg(long, default_value_t = 500_000)]
+        max_iterations: usize,
+
+        /// Compares the runs by "iterations" or "evaluations" (fitness evaluations) to success
+        #[arg(long, default_value = "iterations")]
+        measure: Measure,
+
+        /// Significance level of the Holm-corrected p-values
+        #[arg(long, default_value_t = 0.05)]
+        alpha: f64,
+
+        /// Writes the report (Markdown) to a file instead of the terminal
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Builds the experiment of the command line arguments, i.e. the paper setup.
+fn experiment_from_args(args: &Args) -> ExperimentConfig {
+    let (dataset_type, output_dir, fitness_threshold) = match args.dataset_type.as_str() {
+        "bool" => (DatasetType::Bool, "Experiments_Output_boolean", 0.0001),
+        "f64" => (DatasetType::F64, "Experiments_Output_regression_f64", 0.01),
+        "i64" => (DatasetType::I64, "Experiments_Output_integer", 0.01),
+        _ => (DatasetType::F32, "Experiments_Output_regression", 0.01),
+    };
+    let graph_operator = if args.cgp_type == CGPType::DAG { "dag" } else { "standard" };
+
+    ExperimentConfig {
+        run_id: args.run_id,
+        seed: args.seed,
+        dataset_type,
+        dataset: args.dataset,
+        max_iterations: args.max_iterations,
+        max_fitness_evaluations: args.max_fitness_evaluations,
+        max_wall_clock_seconds: args.max_wall_clock_seconds,
+        stagnation_generations: args.stagnation_generations,
+        checkpoint_interval: args.checkpoint_interval,
+        observers: args.observers.clone(),
+        fitness_cache_size: args.fitness_cache_size,
+        bit_parallel: args.bit_parallel,
+        threads: args.threads,
+        incremental_evaluation: args.incremental_evaluation,
+        evaluator: args.evaluator.clone(),
+        fitness_metric: args.fitness_metric.clone(),
+        fitness_weights: args.fitness_weights.clone(),
+        linear_scaling: args.linear_scaling,
+        constant_tuning: args.constant_tuning_interval.map(|interval| ConstantTuningConfig {
+            interval,
+            optimizer: args.constant_tuning_optimizer.clone(),
+            steps: args.constant_tuning_steps,
+            learning_rate: args.constant_tuning_learning_rate,
+        }),
+        interval_analysis: args.interval_analysis.as_ref().map(|action| IntervalAnalysisConfig {
+            input_domain: args.input_domain.as_ref().map(|bounds| match bounds.chunks_exact(2).remainder() {
+                [] => bounds.chunks_exact(2).map(|range| [range[0], range[1]]).collect(),
+                _ => exit_with_error(format!("--input-domain needs a min and a max per input, got {} values", bounds.len())),
+            }),
+            action: action.clone(),
+            penalty: args.interval_penalty,
+        }),
+        output_dir: output_dir.to_string(),
+        operators: OperatorConfig {
+            node_mutation: graph_operator.to_string(),
+            active_nodes: graph_operator.to_string(),
+            reorder: reorder_operator_for_cgp_type(&args.cgp_type).to_string(),
+            crossover: args.crossover.clone(),
+            evaluation: args.evaluation.clone(),
+            selection: args.selection.clone(),
+            objectives: args.objectives.clone(),
+            ..OperatorConfig::one_plus_four(&args.mutation_type)
+        },
+        cgp: CgpParameters {
+            cgp_type: args.cgp_type.clone(),
+            graph_width: args.nbr_nodes,
+            elitists: args.elitists,
+            population_size: args.population_size,  // can also be lambda
+            eval_after_iterations: 0,
+            nbr_inputs: 0,
+            nbr_outputs: 0,
+            mutation_rate: args.mutation_rate,
+            crossover_type: args.crossover_type.clone(),
+            crossover_rate: args.crossover_rate,
+            multi_point_n: args.multi_point_n,
+            tournament_size: args.tournament_size,
+            number_functions: 0,
+            fitness_threshold,
+            multi_n_number_mutations: args.mutation_multi_n,
+            split_mutation_rate_active: args.split_mutation_rate_active,
+            split_mutation_rate_inactive: args.split_mutation_rate_inactive,
+            constants: args.constants.as_ref().map(|values| match values[..] {
+                [min, max, sigma] => ConstantParameters { min, max, sigma },
+                _ => exit_with_error(format!("--constants needs min,max,sigma, got {} values", values.len())),
+            }),
+        },
+    }
+}
+
+fn exit_with_error(error: String) -> ! {
+    eprintln!("Invalid experiment configuration: {}", error);
+    process::exit(2);
+}
+
+/// Returns the directory the results of `config` are written to and echoes the experiment there.
+fn prepare_save_path(config: &ExperimentConfig, params: &CgpParameters) -> PathBuf {
+    let save_path = config.output_path();
+
+    fs::create_dir_all(save_path.clone()).unwrap();
+
+    let mut resolved_config = config.clone();
+    resolved_config.cgp = params.clone();
+    resolved_config.save(&save_path.join(format!("run_{}_config.toml", config.run_id)))
+        .expect("cannot write experiment file");
+
+    return save_path;
+}
+
+/// Writes a checkpoint of the run together with the current length of the output files.
+fn save_checkpoint<T>(config: &ExperimentConfig,
+                      progress: &RunResult,
+                      runner: &Runner<T>,
+                      checkpoint_path: &Path,
+                      observers: &mut Observers<T>) {
+    let mut checkpoint = Checkpoint::new(config, progress, runner);
+    checkpoint.output_files = observers.output_files();
+    checkpoint.save(checkpoint_path).expect("cannot write checkpoint");
 }
 
+/// Runs the evolution (or resumes it from `checkpoint`) with the observers of the experiment.
+/// Returns `None` if the run was interrupted; a checkpoint to resume it is written in this case.
+/// Otherwise, the iteration file is returned for further results.
+fn run_evolution<T: Clone>(config: &ExperimentConfig,
+                           evolution: &mut Evolution<T>,
+                           save_path: &Path,
+                           checkpoint: Option<Checkpoint>) -> Option<(RunResult, File)>
+where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T> {
+    let iteration_path = save_path.join(format!("run_{}_iteration.txt", config.run_id));
+    let mutation_path = save_path.join(format!("mutated_nodes_{}.txt", config.run_id));
+    let checkpoint_path = config.checkpoint_path();
+
+    let progress = checkpoint.map(|checkpoint| {
+        checkpoint.truncate_output_files().unwrap_or_else(|e| exit_with_error(e));
+        checkpoint.restore(&mut evolution.runner).unwrap_or_else(|e| exit_with_error(e))
+    });
+
+    // a resumed run continues the output files of the checkpoint
+    let append = progress.is_some();
+    evolution.observers.push(Box::new(IterationFileWriter::create(&iteration_path, append).unwrap_or_else(|e| exit_with_error(e))));
+    evolution.observers.push(Box::new(MutationFileWriter::create(&mutation_path, append).unwrap_or_else(|e| exit_with_error(e))));
+    for name in &config.observers {
+        let observer = observer_sinks::get_observer(name, save_path, config.run_id, append)
+            .unwrap_or_else(|e| exit_with_error(e));
+        evolution.observers.push(observer);
+    }
+
+    let on_generation = |progress: &RunResult, runner: &Runner<T>, observers: &mut Observers<T>| {
+        let i = progress.generations;
+        if config.checkpoint_interval > 0 && i > 0 && i.is_multiple_of(config.checkpoint_interval) {
+            save_checkpoint(config, progress, runner, &checkpoint_path, observers);
+        }
+    };
+    let result = match progress {
+        Some(progress) => evolution.run_from(progress, on_generation),
+        None => evolution.run(on_generation),
+    };
+
+    if result.stop_reason == Some(StoppingCriterion::Interrupted) {
+        save_checkpoint(config, &result, &evolution.runner, &checkpoint_path, &mut evolution.observers);
+        println!("Interrupted at iteration {}; resume with --resume {}", result.generations, checkpoint_path.display());
+        return None;
+    }
 
-fn bioma_bool(args: Args) {
-    let (data, label) = match args.dataset {
+    if let Some(cache) = evolution.pipeline.chromosome_eval_op.cache_statistics() {
+        println!("Fitness cache: {} hits of {} lookups ({:.1}%), {} evictions",
+                 cache.hits, cache.lookups(), 100. * cache.hit_rate(), cache.evictions);
+    }
+
+    // the run is finished; an old checkpoint would only repeat its end
+    if checkpoint_path.exists() {
+        fs::remove_file(&checkpoint_path).expect("cannot remove checkpoint");
+    }
+    let output_file = OpenOptions::new()
+        .append(true)
+        .open(&iteration_path)
+        .expect("cannot open file");
+    return Some((result, output_file));
+}
+
+fn bioma_bool(config: ExperimentConfig, checkpoint: Option<Checkpoint>) {
+    let (data, label) = match config.dataset {
         0 => boolean_datasets::parity::get_dataset(),
         1 => boolean_datasets::encode::get_dataset(),
         2 => boolean_datasets::decode::get_dataset(),
         3 => boolean_datasets::multiply::get_dataset(),
         _ => { panic!("Wrong Dataset Number") }
     };
-    let function_set = boolean_function_set::get_boolean_function_set();
-
-    let fitness_threshold = 0.0001;  // Bool
-
-    let mut params = CgpParameters {
-        cgp_type: CGPType::Standard,
-        graph_width: args.nbr_nodes,
-        elitists: 1,
-        population_size: 4,  // can also be lambda
-        eval_after_iterations: 0,
-        nbr_inputs: data[0].len(),
-        nbr_outputs: label[0].len(),
-        mutation_rate: args.mutation_rate,
-        crossover_type: CrossoverType::NoCrossover,
-        crossover_rate: 0.0,
-        multi_point_n: 0,
-        tournament_size: 0,
-        number_functions: function_set.len(),
-        fitness_threshold,
-        multi_n_number_mutations: args.mutation_multi_n,
-        split_mutation_rate_active: args.split_mutation_rate_active,
-        split_mutation_rate_inactive: args.split_mutation_rate_inactive,
-    };
-
-    let node_mutation_op = Rc::new(NodeMutationStandard::new());
-    let chromosome_active_op = Rc::new(ChromosomeFindActiveNodesStandard::new());
 
-    let chromosome_mutation_op: Rc<Box<dyn ChromosomeMutationTrait>>;
-    if args.mutation_type == "single" {
-        chromosome_mutation_op = Rc::new(ChromosomeMutationSingle::new());
-    } else if args.mutation_type == "point" {
-        chromosome_mutation_op = Rc::new(ChromosomeMutationPoint::new());
-    } else if args.mutation_type == "multi" {
-        chromosome_mutation_op = Rc::new(ChromosomeMutationMultiN::new());
+    if config.bit_parallel {
+        let function_set = packed_boolean_function_set::get_packed_boolean_function_set();
+        run_boolean(config, checkpoint, pack_dataset(&data), pack_dataset(&label), function_set);
     } else {
-        chromosome_mutation_op = Rc::new(ChromosomeMutationSplit::new());
+        let function_set = boolean_function_set::get_boolean_function_set();
+        run_boolean(config, checkpoint, data, label, function_set);
     }
+}
 
-    let chromosome_eval_op = Rc::new(ChromosomeEvaluator::new());
+/// Runs a Boolean experiment on plain (`bool`) or packed (`PackedBits`) truth tables; both give
+/// the same results.
+fn run_boolean<T: ProgramValue + Send + Sync + 'static>(config: ExperimentConfig,
+                                                        checkpoint: Option<Checkpoint>,
+                                                        data: Vec<Vec<T>>,
+                                                        label: Vec<Vec<T>>,
+                                                        function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>)
+where ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      ArenaChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      CompiledChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      IncrementalChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+      BooleanMetric: FitnessFunction<T> {
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed.unwrap());
+
+    let mut params = config.resolve_cgp_parameters(data[0].len(), label[0].len(), function_set.len())
+        .unwrap_or_else(|e| exit_with_error(e));
+    let metric = BooleanMetric::from_name(config.fitness_metric_name(), config.fitness_weights.as_ref(), params.nbr_outputs)
+        .unwrap_or_else(|e| exit_with_error(e));
+    let pipeline = EvolutionPipeline::new(&config.operators, &params, Arc::clone(&function_set), Arc::new(metric))
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_evaluator(&config.evaluator)
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_incremental_evaluation(config.incremental_evaluation)
+        .with_fitness_cache(config.fitness_cache_size)
+        .with_threads(config.threads)
+        .unwrap_or_else(|e| exit_with_error(e));
+
+    let save_path = prepare_save_path(&config, &params);
+
+    let runner = Runner::new(params, data, label, None, None, Arc::clone(&function_set), Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.fitness_function), rng);
+    let mut evolution = Evolution::new(runner, pipeline, config.stopping_criteria());
+
+    let Some((result, _)) = run_evolution(&config, &mut evolution, &save_path, checkpoint) else {
+        return;
+    };
+    let fitness_cache = evolution.pipeline.chromosome_eval_op.cache_statistics();
+
+    active_nodes_writer(&mut evolution.runner, &save_path, config.run_id, Arc::clone(&evolution.pipeline.chromosome_active_op), Arc::clone(&function_set));
+    best_chromosome_writer(&evolution.runner, &save_path, config.run_id, Arc::clone(&function_set));
+    best_program_writer(&evolution.runner, &save_path, config.run_id, Arc::clone(&evolution.pipeline.chromosome_active_op), Arc::clone(&function_set));
+    if config.operators.selection == "nsga2" {
+        let objectives = config.operators.objectives().unwrap_or_else(|e| exit_with_error(e));
+        pareto_front_writer(&evolution.runner, &save_path, config.run_id, &objectives, Arc::clone(&evolution.pipeline.chromosome_active_op), Arc::clone(&function_set));
+    }
 
-    let clone_parent2child = CloneParentToChild::new();
-    // let mutation_operator = EAMutateStandard::new();
-    let  mut mutation_operator = EAMutateStandard::new();
-    let eval_operator = EAForwardPassPopulationOnePlusFour::new();
-    let selection_operator = EAElitistSelectionOnePlusFour::new();
+    // the summary marks the run as finished, so it is written last
+    let mut summary = RunSummary::new(&config, &result, None);
+    summary.fitness_cache = fitness_cache;
+    summary.save(&config.summary_path()).expect("cannot write");
+}
 
-    let mut runner = Runner::new(params, data, label, None, None, Rc::clone(&function_set), Rc::clone(&chromosome_active_op));
+fn bioma_f32(config: ExperimentConfig, checkpoint: Option<Checkpoint>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed.unwrap());
+    let dataset = regression_dataset(config.dataset, &mut rng);
+    let eval_dataset = regression_eval_dataset(config.dataset, &mut rng);
 
-    let save_path = Path::new("")
-        .join("Experiments_Output_boolean")
-        .join(format!("dataset_{}", args.dataset))
-        .join(format!("{}", args.mutation_type))
-        .join(format!("number_nodes_{}_prob_active_{}_inactive_{}_multi_{}_point_{}", args.nbr_nodes, args.split_mutation_rate_active, args.split_mutation_rate_inactive, args.mutation_multi_n, args.mutation_rate));
+    run_regression(config, checkpoint, rng, dataset, eval_dataset, regression_function_set::get_regression_function_set());
+}
 
-    fs::create_dir_all(save_path.clone()).unwrap();
-    let save_file_iteration = format!("run_{}_iteration.txt", args.run_id);
-    let mut output_file = File::create(save_path.join(save_file_iteration))
-        .expect("cannot create file");
-
-    let save_file_iteration = format!("mutated_nodes_{}.txt", args.run_id);
-    let mut mutationfile = BufWriter::new(File::create(save_path.join(save_file_iteration))
-        .expect("cannot create file"));
-
-    let mut iteration_number = 0;
-    for i in 0..500_000 {
-        writeln!(output_file, "Iteration: {iteration_number}, Fitness: {:?}", runner.get_best_fitness()).expect("write not okay??");
-        if i % 500 == 0 {
-            println!("i: {}, fitness: {}", i, runner.get_best_fitness());
-        }
-        iteration_number += 1;
+fn bioma_f64(config: ExperimentConfig, checkpoint: Option<Checkpoint>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed.unwrap());
+    let dataset = regression_dataset(config.dataset, &mut rng);
+    let eval_dataset = regression_eval_dataset(config.dataset, &mut rng);
 
-        clone_parent2child.execute(&mut runner);
-        mutation_operator.execute(&mut runner, Rc::clone(&node_mutation_op), Rc::clone(&chromosome_mutation_op), &mut mutationfile);
-        eval_operator.execute(&mut runner, Rc::clone(&chromosome_eval_op), Rc::clone(&chromosome_active_op), Rc::clone(&function_set));
-        selection_operator.execute(&mut runner);
+    run_regression(config, checkpoint, rng, dataset, eval_dataset, f64_function_set::get_f64_function_set());
+}
 
-        if runner.get_best_fitness() < fitness_threshold {
-            break;
-        }
-    }
-    println!("{}", iteration_number);
+fn bioma_i64(config: ExperimentConfig, checkpoint: Option<Checkpoint>) {
+    let rng = ChaCha8Rng::seed_from_u64(config.seed.unwrap());
+    let (dataset, eval_dataset) = match config.dataset {
+        0 => (integer_benchmarks::koza_1::get_dataset(), integer_benchmarks::koza_1::get_eval_dataset()),
+        1 => (integer_benchmarks::sum_of_squares::get_dataset(), integer_benchmarks::sum_of_squares::get_eval_dataset()),
+        _ => { panic!("Wrong Dataset Number") }
+    };
 
-    write!(output_file, "End at iteration: {}", iteration_number).expect("cannot write");
-    active_nodes_writer(&mut runner, &save_path, args.run_id, Rc::clone(&chromosome_active_op), Rc::clone(&function_set));
+    run_regression(config, checkpoint, rng, dataset, eval_dataset, integer_function_set::get_integer_function_set());
 }
 
-fn bioma_f32(args: Args) {
-    let (data, label) = match args.dataset {
+fn regression_dataset<T: Float + SampleUniform>(dataset: usize, rng: &mut ChaCha8Rng) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+    match dataset {
         0 => regression_benchmarks::keijzer::get_dataset(),
-        1 => regression_benchmarks::koza_3::get_dataset(),
-        2 => regression_benchmarks::nguyen_7::get_dataset(),
+        1 => regression_benchmarks::koza_3::get_dataset(rng),
+        2 => regression_benchmarks::nguyen_7::get_dataset(rng),
         3 => regression_benchmarks::pagie_1::get_dataset(),
+        4 => regression_benchmarks::complex_square::get_dataset(),
         _ => { panic!("Wrong Dataset Number") }
-    };
-    let (eval_data, eval_label) = match args.dataset {
+    }
+}
+
+fn regression_eval_dataset<T: Float + SampleUniform>(dataset: usize, rng: &mut ChaCha8Rng) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+    match dataset {
         0 => regression_benchmarks::keijzer::get_eval_dataset(),
-        1 => regression_benchmarks::koza_3::get_eval_dataset(),
-        2 => regression_benchmarks::nguyen_7::get_eval_dataset(),
+        1 => regression_benchmarks::koza_3::get_eval_dataset(rng),
+        2 => regression_benchmarks::nguyen_7::get_eval_dataset(rng),
         3 => regression_benchmarks::pagie_1::get_eval_dataset(),
+        4 => regression_benchmarks::complex_square::get_eval_dataset(),
         _ => { panic!("Wrong Dataset Number") }
-    };
+    }
+}
 
-    let function_set = regression_function_set::get_regression_function_set();
-
-    let fitness_threshold = 0.01;  // Regression
-
-    let mut params = CgpParameters {
-        cgp_type: CGPType::Standard,
-        graph_width: args.nbr_nodes,
-        elitists: 1,
-        population_size: 4,  // can also be lambda
-        eval_after_iterations: 0,
-        nbr_inputs: data[0].len(),
-        nbr_outputs: label[0].len(),
-        mutation_rate: args.mutation_rate,
-        crossover_type: CrossoverType::NoCrossover,
-        crossover_rate: 0.0,
-        multi_point_n: 0,
-        tournament_size: 0,
-        number_functions: function_set.len(),
-        fitness_threshold,
-        multi_n_number_mutations: args.mutation_multi_n,
-        split_mutation_rate_active: args.split_mutation_rate_active,
-        split_mutation_rate_inactive: args.split_mutation_rate_inactive,
+/// Error of every output node of `chromosome`, after its linear scaling if it has one.
+fn fitness_per_output<T: Numeric>(metric: &RegressionMetric,
+                                  chromosome: &Chromosome,
+                                  inputs: &[Vec<T>],
+                                  labels: &[Vec<T>],
+                                  function_set: &[Box<dyn FunctionTrait<T>>]) -> Vec<f32> {
+    let prediction = predict(chromosome, inputs, function_set);
+    return match &chromosome.linear_scaling {
+        Some(scaling) => metric.fitness_per_output(&linear_scaling::scale_outputs(&prediction, scaling), &linear_scaling::to_f64(labels)),
+        None => metric.fitness_per_output(&prediction, labels),
     };
+}
 
-    let node_mutation_op = Rc::new(NodeMutationStandard::new());
-    let chromosome_active_op = Rc::new(ChromosomeFindActiveNodesStandard::new());
-
-    let chromosome_mutation_op: Rc<Box<dyn ChromosomeMutationTrait>>;
-    if args.mutation_type == "single" {
-        chromosome_mutation_op = Rc::new(ChromosomeMutationSingle::new());
-    } else if args.mutation_type == "point" {
-        chromosome_mutation_op = Rc::new(ChromosomeMutationPoint::new());
-    } else if args.mutation_type == "multi" {
-        chromosome_mutation_op = Rc::new(ChromosomeMutationMultiN::new());
+/// Runs a regression experiment on any numeric domain; the best chromosome is evaluated on the
+/// eval dataset at the end.
+fn run_regression<T: Numeric + ProgramValue>(config: ExperimentConfig,
+                              checkpoint: Option<Checkpoint>,
+                              rng: ChaCha8Rng,
+                              (data, label): (Vec<Vec<T>>, Vec<Vec<T>>),
+                              (eval_data, eval_label): (Vec<Vec<T>>, Vec<Vec<T>>),
+                              function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>) {
+    let function_set = if config.cgp.constants.is_some() { constant::with_constant(function_set).unwrap_or_else(|e| exit_with_error(e)) } else { function_set };
+    // regression labels hold one vector per output node
+    let mut params = config.resolve_cgp_parameters(data[0].len(), label.len(), function_set.len())
+        .unwrap_or_else(|e| exit_with_error(e));
+    let metric = RegressionMetric::from_name(config.fitness_metric_name()).unwrap_or_else(|e| exit_with_error(e));
+    let input_domain = config.interval_analysis.as_ref().map(|analysis| match &analysis.input_domain {
+        Some(ranges) if ranges.len() != params.nbr_inputs => exit_with_error(format!(
+            "interval_analysis.input_domain needs one range per input ({}), got {}", params.nbr_inputs, ranges.len())),
+        Some(ranges) => ranges.iter().map(|[min, max]| Interval::new(*min, *max)).collect(),
+        None => interval_analysis::input_domain_of(&[&utility_funcs::transpose(data.clone()), &utility_funcs::transpose(eval_data.clone())]),
+    });
+    // programs with an undefined or unbounded output are penalized on every dataset
+    let interval_checked = |metric: Arc<dyn FitnessFunction<T>>| -> Arc<dyn FitnessFunction<T>> {
+        match (&input_domain, config.interval_analysis.as_ref().and_then(|analysis| analysis.penalty())) {
+            (Some(input_domain), Some(penalty)) => Arc::new(IntervalCheckedMetric::new(metric, Arc::clone(&function_set), input_domain.clone(), penalty)),
+            _ => metric,
+        }
+    };
+    let fitness_function: Arc<dyn FitnessFunction<T>> = if config.linear_scaling {
+        interval_checked(Arc::new(LinearScalingMetric::fitted(metric.clone())))
     } else {
-        chromosome_mutation_op = Rc::new(ChromosomeMutationSplit::new());
-    }
-
-    let chromosome_eval_op = Rc::new(ChromosomeEvaluator::new());
-
-    let clone_parent2child = CloneParentToChild::new();
-    let mut mutation_operator = EAMutateStandard::new();
-
-    let eval_operator = EAForwardPassPopulationOnePlusFour::new();
-    let selection_operator = EAElitistSelectionOnePlusFour::new();
+        interval_checked(Arc::new(metric.clone()))
+    };
+    let pipeline = EvolutionPipeline::new(&config.operators, &params, Arc::clone(&function_set), fitness_function)
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_evaluator(&config.evaluator)
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_incremental_evaluation(config.incremental_evaluation)
+        .with_fitness_cache(config.fitness_cache_size)
+        .with_threads(config.threads)
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_constant_tuning(config.constant_tuning.as_ref().map(|tuning| -> Box<dyn GeneralTuneConstantsTrait<T>> {
+            Box::new(TuneBestConstants::new(tuning.interval, tuning.optimizer().unwrap_or_else(|e| exit_with_error(e)), tuning.steps))
+        }));
+
+    let save_path = prepare_save_path(&config, &params);
+
+    let runner = Runner::new(params, data, label, Some(eval_data), Some(eval_label), Arc::clone(&function_set), Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.fitness_function), rng);
+    let mut evolution = Evolution::new(runner, pipeline, config.stopping_criteria());
+
+    let Some((result, mut output_file)) = run_evolution(&config, &mut evolution, &save_path, checkpoint) else {
+        return;
+    };
+    // before the evaluation on the eval dataset, which goes through the cache as well
+    let fitness_cache = evolution.pipeline.chromosome_eval_op.cache_statistics();
+
+    let pipeline = &evolution.pipeline;
+    let test_evaluator = if config.linear_scaling {
+        // the eval data is scaled with the scaling of the training data
+        let fixed: Box<dyn EvaluateChromosomeTrait<T>> = Box::new(ArenaChromosomeEvaluator::with_fitness_function(interval_checked(Arc::new(LinearScalingMetric::fixed(metric.clone())))));
+        Arc::new(fixed)
+    } else {
+        Arc::clone(&pipeline.chromosome_eval_op)
+    };
+    let fitness_train = pipeline.eval_operator.execute_test_set(&mut evolution.runner, test_evaluator, Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&function_set));
 
-    let mut runner = Runner::new(params, data, label, Some(eval_data), Some(eval_label), Rc::clone(&function_set), Rc::clone(&chromosome_active_op));
+    writeln!(output_file, "Fitness Eval: {}", evolution.runner.get_best_fitness()).expect("cannot write");
+    writeln!(output_file, "Fitness Train: {}", fitness_train).expect("cannot write");
 
-    let save_path = Path::new("")
-        .join("Experiments_Output_regression")
-        .join(format!("dataset_{}", args.dataset))
-        .join(format!("{}", args.mutation_type))
-        .join(format!("number_nodes_{}_prob_active_{}_inactive_{}_multi_{}_point_{}", args.nbr_nodes, args.split_mutation_rate_active, args.split_mutation_rate_inactive, args.mutation_multi_n, args.mutation_rate));
+    let runner = &evolution.runner;
+    let mut best = get_runner_parent(runner);
+    evolution.pipeline.chromosome_active_op.execute(&mut best, Arc::clone(&function_set));
+    let best_fitness_per_output = fitness_per_output(&metric, &best, &runner.data, &runner.label, &function_set);
+    let eval_fitness_per_output = fitness_per_output(&metric, &best, runner.eval_data.as_ref().unwrap(), runner.eval_label.as_ref().unwrap(), &function_set);
+    writeln!(output_file, "Best fitness per output: {:?}", best_fitness_per_output).expect("cannot write");
+    writeln!(output_file, "Eval fitness per output: {:?}", eval_fitness_per_output).expect("cannot write");
+    let best_interval_analysis = input_domain.as_ref().map(|input_domain| interval_analysis::analyze(&best, input_domain, &function_set));
+    if let Some(analysis) = &best_interval_analysis {
+        writeln!(output_file, "Interval analysis: {}", analysis).expect("cannot write");
+    }
 
-    fs::create_dir_all(save_path.clone()).unwrap();
-    let save_file_iteration = format!("run_{}_iteration.txt", args.run_id);
-    let mut output_file = File::create(save_path.join(save_file_iteration))
-        .expect("cannot create file");
+    active_nodes_writer(&mut evolution.runner, &save_path, config.run_id, Arc::clone(&evolution.pipeline.chromosome_active_op), Arc::clone(&function_set));
+    best_chromosome_writer(&evolution.runner, &save_path, config.run_id, Arc::clone(&function_set));
+    best_program_writer(&evolution.runner, &save_path, config.run_id, Arc::clone(&evolution.pipeline.chromosome_active_op), Arc::clone(&function_set));
+    if config.operators.selection == "nsga2" {
+        let objectives = config.operators.objectives().unwrap_or_else(|e| exit_with_error(e));
+        pareto_front_writer(&evolution.runner, &save_path, config.run_id, &objectives, Arc::clone(&evolution.pipeline.chromosome_active_op), Arc::clone(&function_set));
+    }
 
-    let save_file_iteration = format!("mutated_nodes_{}.txt", args.run_id);
-    let mut mutationfile = BufWriter::new(File::create(save_path.join(save_file_iteration))
-        .expect("cannot create file"));
+    // the summary marks the run as finished, so it is written last
+    let mut summary = RunSummary::new(&config, &result, Some(fitness_train));
+    summary.fitness_cache = fitness_cache;
+    summary.best_fitness_per_output = Some(best_fitness_per_output);
+    summary.eval_fitness_per_output = Some(eval_fitness_per_output);
+    summary.interval_safe = best_interval_analysis.map(|analysis| analysis.is_safe());
+    summary.save(&config.summary_path()).expect("cannot write");
+}
 
-    let mut iteration_number = 0;
+fn batch(file: &Path, workers: Option<usize>) {
+    let mut batch = BatchConfig::load(file).unwrap_or_else(|e| exit_with_error(e));
+    if let Some(workers) = workers {
+        batch.workers = workers;
+    }
+    let executable = std::env::current_exe().expect("cannot find own executable");
 
-    for _ in 0..500_000 {
-        writeln!(output_file, "Iteration: {iteration_number}, Fitness: {:?}", runner.get_best_fitness()).expect("write not okay??");
+    checkpoint::install_signal_handler().unwrap_or_else(|e| exit_with_error(e));
+    if let Err(error) = batch.run(&executable) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
 
-        if iteration_number % 500 == 0 {
-            println!("i: {}, fitness: {}", iteration_number, runner.get_best_fitness());
-        }
+fn analyze(directories: &[PathBuf], max_iterations: usize, measure: Measure, alpha: f64, output: &Option<PathBuf>) {
+    let (configurations, unfinished) = analysis::collect_configurations(directories, max_iterations, measure)
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        });
+    if unfinished > 0 {
+        eprintln!("Skipped {} unfinished runs", unfinished);
+    }
 
-        iteration_number += 1;
+    let report = analysis::report(&configurations, alpha, measure);
+    match output {
+        Some(path) => fs::write(path, report).expect("cannot write report"),
+        None => print!("{}", report),
+    }
+}
 
-        clone_parent2child.execute(&mut runner);
-        mutation_operator.execute(&mut runner, Rc::clone(&node_mutation_op), Rc::clone(&chromosome_mutation_op), &mut mutationfile);
-        eval_operator.execute(&mut runner, Rc::clone(&chromosome_eval_op), Rc::clone(&chromosome_active_op), Rc::clone(&function_set));
-        selection_operator.execute(&mut runner);
+fn main() {
+    let mut args = Args::parse();
 
-        if runner.get_best_fitness() < fitness_threshold {
-            break;
+    match &args.command {
+        Some(Command::Batch { file, workers }) => return batch(file, *workers),
+        Some(Command::Analyze { directories, max_iterations, measure, alpha, output }) => {
+            return analyze(directories, *max_iterations, *measure, *alpha, output);
         }
+        None => {}
     }
 
-    let fitness_train = eval_operator.execute_test_set(&mut runner, Rc::clone(&chromosome_eval_op), Rc::clone(&chromosome_active_op), Rc::clone(&function_set));
-
-    println!("{}", runner.get_best_fitness());
-    writeln!(output_file, "End at iteration: {}", iteration_number).expect("cannot write");
-    writeln!(output_file, "Fitness Eval: {}", runner.get_best_fitness()).expect("cannot write");
-    writeln!(output_file, "Fitness Train: {}", fitness_train).expect("cannot write");
-
-    active_nodes_writer(&mut runner, &save_path, args.run_id, Rc::clone(&chromosome_active_op), Rc::clone(&function_set));
-}
+    let mut checkpoint: Option<Checkpoint> = None;
+    let mut config = match (&args.resume, &args.config) {
+        (Some(path), _) => {
+            let loaded = Checkpoint::load(path).unwrap_or_else(|e| exit_with_error(e));
+            let config = loaded.experiment_config().unwrap_or_else(|e| exit_with_error(e));
+            checkpoint = Some(loaded);
+            config
+        }
+        (None, Some(path)) => ExperimentConfig::load(path).unwrap_or_else(|e| exit_with_error(e)),
+        (None, None) => {
+            let config = experiment_from_args(&args);
+            config.validate().unwrap_or_else(|e| exit_with_error(e));
+            config
+        }
+    };
+    config.resolve_seed();
+    println!("{}", config);
 
-fn main() {
-    let mut args = Args::parse();
+    checkpoint::install_signal_handler().unwrap_or_else(|e| exit_with_error(e));
 
-    if args.dataset_type == "bool" {
-        bioma_bool(args);
-    } else {
-        bioma_f32(args);
+    match config.dataset_type {
+        DatasetType::Bool => bioma_bool(config, checkpoint),
+        DatasetType::F32 => bioma_f32(config, checkpoint),
+        DatasetType::F64 => bioma_f64(config, checkpoint),
+        DatasetType::I64 => bioma_i64(config, checkpoint),
     }
-}
\ No newline at end of file
+}
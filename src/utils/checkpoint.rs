@@ -0,0 +1,234 @@
+//! Snapshots of a running `Evolution`, so that a run can be resumed after the process died.
+//! A `Checkpoint` contains everything of a `Runner` that changes during a run (population,
+//! fitness values, elitist and child ids, RNG state) plus the progress of the run and the
+//! experiment. The dataset is not saved; it is recreated from the experiment and its seed.
+//! Checkpoints are written with bincode, as the RNG state does not fit into TOML.
+
+use std::fs;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::experiment::experiment_config::ExperimentConfig;
+use crate::utils::runner::{RunResult, Runner};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Catches SIGINT and SIGTERM; afterwards `StoppingCriterion::Interrupted` is met.
+pub fn install_signal_handler() -> Result<(), String> {
+    ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst))
+        .map_err(|e| format!("cannot install signal handler: {}", e))
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    // the experiment as TOML; bincode cannot read optional fields that are skipped when empty
+    pub experiment: String,
+    pub progress: RunResult,
+    pub population: Vec<Chromosome>,
+    pub fitness_vals: Vec<f32>,
+    pub fitness_vals_sorted: Vec<f32>,
+    pub elitist_ids: Vec<usize>,
+    pub child_ids: Vec<usize>,
+    pub tournament_selected: Option<Vec<usize>>,
+    pub rng: ChaCha8Rng,
+    // Output files and their length when the checkpoint was taken. Lines written after the
+    // checkpoint are removed on resume.
+    pub output_files: Vec<(PathBuf, u64)>,
+}
+
+impl Checkpoint {
+    pub fn new<T>(config: &ExperimentConfig, progress: &RunResult, runner: &Runner<T>) -> Self {
+        Self {
+            experiment: config.to_toml(),
+            progress: progress.clone(),
+            population: runner.population.clone(),
+            fitness_vals: runner.fitness_vals.clone(),
+            fitness_vals_sorted: runner.fitness_vals_sorted.clone(),
+            elitist_ids: runner.elitist_ids.clone(),
+            child_ids: runner.child_ids.clone(),
+            tournament_selected: runner.tournament_selected.clone(),
+            rng: runner.rng.clone(),
+            output_files: vec![],
+        }
+    }
+
+    /// Writes to a temporary file first, so a crash while saving keeps the previous checkpoint.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = bincode::serialize(self)
+            .map_err(|e| format!("cannot serialize checkpoint: {}", e))?;
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, content)
+            .map_err(|e| format!("cannot write checkpoint {}: {}", tmp_path.display(), e))?;
+        fs::rename(&tmp_path, path)
+            .map_err(|e| format!("cannot write checkpoint {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read(path)
+            .map_err(|e| format!("cannot read checkpoint {}: {}", path.display(), e))?;
+        bincode::deserialize(&content)
+            .map_err(|e| format!("cannot parse checkpoint {}: {}", path.display(), e))
+    }
+
+    pub fn experiment_config(&self) -> Result<ExperimentConfig, String> {
+        let config: ExperimentConfig = toml::from_str(&self.experiment)
+            .map_err(|e| format!("cannot parse experiment of checkpoint: {}", e))?;
+        config.validate()?;
+        return Ok(config);
+    }
+
+    /// Cuts the output files back to their length at the time of the checkpoint.
+    pub fn truncate_output_files(&self) -> Result<(), String> {
+        for (path, length) in &self.output_files {
+            OpenOptions::new()
+                .write(true)
+                .open(path)
+                .and_then(|file| file.set_len(*length))
+                .map_err(|e| format!("cannot truncate {}: {}", path.display(), e))?;
+        }
+        return Ok(());
+    }
+
+    /// Overwrites the state of a freshly created runner and returns the progress of the run.
+    pub fn restore<T>(self, runner: &mut Runner<T>) -> Result<RunResult, String> {
+        let population_size = runner.params.elitists + runner.params.population_size;
+        if self.population.len() != population_size || self.fitness_vals.len() != population_size {
+            return Err(format!("checkpoint has a population of {}, the experiment needs {}",
+                               self.population.len(), population_size));
+        }
+
+        runner.population = self.population;
+        runner.fitness_vals = self.fitness_vals;
+        runner.fitness_vals_sorted = self.fitness_vals_sorted;
+        runner.elitist_ids = self.elitist_ids;
+        runner.child_ids = self.child_ids;
+        runner.tournament_selected = self.tournament_selected;
+        runner.rng = self.rng;
+
+        return Ok(self.progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::SeedableRng;
+    use super::*;
+    use crate::components::operator_registry::EvolutionPipeline;
+    use crate::datasets::boolean_datasets;
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::utils::fitness_metrics::BooleanMetric;
+    use crate::utils::observer::EvolutionObserver;
+    use crate::utils::observer_sinks::{IterationFileWriter, MutationFileWriter};
+    use crate::utils::runner::{Evolution, StoppingCriterion};
+
+    fn config(population_size: usize) -> ExperimentConfig {
+        let mut config = ExperimentConfig::load(Path::new("experiments/multiply_single.toml")).unwrap();
+        config.cgp.graph_width = 50;
+        config.cgp.population_size = population_size;
+        return config;
+    }
+
+    /// A run of `config` up to `generations` that writes its iteration and mutation files to
+    /// `dir`; `seed` only seeds the initial population.
+    fn evolution(config: &ExperimentConfig, seed: u64, generations: usize, dir: &Path, append: bool) -> Evolution<bool> {
+        let function_set = get_boolean_function_set();
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        let params = config.resolve_cgp_parameters(data[0].len(), label[0].len(), function_set.len()).unwrap();
+        let pipeline = EvolutionPipeline::new(&config.operators, &params, Arc::clone(&function_set), Arc::new(BooleanMetric::Hamming)).unwrap();
+        let runner = Runner::new(params, data, label, None, None, Arc::clone(&function_set), Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.fitness_function), ChaCha8Rng::seed_from_u64(seed));
+
+        let mut evolution = Evolution::new(runner, pipeline, vec![StoppingCriterion::MaxGenerations(generations)]);
+        evolution.observers.push(Box::new(IterationFileWriter::create(&dir.join("iteration.txt"), append).unwrap()));
+        evolution.observers.push(Box::new(MutationFileWriter::create(&dir.join("mutated_nodes.txt"), append).unwrap()));
+        return evolution;
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cgp_checkpoint_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        return dir;
+    }
+
+    #[test]
+    fn resumed_run_matches_uninterrupted_run() {
+        let config = config(4);
+        let uninterrupted_dir = temp_dir("uninterrupted");
+        let resumed_dir = temp_dir("resumed");
+        let checkpoint_path = resumed_dir.join("checkpoint.bin");
+
+        let mut uninterrupted = evolution(&config, 0, 300, &uninterrupted_dir, false);
+        let expected = uninterrupted.run(|_, _, _| {});
+
+        // the process dies 20 generations after the checkpoint of generation 100
+        let mut interrupted = evolution(&config, 0, 120, &resumed_dir, false);
+        interrupted.run(|progress, runner, observers| {
+            if progress.generations == 100 {
+                let mut checkpoint = Checkpoint::new(&config, progress, runner);
+                checkpoint.output_files = observers.output_files();
+                checkpoint.save(&checkpoint_path).unwrap();
+            }
+        });
+        drop(interrupted);
+
+        let checkpoint = Checkpoint::load(&checkpoint_path).unwrap();
+        assert_eq!(config.to_toml(), checkpoint.experiment_config().unwrap().to_toml());
+        checkpoint.truncate_output_files().unwrap();
+        let mut resumed = evolution(&config, 1, 300, &resumed_dir, true);
+        let progress = checkpoint.restore(&mut resumed.runner).unwrap();
+        assert_eq!(100, progress.generations);
+        let result = resumed.run_from(progress, |_, _, _| {});
+
+        assert_eq!(expected.generations, result.generations);
+        assert_eq!(expected.fitness_evaluations, result.fitness_evaluations);
+        assert_eq!(expected.best_fitness, result.best_fitness);
+        assert_eq!(uninterrupted.runner.fitness_vals, resumed.runner.fitness_vals);
+        assert_eq!(bincode::serialize(&uninterrupted.runner.population).unwrap(), bincode::serialize(&resumed.runner.population).unwrap());
+        for file in ["iteration.txt", "mutated_nodes.txt"] {
+            assert_eq!(fs::read(uninterrupted_dir.join(file)).unwrap(), fs::read(resumed_dir.join(file)).unwrap(), "{}", file);
+        }
+
+        fs::remove_dir_all(uninterrupted_dir).unwrap();
+        fs::remove_dir_all(resumed_dir).unwrap();
+    }
+
+    #[test]
+    fn output_files_are_cut_to_their_length_at_the_checkpoint() {
+        let dir = temp_dir("truncate");
+        let path = dir.join("output.txt");
+        fs::write(&path, "0 0.5\n1 0.25\n").unwrap();
+        let config = config(4);
+        let mut evolution = evolution(&config, 0, 0, &dir, false);
+        let progress = evolution.run(|_, _, _| {});
+        let mut checkpoint = Checkpoint::new(&config, &progress, &evolution.runner);
+        checkpoint.output_files = vec![(path.clone(), 6)];
+
+        checkpoint.truncate_output_files().unwrap();
+
+        assert_eq!("0 0.5\n", fs::read_to_string(&path).unwrap());
+        checkpoint.output_files = vec![(dir.join("missing.txt"), 0)];
+        assert!(checkpoint.truncate_output_files().is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn restore_rejects_other_population_size() {
+        let dir = temp_dir("population");
+        let mut small = evolution(&config(4), 0, 0, &dir, false);
+        let mut large = evolution(&config(8), 0, 0, &dir, false);
+        let progress = small.run(|_, _, _| {});
+
+        let error = Checkpoint::new(&config(4), &progress, &small.runner).restore(&mut large.runner).err().unwrap();
+
+        assert_eq!("checkpoint has a population of 5, the experiment needs 9", error);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
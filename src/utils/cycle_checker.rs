@@ -1,6 +1,7 @@
 use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CGPEdges {
     edges: Vec<Vec<usize>>,
 }
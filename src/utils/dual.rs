@@ -0,0 +1,148 @@
+//! Dual numbers for forward-mode automatic differentiation. A `Dual` carries a value and its
+//! partial derivatives with respect to a fixed number of variables, the constants of a
+//! chromosome; see `constant_tuning`. The functions of the `f32` and `f64` function sets are
+//! evaluated on them with `FunctionTrait::execute_dual_into`.
+
+use std::ops::{Add, Mul, Sub};
+use float_eq::float_eq;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dual {
+    pub value: f64,
+    // d value / d variable, one entry per variable
+    pub derivatives: Vec<f64>,
+}
+
+impl Dual {
+    /// A value that does not depend on any of the `nbr_variables` variables, i.e. an input.
+    pub fn constant(value: f64, nbr_variables: usize) -> Self {
+        return Self { value, derivatives: vec![0.; nbr_variables] };
+    }
+
+    /// The variable `index` of `nbr_variables`.
+    pub fn variable(value: f64, index: usize, nbr_variables: usize) -> Self {
+        let mut derivatives = vec![0.; nbr_variables];
+        derivatives[index] = 1.;
+        return Self { value, derivatives };
+    }
+
+    /// `f(self)` for a function with `f(self.value) = value` and `f'(self.value) = derivative`.
+    fn chain(&self, value: f64, derivative: f64) -> Self {
+        return Self { value, derivatives: self.derivatives.iter().map(|d| derivative * d).collect() };
+    }
+
+    pub fn sin(&self) -> Self {
+        return self.chain(self.value.sin(), self.value.cos());
+    }
+
+    pub fn cos(&self) -> Self {
+        return self.chain(self.value.cos(), -self.value.sin());
+    }
+
+    pub fn exp(&self) -> Self {
+        let value = self.value.exp();
+        return self.chain(value, value);
+    }
+}
+
+impl Add for &Dual {
+    type Output = Dual;
+
+    fn add(self, other: &Dual) -> Dual {
+        return Dual {
+            value: self.value + other.value,
+            derivatives: self.derivatives.iter().zip(other.derivatives.iter()).map(|(a, b)| a + b).collect(),
+        };
+    }
+}
+
+impl Sub for &Dual {
+    type Output = Dual;
+
+    fn sub(self, other: &Dual) -> Dual {
+        return Dual {
+            value: self.value - other.value,
+            derivatives: self.derivatives.iter().zip(other.derivatives.iter()).map(|(a, b)| a - b).collect(),
+        };
+    }
+}
+
+impl Mul for &Dual {
+    type Output = Dual;
+
+    // the product rule adds the derivatives
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, other: &Dual) -> Dual {
+        return Dual {
+            value: self.value * other.value,
+            derivatives: self.derivatives.iter()
+                .zip(other.derivatives.iter())
+                .map(|(a, b)| a * other.value + self.value * b)
+                .collect(),
+        };
+    }
+}
+
+/// `protected_div` of the numeric function sets: 1 for a divisor close to 0, which does not
+/// depend on the variables.
+pub fn protected_div(a: &Dual, b: &Dual) -> Dual {
+    if float_eq!(b.value, 0.0, abs <= 0.000_1) {
+        return Dual::constant(1., a.derivatives.len());
+    }
+    // (a / b)' = (a' - (a / b) * b') / b
+    let value = a.value / b.value;
+    return Dual {
+        value,
+        derivatives: a.derivatives.iter()
+            .zip(b.derivatives.iter())
+            .map(|(da, db)| (da - value * db) / b.value)
+            .collect(),
+    };
+}
+
+/// `protected_ln` of the numeric function sets: the logarithm of the absolute value, 1 for an
+/// argument close to 0.
+pub fn protected_ln(x: &Dual) -> Dual {
+    if float_eq!(x.value, 0.0, abs <= 0.000_1) {
+        return Dual::constant(1., x.derivatives.len());
+    }
+    // ln |x|' = 1 / x for both signs of x
+    return x.chain(x.value.abs().ln(), 1. / x.value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::type_complexity)]
+    fn derivatives_match_finite_differences() {
+        let functions: [(&str, fn(&Dual, &Dual) -> Dual); 8] = [
+            ("add", |a, b| a + b),
+            ("sub", |a, b| a - b),
+            ("mul", |a, b| a * b),
+            ("div", protected_div),
+            ("sin", |a, _| a.sin()),
+            ("cos", |a, _| a.cos()),
+            ("ln", |a, _| protected_ln(a)),
+            ("exp", |a, _| a.exp()),
+        ];
+        let h = 1e-6;
+        for (name, function) in functions {
+            for (a, b) in [(0.7, -1.3), (-2.5, 0.4), (1.9, 3.1)] {
+                let result = function(&Dual::variable(a, 0, 2), &Dual::variable(b, 1, 2));
+                let da = (function(&Dual::constant(a + h, 2), &Dual::constant(b, 2)).value
+                    - function(&Dual::constant(a - h, 2), &Dual::constant(b, 2)).value) / (2. * h);
+                let db = (function(&Dual::constant(a, 2), &Dual::constant(b + h, 2)).value
+                    - function(&Dual::constant(a, 2), &Dual::constant(b - h, 2)).value) / (2. * h);
+                assert!((result.derivatives[0] - da).abs() < 1e-6, "d{}/da at ({}, {})", name, a, b);
+                assert!((result.derivatives[1] - db).abs() < 1e-6, "d{}/db at ({}, {})", name, a, b);
+            }
+        }
+
+        // the protected cases are constant
+        let zero = Dual::variable(0., 0, 1);
+        assert_eq!(Dual::constant(1., 1), protected_div(&Dual::variable(2., 0, 1), &zero));
+        assert_eq!(Dual::constant(1., 1), protected_ln(&zero));
+    }
+}
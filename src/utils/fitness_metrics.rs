@@ -1,23 +1,139 @@
-pub fn fitness_regression(prediction: &Vec<Vec<f32>>, labels: &Vec<Vec<f32>>) -> f32 {
-    assert_eq!(prediction.len(), labels.len());
-    let mut fitness: f32 = 0.;
-    prediction.iter().zip(labels.iter()).for_each(|(inner_pred, inner_label)|
-            inner_pred.iter().zip(inner_label.iter()).for_each(|(x, y)| fitness += (x - y).abs())
-    );
+//! Fitness metrics of the regression and the Boolean datasets; see `FitnessFunction`. The
+//! defaults, `RegressionMetric::SumAbsoluteError` and `BooleanMetric::Hamming`, are the fitness
+//! of the paper. The metrics are selected by name, see `REGRESSION_METRICS` and
+//! `BOOLEAN_METRICS`.
+
+use std::slice;
+use crate::utils::fitness_trait::FitnessFunction;
+use crate::utils::numeric::Numeric;
+use crate::utils::packed_bits::PackedBits;
+
+pub const REGRESSION_METRICS: [&str; 7] = ["sae", "mae", "mse", "rmse", "nrmse", "one_minus_r2", "max_error"];
+pub const BOOLEAN_METRICS: [&str; 2] = ["hamming", "weighted_hamming"];
+
+/// Error of a regression model; the mean over the output nodes of the sum of the absolute errors
+/// of each output. Both `prediction` and `labels` hold one vector per output node.
+pub fn fitness_regression<T: Numeric>(prediction: &[Vec<T>], labels: &[Vec<T>]) -> f32 {
+    assert_eq!(prediction.len(), labels.len(), "one label vector per output node is needed");
+    let mut fitness = T::sum_absolute_errors(prediction, labels) as f32;
+
+    fitness /= prediction.len() as f32;
+
+    return finite_or_max(fitness);
+}
+
+/// The metrics of regression problems. Every metric is computed for each output node on its own;
+/// the fitness is the mean over the output nodes, except for `MaxError`. A prediction with a NaN
+/// or infinite value, and a metric that overflows, get the worst fitness, `f32::MAX`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegressionMetric {
+    /// "sae": sum of the absolute errors; see `fitness_regression`.
+    SumAbsoluteError,
+    /// "mae": mean absolute error.
+    MeanAbsoluteError,
+    /// "mse": mean squared error.
+    MeanSquaredError,
+    /// "rmse": root of the mean squared error.
+    RootMeanSquaredError,
+    /// "nrmse": RMSE divided by the standard deviation of the labels, so outputs of different
+    /// scales weigh the same. Constant labels have no deviation; their RMSE is not normalized.
+    NormalizedRootMeanSquaredError,
+    /// "one_minus_r2": 1 - R², i.e. the squared error relative to the one of the mean of the
+    /// labels. Constant labels have a fitness of 0 if they are predicted exactly and `f32::MAX`
+    /// otherwise.
+    OneMinusRSquared,
+    /// "max_error": largest absolute error of any row; the largest of all output nodes.
+    MaxError,
+}
+
+impl RegressionMetric {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "sae" => Ok(Self::SumAbsoluteError),
+            "mae" => Ok(Self::MeanAbsoluteError),
+            "mse" => Ok(Self::MeanSquaredError),
+            "rmse" => Ok(Self::RootMeanSquaredError),
+            "nrmse" => Ok(Self::NormalizedRootMeanSquaredError),
+            "one_minus_r2" => Ok(Self::OneMinusRSquared),
+            "max_error" => Ok(Self::MaxError),
+            _ => Err(format!("unknown regression fitness metric '{}', allowed: {:?}", name, REGRESSION_METRICS)),
+        }
+    }
+
+    /// The fitness of every output node on its own.
+    pub fn fitness_per_output<T: Numeric>(&self, prediction: &[Vec<T>], labels: &[Vec<T>]) -> Vec<f32> {
+        assert_eq!(prediction.len(), labels.len(), "one label vector per output node is needed");
+        return prediction.iter()
+            .zip(labels.iter())
+            .map(|(inner_pred, inner_label)| self.output_fitness(inner_pred, inner_label))
+            .collect();
+    }
+
+    fn output_fitness<T: Numeric>(&self, prediction: &Vec<T>, labels: &Vec<T>) -> f32 {
+        if *self == Self::SumAbsoluteError {
+            return finite_or_max(T::sum_absolute_errors(slice::from_ref(prediction), slice::from_ref(labels)) as f32);
+        }
 
-    fitness = fitness / (prediction.len() as f32);
+        let prediction: Vec<f64> = prediction.iter().map(|x| x.to_f64().unwrap()).collect();
+        if prediction.iter().any(|x| !x.is_finite()) {
+            return f32::MAX;
+        }
+        let labels: Vec<f64> = labels.iter().map(|y| y.to_f64().unwrap()).collect();
+        let nbr_rows = labels.len() as f64;
+        let errors: Vec<f64> = prediction.iter().zip(labels.iter()).map(|(x, y)| x - y).collect();
+        let squared_error: f64 = errors.iter().map(|e| e * e).sum();
+        let mean_label = labels.iter().sum::<f64>() / nbr_rows;
+        let squared_deviation: f64 = labels.iter().map(|y| (y - mean_label) * (y - mean_label)).sum();
 
-    if fitness.is_nan() {
-        fitness = f32::MAX;
-    } else if fitness.is_infinite() {
-        fitness = f32::MAX;
+        let fitness = match self {
+            Self::SumAbsoluteError => unreachable!(),
+            Self::MeanAbsoluteError => errors.iter().map(|e| e.abs()).sum::<f64>() / nbr_rows,
+            Self::MeanSquaredError => squared_error / nbr_rows,
+            Self::RootMeanSquaredError => (squared_error / nbr_rows).sqrt(),
+            Self::NormalizedRootMeanSquaredError => {
+                let rmse = (squared_error / nbr_rows).sqrt();
+                let deviation = (squared_deviation / nbr_rows).sqrt();
+                if deviation == 0. { rmse } else { rmse / deviation }
+            }
+            Self::OneMinusRSquared => {
+                if squared_deviation > 0. {
+                    squared_error / squared_deviation
+                } else if squared_error == 0. {
+                    0.
+                } else {
+                    f64::INFINITY
+                }
+            }
+            Self::MaxError => errors.iter().map(|e| e.abs()).fold(0., f64::max),
+        };
+        return finite_or_max(fitness as f32);
     }
+}
 
+impl<T: Numeric> FitnessFunction<T> for RegressionMetric {
+    fn fitness(&self, prediction: &Vec<Vec<T>>, labels: &Vec<Vec<T>>) -> f32 {
+        match self {
+            // bit-identical to the fitness before the metrics were selectable
+            Self::SumAbsoluteError => fitness_regression(prediction, labels),
+            Self::MaxError => self.fitness_per_output(prediction, labels).into_iter().fold(0., f32::max),
+            _ => {
+                let per_output = self.fitness_per_output(prediction, labels);
+                let mean = per_output.iter().map(|fitness| *fitness as f64).sum::<f64>() / per_output.len() as f64;
+                finite_or_max(mean as f32)
+            }
+        }
+    }
+}
+
+fn finite_or_max(fitness: f32) -> f32 {
+    if !fitness.is_finite() {
+        return f32::MAX;
+    }
     return fitness;
 }
 
 
-pub fn fitness_boolean(prediction: &Vec<Vec<bool>>, labels: &Vec<Vec<bool>>) -> f32 {
+pub fn fitness_boolean(prediction: &[Vec<bool>], labels: &[Vec<bool>]) -> f32 {
     assert_eq!(prediction.len(), labels.len());
 
     let mut fitness: i32 = 0;
@@ -29,4 +145,159 @@ pub fn fitness_boolean(prediction: &Vec<Vec<bool>>, labels: &Vec<Vec<bool>>) ->
 
     let fitness = 1. - (fitness as f32 / number_bits as f32);
     return fitness;
-}
\ No newline at end of file
+}
+
+/// `fitness_boolean` on packed datasets; the Hamming distance is counted 64 rows at a time.
+pub fn fitness_boolean_packed(prediction: &[Vec<PackedBits>], labels: &[Vec<PackedBits>]) -> f32 {
+    assert_eq!(prediction.len(), labels.len());
+
+    let mut distance: u32 = 0;
+    prediction.iter().zip(labels.iter()).for_each(|(inner_pred, inner_label)|
+        inner_pred.iter().zip(inner_label.iter()).for_each(|(x, y)| distance += y.hamming_distance(*x))
+    );
+
+    let number_bits: u32 = labels.iter().flatten().map(|label| label.valid.count_ones()).sum();
+
+    // same arithmetic as `fitness_boolean`, so both give bit-identical results
+    let fitness = (number_bits - distance) as i32;
+    let fitness = 1. - (fitness as f32 / number_bits as f32);
+    return fitness;
+}
+
+/// The metrics of Boolean datasets. Boolean values cannot be NaN or infinite.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BooleanMetric {
+    /// "hamming": fraction of wrong bits; see `fitness_boolean`.
+    Hamming,
+    /// "weighted_hamming": fraction of wrong bits where a wrong bit of output node `i` counts
+    /// `weights[i]` times; 0 to 1 like `Hamming`.
+    WeightedHamming(Vec<f64>),
+}
+
+impl BooleanMetric {
+    /// `weights` are the weights of the output nodes of `"weighted_hamming"`, one per output node.
+    pub fn from_name(name: &str, weights: Option<&Vec<f64>>, nbr_outputs: usize) -> Result<Self, String> {
+        match (name, weights) {
+            ("hamming", None) => Ok(Self::Hamming),
+            ("weighted_hamming", Some(weights)) => {
+                if weights.len() != nbr_outputs {
+                    return Err(format!("weighted_hamming needs one weight per output node, got {} weights for {} outputs", weights.len(), nbr_outputs));
+                }
+                if weights.iter().any(|weight| !weight.is_finite() || *weight < 0.) || weights.iter().sum::<f64>() <= 0. {
+                    return Err(format!("weights must be finite and >= 0, and at least one must be > 0, got {:?}", weights));
+                }
+                Ok(Self::WeightedHamming(weights.clone()))
+            }
+            ("weighted_hamming", None) => Err("weighted_hamming needs the weights of the output nodes".to_string()),
+            ("hamming", Some(_)) => Err("only weighted_hamming takes weights".to_string()),
+            _ => Err(format!("unknown boolean fitness metric '{}', allowed: {:?}", name, BOOLEAN_METRICS)),
+        }
+    }
+
+    // number of wrong bits of every output node
+    fn weighted_fitness(weights: &[f64], wrong_bits: &[u64], number_rows: u64) -> f32 {
+        let weighted: f64 = weights.iter().zip(wrong_bits.iter()).map(|(weight, wrong)| weight * *wrong as f64).sum();
+        return (weighted / (weights.iter().sum::<f64>() * number_rows as f64)) as f32;
+    }
+}
+
+impl FitnessFunction<bool> for BooleanMetric {
+    fn fitness(&self, prediction: &Vec<Vec<bool>>, labels: &Vec<Vec<bool>>) -> f32 {
+        match self {
+            Self::Hamming => fitness_boolean(prediction, labels),
+            Self::WeightedHamming(weights) => {
+                assert_eq!(prediction.len(), labels.len());
+                let mut wrong_bits = vec![0; weights.len()];
+                prediction.iter().zip(labels.iter()).for_each(|(inner_pred, inner_label)|
+                    inner_pred.iter().zip(inner_label.iter()).enumerate().for_each(|(i, (x, y))| { if x != y { wrong_bits[i] += 1 } })
+                );
+                Self::weighted_fitness(weights, &wrong_bits, labels.len() as u64)
+            }
+        }
+    }
+}
+
+/// Counted 64 rows at a time; the same fitness as on the unpacked dataset.
+impl FitnessFunction<PackedBits> for BooleanMetric {
+    fn fitness(&self, prediction: &Vec<Vec<PackedBits>>, labels: &Vec<Vec<PackedBits>>) -> f32 {
+        match self {
+            Self::Hamming => fitness_boolean_packed(prediction, labels),
+            Self::WeightedHamming(weights) => {
+                assert_eq!(prediction.len(), labels.len());
+                let mut wrong_bits = vec![0; weights.len()];
+                prediction.iter().zip(labels.iter()).for_each(|(inner_pred, inner_label)|
+                    inner_pred.iter().zip(inner_label.iter()).enumerate().for_each(|(i, (x, y))| wrong_bits[i] += y.hamming_distance(*x) as u64)
+                );
+                let number_rows: u32 = labels.iter().map(|label| label[0].valid.count_ones()).sum();
+                Self::weighted_fitness(weights, &wrong_bits, number_rows as u64)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::packed_bits::pack_dataset;
+
+    #[test]
+    fn regression_metrics() {
+        let prediction = vec![vec![1., 2., 3., 5.]];
+        let labels = vec![vec![1., 2., 3., 4.]];
+        let fitness = |name: &str| FitnessFunction::<f64>::fitness(&RegressionMetric::from_name(name).unwrap(), &prediction, &labels);
+        assert_eq!(1., fitness("sae"));
+        assert_eq!(0.25, fitness("mae"));
+        assert_eq!(0.25, fitness("mse"));
+        assert_eq!(0.5, fitness("rmse"));
+        // the labels have a deviation of sqrt(1.25) and a squared deviation of 5
+        assert_eq!((0.5 / 1.25f64.sqrt()) as f32, fitness("nrmse"));
+        assert_eq!(0.2, fitness("one_minus_r2"));
+        assert_eq!(1., fitness("max_error"));
+        assert!(RegressionMetric::from_name("hamming").is_err());
+    }
+
+    #[test]
+    fn regression_metrics_of_several_outputs() {
+        let prediction = vec![vec![0, 0], vec![4, 0]];
+        let labels = vec![vec![0, 2], vec![0, 0]];
+        assert_eq!(vec![1., 2.], RegressionMetric::MeanAbsoluteError.fitness_per_output(&prediction, &labels));
+        assert_eq!(1.5, RegressionMetric::MeanAbsoluteError.fitness(&prediction, &labels));
+        assert_eq!(4., RegressionMetric::MaxError.fitness(&prediction, &labels));
+    }
+
+    #[test]
+    fn non_finite_predictions_get_the_worst_fitness() {
+        let labels = vec![vec![1f32, 2.]];
+        for name in REGRESSION_METRICS {
+            let metric = RegressionMetric::from_name(name).unwrap();
+            for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+                assert_eq!(f32::MAX, metric.fitness(&vec![vec![1., value]], &labels), "{}", name);
+            }
+        }
+        // constant labels
+        let labels = vec![vec![3f32, 3.]];
+        assert_eq!(0., RegressionMetric::OneMinusRSquared.fitness(&vec![vec![3., 3.]], &labels));
+        assert_eq!(f32::MAX, RegressionMetric::OneMinusRSquared.fitness(&vec![vec![3., 4.]], &labels));
+        assert_eq!(0.5f32.sqrt(), RegressionMetric::NormalizedRootMeanSquaredError.fitness(&vec![vec![3., 4.]], &labels));
+    }
+
+    #[test]
+    fn weighted_hamming_matches_on_packed_datasets() {
+        // 70 rows of two outputs; the first output is wrong in 7 rows, the second in 35
+        let labels: Vec<Vec<bool>> = (0..70).map(|i| vec![i % 2 == 0, i % 3 == 0]).collect();
+        let prediction: Vec<Vec<bool>> = labels.iter()
+            .enumerate()
+            .map(|(i, label)| vec![label[0] != (i % 10 == 0), label[1] != (i % 2 == 0)])
+            .collect();
+        let metric = BooleanMetric::from_name("weighted_hamming", Some(&vec![3., 1.]), 2).unwrap();
+        let fitness = metric.fitness(&prediction, &labels);
+        assert_eq!(((3. * 7. + 35.) / (4. * 70.)) as f32, fitness);
+        assert_eq!(fitness, metric.fitness(&pack_dataset(&prediction), &pack_dataset(&labels)));
+
+        let equal_weights = BooleanMetric::from_name("weighted_hamming", Some(&vec![1., 1.]), 2).unwrap();
+        assert_eq!(BooleanMetric::Hamming.fitness(&prediction, &labels), equal_weights.fitness(&prediction, &labels));
+        assert!(BooleanMetric::from_name("weighted_hamming", Some(&vec![1.]), 2).is_err());
+        assert!(BooleanMetric::from_name("weighted_hamming", Some(&vec![0., 0.]), 2).is_err());
+        assert!(BooleanMetric::from_name("hamming", Some(&vec![1., 1.]), 2).is_err());
+    }
+}
@@ -1,3 +1,20 @@
-pub trait FitnessFunction<P> {
-    fn fitness_function(prediction: &Vec<P>, labels: &Vec<P>) -> f32;
-}
\ No newline at end of file
+//! A fitness metric compares the outputs of a chromosome to the labels; every evaluator takes one.
+//! See `fitness_metrics` for the metrics of the regression and the Boolean datasets.
+
+use crate::components::cgp_components::chromosome::Chromosome;
+
+/// Send + Sync, so the evaluators that hold a metric can be shared between threads.
+pub trait FitnessFunction<T>: Send + Sync {
+    /// Fitness of `prediction`, lower is better and 0 is a perfect prediction. `prediction` has
+    /// the layout of `labels`: one vector per output node for regression, one vector per row for
+    /// Boolean datasets. Never NaN; every metric defines the fitness of NaN and infinite values.
+    #[allow(clippy::ptr_arg)]
+    fn fitness(&self, prediction: &Vec<Vec<T>>, labels: &Vec<Vec<T>>) -> f32;
+
+    /// Fitness of `prediction`, the outputs of `chromosome`; the evaluators call this one.
+    /// Metrics that fit parameters of a chromosome to the labels keep them in the chromosome, see
+    /// `LinearScalingMetric`.
+    fn chromosome_fitness(&self, _chromosome: &mut Chromosome, prediction: &Vec<Vec<T>>, labels: &Vec<Vec<T>>) -> f32 {
+        return self.fitness(prediction, labels);
+    }
+}
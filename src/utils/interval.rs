@@ -0,0 +1,206 @@
+//! Interval arithmetic for the analysis of evolved programs, see `interval_analysis`. An
+//! `Interval` encloses every value a node can take for inputs from the input domain. The bounds
+//! follow the numeric domain of the program, not the real numbers: an infinite bound means that
+//! the value can overflow, and an interval with NaN bounds is undefined. A value is undefined if
+//! it can be NaN, i.e. `0 * inf`, or if a protected function can be applied close to its
+//! singularity, where the protection replaces the result by 1.
+
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Mul, Sub};
+
+// arguments with an absolute value up to this are protected, see `protected_div` of the function sets
+const PROTECTED: f64 = 0.000_1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Interval {
+    pub const UNDEFINED: Interval = Interval { min: f64::NAN, max: f64::NAN };
+
+    pub fn new(min: f64, max: f64) -> Self {
+        return Self { min, max };
+    }
+
+    pub fn point(value: f64) -> Self {
+        return Self { min: value, max: value };
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        return self.min.is_nan() || self.max.is_nan();
+    }
+
+    /// Defined and without overflow.
+    pub fn is_bounded(&self) -> bool {
+        return self.min.is_finite() && self.max.is_finite();
+    }
+
+    /// Bounds with an absolute value above `max`, the largest value of the numeric domain,
+    /// overflow to infinity.
+    pub fn overflow_above(&self, max: f64) -> Self {
+        let overflow = |x: f64| if x.abs() > max { x.signum() * f64::INFINITY } else { x };
+        return Self { min: overflow(self.min), max: overflow(self.max) };
+    }
+
+    fn contains_zero(&self) -> bool {
+        return self.min <= 0. && self.max >= 0.;
+    }
+
+    /// Whether a protected function replaces some result for an argument of the interval.
+    fn meets_protection(&self) -> bool {
+        return self.min <= PROTECTED && self.max >= -PROTECTED;
+    }
+
+    /// Whether `phase + k * 2π` lies in the interval for some integer `k`.
+    fn contains_phase(&self, phase: f64) -> bool {
+        return phase + ((self.min - phase) / TAU).ceil() * TAU <= self.max;
+    }
+
+    /// Range of a function with period 2π, its maxima at `max_phase` and its minima at
+    /// `max_phase + π`; sin(inf) is NaN.
+    fn periodic(&self, function: fn(f64) -> f64, max_phase: f64) -> Self {
+        if !self.is_bounded() {
+            return Self::UNDEFINED;
+        }
+        if self.max - self.min >= TAU {
+            return Self::new(-1., 1.);
+        }
+        let (a, b) = (function(self.min), function(self.max));
+        let max = if self.contains_phase(max_phase) { 1. } else { a.max(b) };
+        let min = if self.contains_phase(max_phase + PI) { -1. } else { a.min(b) };
+        return Self::new(min, max);
+    }
+
+    pub fn sin(&self) -> Self {
+        return self.periodic(f64::sin, FRAC_PI_2);
+    }
+
+    pub fn cos(&self) -> Self {
+        return self.periodic(f64::cos, 0.);
+    }
+
+    pub fn exp(&self) -> Self {
+        return Self::new(self.min.exp(), self.max.exp());
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_undefined() {
+            return write!(f, "undefined");
+        }
+        write!(f, "[{}, {}]", self.min, self.max)
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, other: Interval) -> Interval {
+        // inf + -inf
+        if (self.max == f64::INFINITY && other.min == f64::NEG_INFINITY) || (self.min == f64::NEG_INFINITY && other.max == f64::INFINITY) {
+            return Interval::UNDEFINED;
+        }
+        return Interval::new(self.min + other.min, self.max + other.max);
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, other: Interval) -> Interval {
+        // inf - inf
+        if (self.max == f64::INFINITY && other.max == f64::INFINITY) || (self.min == f64::NEG_INFINITY && other.min == f64::NEG_INFINITY) {
+            return Interval::UNDEFINED;
+        }
+        return Interval::new(self.min - other.max, self.max - other.min);
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, other: Interval) -> Interval {
+        // 0 * inf
+        if self.is_undefined() || other.is_undefined()
+            || (!self.is_bounded() && other.contains_zero()) || (!other.is_bounded() && self.contains_zero()) {
+            return Interval::UNDEFINED;
+        }
+        let products = [self.min * other.min, self.min * other.max, self.max * other.min, self.max * other.max];
+        return Interval::new(products.iter().copied().fold(f64::INFINITY, f64::min),
+                             products.iter().copied().fold(f64::NEG_INFINITY, f64::max));
+    }
+}
+
+/// `protected_div` of the numeric function sets; undefined if the divisor can be close to 0.
+pub fn protected_div(a: Interval, b: Interval) -> Interval {
+    if b.is_undefined() || b.meets_protection() {
+        return Interval::UNDEFINED;
+    }
+    // b does not contain 0, so 1 / b keeps the order of its bounds
+    return a * Interval::new(1. / b.max, 1. / b.min);
+}
+
+/// `protected_ln` of the numeric function sets, the logarithm of the absolute value; undefined if
+/// the argument can be close to 0.
+pub fn protected_ln(x: Interval) -> Interval {
+    if x.is_undefined() || x.meets_protection() {
+        return Interval::UNDEFINED;
+    }
+    if x.min > 0. {
+        return Interval::new(x.min.ln(), x.max.ln());
+    }
+    return Interval::new((-x.max).ln(), (-x.min).ln());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encloses_sampled_values() {
+        let intervals = [Interval::new(-3., -0.5), Interval::new(-1., 2.), Interval::new(0.2, 7.), Interval::new(4., 4.)];
+        let sample = |x: &Interval| (0..=20).map(|i| x.min + (x.max - x.min) * i as f64 / 20.).collect::<Vec<f64>>();
+        let contains = |x: Interval, value: f64| x.min - 1e-12 <= value && value <= x.max + 1e-12;
+
+        for a in intervals {
+            for x in sample(&a) {
+                assert!(contains(a.sin(), x.sin()) && contains(a.cos(), x.cos()) && contains(a.exp(), x.exp()));
+                if !a.meets_protection() {
+                    assert!(contains(protected_ln(a), x.abs().ln()));
+                }
+                for b in intervals {
+                    for y in sample(&b) {
+                        assert!(contains(a + b, x + y) && contains(a - b, x - y) && contains(a * b, x * y));
+                        if !b.meets_protection() {
+                            assert!(contains(protected_div(a, b), x / y));
+                        }
+                    }
+                }
+            }
+        }
+        // the extrema of sin and cos are found
+        assert_eq!(Interval::new(-1., 1.), Interval::new(1., 5.).sin());
+        assert_eq!(Interval::new(-1., 0.5f64.cos()), Interval::new(0.5, 4.).cos());
+    }
+
+    #[test]
+    fn flags_protection_and_overflow() {
+        assert!(protected_div(Interval::point(1.), Interval::new(-1., 1.)).is_undefined());
+        assert!(protected_div(Interval::point(1.), Interval::new(0.00005, 1.)).is_undefined());
+        assert!(protected_ln(Interval::new(0., 2.)).is_undefined());
+        assert_eq!(Interval::new(0., 2f64.ln()), protected_ln(Interval::new(-2., -1.)));
+
+        let overflow = Interval::new(0., 100.).exp().overflow_above(f32::MAX as f64);
+        assert!(!overflow.is_bounded() && !overflow.is_undefined());
+        assert!(Interval::new(0., 100.).exp().is_bounded());
+        // inf - inf and 0 * inf are NaN, like sin(inf)
+        assert!((overflow - overflow).is_undefined());
+        assert!((overflow * Interval::point(0.)).is_undefined());
+        assert!(overflow.sin().is_undefined());
+        assert!(Interval::UNDEFINED.exp().is_undefined());
+    }
+}
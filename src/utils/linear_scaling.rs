@@ -0,0 +1,182 @@
+//! Linear scaling of regression outputs (Keijzer, 2003). Before the error metric is applied, every
+//! output `y` of a chromosome is replaced by `intercept + slope * y`, with the coefficients of the
+//! least squared error to the labels. The search only has to find the shape of a function, not
+//! its offset and scale. The coefficients fitted on the training data are kept in
+//! `Chromosome::linear_scaling` and reused on the eval data.
+
+use serde::{Deserialize, Serialize};
+use crate::components::cgp_components::chromosome::Chromosome;
+use crate::utils::fitness_metrics::RegressionMetric;
+use crate::utils::fitness_trait::FitnessFunction;
+use crate::utils::numeric::Numeric;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinearScaling {
+    pub intercept: f64,
+    pub slope: f64,
+}
+
+impl LinearScaling {
+    /// `intercept + slope * prediction` with the least squared error to `labels`. A constant
+    /// prediction gets a slope of 0 and the mean of the labels; a prediction with NaN or infinite
+    /// values is not scaled, so the metric still gives it the worst fitness.
+    pub fn fit(prediction: &[f64], labels: &[f64]) -> Self {
+        if prediction.iter().any(|x| !x.is_finite()) {
+            return Self { intercept: 0., slope: 1. };
+        }
+        let nbr_rows = labels.len() as f64;
+        let mean_prediction = prediction.iter().sum::<f64>() / nbr_rows;
+        let mean_label = labels.iter().sum::<f64>() / nbr_rows;
+
+        let mut covariance = 0.;
+        let mut variance = 0.;
+        for (x, y) in prediction.iter().zip(labels.iter()) {
+            covariance += (x - mean_prediction) * (y - mean_label);
+            variance += (x - mean_prediction) * (x - mean_prediction);
+        }
+        let slope = if variance > 0. && (covariance / variance).is_finite() { covariance / variance } else { 0. };
+        return Self { intercept: mean_label - slope * mean_prediction, slope };
+    }
+
+    pub fn apply(&self, prediction: &[f64]) -> Vec<f64> {
+        return prediction.iter().map(|x| self.intercept + self.slope * x).collect();
+    }
+}
+
+pub fn to_f64<T: Numeric>(values: &[Vec<T>]) -> Vec<Vec<f64>> {
+    return values.iter()
+        .map(|inner| inner.iter().map(|x| x.to_f64().unwrap()).collect())
+        .collect();
+}
+
+/// Scales every output node (one vector per output node) with its own coefficients. The scaled
+/// outputs are `f64` for every numeric domain; the ones of an `i64` program are not integers.
+pub fn scale_outputs<T: Numeric>(outputs: &[Vec<T>], scaling: &[LinearScaling]) -> Vec<Vec<f64>> {
+    assert_eq!(outputs.len(), scaling.len(), "one linear scaling per output node is needed");
+    return to_f64(outputs).iter()
+        .zip(scaling.iter())
+        .map(|(output, scaling)| scaling.apply(output))
+        .collect();
+}
+
+/// `metric` of the linearly scaled outputs; the outputs are scaled and compared in `f64`.
+pub struct LinearScalingMetric {
+    metric: RegressionMetric,
+    refit: bool,
+}
+
+impl LinearScalingMetric {
+    /// Fits the scaling of every evaluated chromosome to the labels and keeps it in the
+    /// chromosome; for the training data.
+    pub fn fitted(metric: RegressionMetric) -> Self {
+        Self { metric, refit: true }
+    }
+
+    /// Applies the scaling kept in the chromosome, i.e. the one fitted on the training data; for
+    /// the eval data.
+    pub fn fixed(metric: RegressionMetric) -> Self {
+        Self { metric, refit: false }
+    }
+}
+
+impl<T: Numeric> FitnessFunction<T> for LinearScalingMetric {
+    /// The fitness with the scaling fitted to `labels`.
+    fn fitness(&self, prediction: &Vec<Vec<T>>, labels: &Vec<Vec<T>>) -> f32 {
+        let labels = to_f64(labels);
+        let scaled: Vec<Vec<f64>> = to_f64(prediction).iter()
+            .zip(labels.iter())
+            .map(|(output, label)| LinearScaling::fit(output, label).apply(output))
+            .collect();
+        return self.metric.fitness(&scaled, &labels);
+    }
+
+    fn chromosome_fitness(&self, chromosome: &mut Chromosome, prediction: &Vec<Vec<T>>, labels: &Vec<Vec<T>>) -> f32 {
+        let labels = to_f64(labels);
+        if self.refit {
+            chromosome.linear_scaling = Some(to_f64(prediction).iter()
+                .zip(labels.iter())
+                .map(|(output, label)| LinearScaling::fit(output, label))
+                .collect());
+        }
+        let scaling = chromosome.linear_scaling
+            .as_ref()
+            .expect("the chromosome has no linear scaling; it must be evaluated on the training data first");
+        return self.metric.fitness(&scale_outputs(prediction, scaling), &labels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::cgp_components::chromosome_evaluator_operators::{predict, ArenaChromosomeEvaluator, CachedChromosomeEvaluator, DatasetId, EvaluateChromosomeTrait};
+    use crate::components::cgp_components::chromosome_find_active_node_operators::{ChromosomeActiveNodeTrait, ChromosomeFindActiveNodesStandard};
+    use crate::datasets::regression_benchmarks;
+    use crate::function_set::regression_function_set::get_regression_function_set;
+    use crate::global_params::CgpParameters;
+    use crate::utils::utility_funcs::transpose;
+
+    #[test]
+    fn fits_offset_and_scale() {
+        let prediction = [0., 1., 2., 3.];
+        let labels: Vec<f64> = prediction.iter().map(|x| 3. - 2. * x).collect();
+        assert_eq!(LinearScaling { intercept: 3., slope: -2. }, LinearScaling::fit(&prediction, &labels));
+        assert_eq!(0f32, FitnessFunction::<f64>::fitness(&LinearScalingMetric::fitted(RegressionMetric::MeanSquaredError), &vec![prediction.to_vec()], &vec![labels]));
+
+        assert_eq!(LinearScaling { intercept: 2., slope: 0. }, LinearScaling::fit(&[5., 5.], &[1., 3.]));
+        assert_eq!(LinearScaling { intercept: 0., slope: 1. }, LinearScaling::fit(&[f64::NAN, 5.], &[1., 3.]));
+        let metric = LinearScalingMetric::fitted(RegressionMetric::SumAbsoluteError);
+        assert_eq!(f32::MAX, metric.fitness(&vec![vec![f32::INFINITY, 5.]], &vec![vec![1., 3.]]));
+    }
+
+    #[test]
+    fn eval_data_is_scaled_with_the_training_scaling() {
+        let function_set = get_regression_function_set();
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<f32>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+        let (data, label) = regression_benchmarks::keijzer::get_dataset();
+        let (eval_data, eval_label) = regression_benchmarks::keijzer::get_eval_dataset();
+        let (data, eval_data) = (transpose(data), transpose(eval_data));
+        let params = CgpParameters::for_tests(50, 1, 1, function_set.len());
+        let metric = RegressionMetric::MeanSquaredError;
+        let fitted = ArenaChromosomeEvaluator::with_fitness_function(Arc::new(LinearScalingMetric::fitted(metric.clone())));
+        let fixed = ArenaChromosomeEvaluator::with_fitness_function(Arc::new(LinearScalingMetric::fixed(metric.clone())));
+
+        let rng = &mut ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..20 {
+            let mut chromosome = Chromosome::new(params.clone(), rng);
+            let fitness = fitted.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set));
+            let scaling = chromosome.linear_scaling.clone().unwrap();
+            // the identity is one of the scalings the fit chooses from
+            let unscaled = metric.fitness(&predict(&chromosome, &data, &function_set), &label);
+            assert!(fitness <= unscaled || unscaled == f32::MAX, "{} > {}", fitness, unscaled);
+
+            let eval_fitness = fixed.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Eval, &eval_data, &eval_label, Arc::clone(&function_set));
+            assert_eq!(Some(&scaling), chromosome.linear_scaling.as_ref());
+            let prediction = scale_outputs(&predict(&chromosome, &eval_data, &function_set), &scaling);
+            assert_eq!(metric.fitness(&prediction, &to_f64(&eval_label)), eval_fitness);
+        }
+    }
+
+    #[test]
+    fn cache_hit_restores_the_fitted_scaling() {
+        let function_set = get_regression_function_set();
+        let active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<f32>>> = Arc::new(ChromosomeFindActiveNodesStandard::new());
+        let (data, label) = regression_benchmarks::keijzer::get_dataset();
+        let data = transpose(data);
+        let fitted: Box<dyn EvaluateChromosomeTrait<f32>> = Box::new(ArenaChromosomeEvaluator::with_fitness_function(Arc::new(LinearScalingMetric::fitted(RegressionMetric::MeanSquaredError))));
+        let cached = CachedChromosomeEvaluator::with_capacity(10, Arc::new(fitted));
+
+        let mut chromosome = Chromosome::new(CgpParameters::for_tests(50, 1, 1, function_set.len()), &mut ChaCha8Rng::seed_from_u64(0));
+        let fitness = cached.evaluate(&mut chromosome, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set));
+        let scaling = chromosome.linear_scaling.clone();
+
+        // e.g. the scaling of another parent
+        let mut copy = chromosome.clone();
+        copy.linear_scaling = Some(vec![LinearScaling { intercept: 1., slope: 2. }]);
+        assert_eq!(fitness, cached.evaluate(&mut copy, Arc::clone(&active_node_func), DatasetId::Train, &data, &label, Arc::clone(&function_set)));
+        assert_eq!(scaling, copy.linear_scaling);
+        assert_eq!(1, EvaluateChromosomeTrait::<f32>::cache_statistics(&cached).unwrap().hits);
+    }
+}
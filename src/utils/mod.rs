@@ -1,8 +1,16 @@
 pub mod utility_funcs;
 pub mod fitness_metrics;
+pub mod packed_bits;
 pub mod runner;
+pub mod checkpoint;
+pub mod observer;
+pub mod observer_sinks;
 pub mod fitness_trait;
 pub mod cycle_checker;
 
 pub mod linspace;
-pub mod txt_writer;
\ No newline at end of file
+pub mod txt_writer;
+pub mod numeric;
+pub mod linear_scaling;
+pub mod dual;
+pub mod interval;
\ No newline at end of file
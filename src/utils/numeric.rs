@@ -0,0 +1,77 @@
+//! Numeric domains of regression problems: `f32` (the paper setup), `f64` for precise symbolic
+//! regression and `i64` for integer problems. Every domain has its own function set, see
+//! `regression_function_set`, `f64_function_set` and `integer_function_set`.
+
+use std::fmt::Debug;
+use num::Float;
+
+/// `ToPrimitive` for the metrics that are computed in `f64`; see `RegressionMetric`. `Bounded` for
+/// the overflow of the interval analysis, see `interval_analysis`.
+pub trait Numeric: num::Num + num::ToPrimitive + num::Bounded + Copy + PartialOrd + Debug + Send + Sync + 'static {
+    /// Sum of the absolute differences of all values of `prediction` and `labels`; see
+    /// `fitness_metrics::fitness_regression`.
+    fn sum_absolute_errors(prediction: &[Vec<Self>], labels: &[Vec<Self>]) -> f64;
+
+    /// Value of a constant gene, see `CGPNode::constant`.
+    fn from_constant(constant: f64) -> Self;
+}
+
+// summed in the type itself, so `f32` gives the same fitness as before the other domains
+fn sum_absolute_errors_float<T: Float>(prediction: &[Vec<T>], labels: &[Vec<T>]) -> T {
+    let mut sum = T::zero();
+    prediction.iter().zip(labels.iter()).for_each(|(inner_pred, inner_label)|
+        inner_pred.iter().zip(inner_label.iter()).for_each(|(x, y)| sum = sum + (*x - *y).abs())
+    );
+    return sum;
+}
+
+impl Numeric for f32 {
+    fn sum_absolute_errors(prediction: &[Vec<f32>], labels: &[Vec<f32>]) -> f64 {
+        return sum_absolute_errors_float(prediction, labels) as f64;
+    }
+
+    fn from_constant(constant: f64) -> f32 {
+        return constant as f32;
+    }
+}
+
+impl Numeric for f64 {
+    fn sum_absolute_errors(prediction: &[Vec<f64>], labels: &[Vec<f64>]) -> f64 {
+        return sum_absolute_errors_float(prediction, labels);
+    }
+
+    fn from_constant(constant: f64) -> f64 {
+        return constant;
+    }
+}
+
+impl Numeric for i64 {
+    fn sum_absolute_errors(prediction: &[Vec<i64>], labels: &[Vec<i64>]) -> f64 {
+        // the difference of two i64 does not always fit into an i64
+        let mut sum: u128 = 0;
+        prediction.iter().zip(labels.iter()).for_each(|(inner_pred, inner_label)|
+            inner_pred.iter().zip(inner_label.iter()).for_each(|(x, y)| sum += (*x as i128 - *y as i128).unsigned_abs())
+        );
+        return sum as f64;
+    }
+
+    // the nearest integer; saturates at the bounds of i64
+    fn from_constant(constant: f64) -> i64 {
+        return constant.round() as i64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fitness_metrics::fitness_regression;
+
+    #[test]
+    fn integer_errors_do_not_overflow() {
+        let prediction = vec![vec![i64::MIN, i64::MAX, 3]];
+        let labels = vec![vec![i64::MAX, i64::MIN, 5]];
+        assert_eq!(4. * (i64::MAX as f64) + 4., i64::sum_absolute_errors(&prediction, &labels));
+        assert_eq!(2., fitness_regression(&[vec![3]], &[vec![5]]));
+        assert_eq!(0.5, fitness_regression(&[vec![0.25f64, 1.]], &[vec![0., 0.75]]));
+    }
+}
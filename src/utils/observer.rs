@@ -0,0 +1,171 @@
+//! Hooks into a run of `Evolution`, i.e. for logging. Observers only read the state of a run;
+//! built-in sinks are in `observer_sinks`.
+
+use std::path::PathBuf;
+use crate::components::cgp_components::chromosome_mutation_operators::MutationReport;
+use crate::utils::runner::{RunResult, Runner};
+
+/// A chromosome of the population was mutated.
+pub struct MutationEvent<'a> {
+    pub chromosome_id: usize,
+    pub report: &'a MutationReport,
+}
+
+/// Every callback does nothing by default.
+pub trait EvolutionObserver<T> {
+    /// Before each generation, with the progress so far.
+    fn on_generation(&mut self, _progress: &RunResult, _runner: &Runner<T>) {}
+
+    /// After a generation that improved the best fitness.
+    fn on_improvement(&mut self, _progress: &RunResult, _runner: &Runner<T>) {}
+
+    fn on_mutation(&mut self, _mutation: &MutationEvent) {}
+
+    /// After the selection of a generation; see `runner.elitist_ids`.
+    fn on_selection(&mut self, _runner: &Runner<T>) {}
+
+    /// The run met a stopping criterion. Not called for interrupted runs; they finish after they
+    /// are resumed.
+    fn on_finish(&mut self, _result: &RunResult, _runner: &Runner<T>) {}
+
+    /// Flushes the files the observer writes to and returns them with their length; see
+    /// `Checkpoint::output_files`.
+    fn output_files(&mut self) -> Vec<(PathBuf, u64)> {
+        vec![]
+    }
+}
+
+/// All observers of a run; events are passed on in the order the observers were added.
+pub struct Observers<T> {
+    observers: Vec<Box<dyn EvolutionObserver<T>>>,
+}
+
+impl<T> Observers<T> {
+    pub fn new() -> Self {
+        Self {
+            observers: vec![],
+        }
+    }
+
+    pub fn push(&mut self, observer: Box<dyn EvolutionObserver<T>>) {
+        self.observers.push(observer);
+    }
+
+    pub fn len(&self) -> usize {
+        self.observers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+}
+
+impl<T> Default for Observers<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> EvolutionObserver<T> for Observers<T> {
+    fn on_generation(&mut self, progress: &RunResult, runner: &Runner<T>) {
+        for observer in &mut self.observers {
+            observer.on_generation(progress, runner);
+        }
+    }
+
+    fn on_improvement(&mut self, progress: &RunResult, runner: &Runner<T>) {
+        for observer in &mut self.observers {
+            observer.on_improvement(progress, runner);
+        }
+    }
+
+    fn on_mutation(&mut self, mutation: &MutationEvent) {
+        for observer in &mut self.observers {
+            observer.on_mutation(mutation);
+        }
+    }
+
+    fn on_selection(&mut self, runner: &Runner<T>) {
+        for observer in &mut self.observers {
+            observer.on_selection(runner);
+        }
+    }
+
+    fn on_finish(&mut self, result: &RunResult, runner: &Runner<T>) {
+        for observer in &mut self.observers {
+            observer.on_finish(result, runner);
+        }
+    }
+
+    fn output_files(&mut self) -> Vec<(PathBuf, u64)> {
+        self.observers
+            .iter_mut()
+            .flat_map(|observer| observer.output_files())
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+    use crate::components::operator_registry::EvolutionPipeline;
+    use crate::datasets::boolean_datasets;
+    use crate::experiment::experiment_config::OperatorConfig;
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::global_params::CgpParameters;
+    use crate::utils::fitness_metrics::BooleanMetric;
+    use crate::utils::runner::{Evolution, StoppingCriterion};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Rc<std::cell::RefCell<Vec<String>>>,
+    }
+
+    impl<T> EvolutionObserver<T> for Recorder {
+        fn on_generation(&mut self, progress: &RunResult, _runner: &Runner<T>) {
+            self.events.borrow_mut().push(format!("generation {}", progress.generations));
+        }
+
+        fn on_mutation(&mut self, mutation: &MutationEvent) {
+            self.events.borrow_mut().push(format!("mutation {}", mutation.chromosome_id));
+        }
+
+        fn on_selection(&mut self, _runner: &Runner<T>) {
+            self.events.borrow_mut().push("selection".to_string());
+        }
+
+        fn on_finish(&mut self, result: &RunResult, _runner: &Runner<T>) {
+            self.events.borrow_mut().push(format!("finish {}", result.generations));
+        }
+    }
+
+    #[test]
+    fn observers_see_every_event_in_order() {
+        let params = CgpParameters::for_tests(20, 6, 6, 4);
+        let function_set = get_boolean_function_set();
+        let pipeline = EvolutionPipeline::new(&OperatorConfig::one_plus_four("single"), &params, Arc::clone(&function_set), Arc::new(BooleanMetric::Hamming)).unwrap();
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        let runner = Runner::new(params, data, label, None, None, function_set, Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.fitness_function), ChaCha8Rng::seed_from_u64(0));
+
+        let mut evolution = Evolution::new(runner, pipeline, vec![StoppingCriterion::MaxGenerations(2)]);
+        let recorder = Recorder::default();
+        let events = Rc::clone(&recorder.events);
+        evolution.observers.push(Box::new(recorder));
+        evolution.run(|_, _, _| {});
+
+        let events = events.borrow();
+        assert_eq!(13, events.len());
+        assert_eq!("generation 0", events[0]);
+        assert_eq!(4, events[1..5].iter().filter(|event| event.starts_with("mutation")).count());
+        assert_eq!("selection", events[5]);
+        assert_eq!("generation 1", events[6]);
+        assert_eq!(4, events[7..11].iter().filter(|event| event.starts_with("mutation")).count());
+        assert_eq!("selection", events[11]);
+        assert_eq!("finish 2", events[12]);
+    }
+}
@@ -0,0 +1,253 @@
+//! Built-in `EvolutionObserver`s. `stdout`, `csv` and `jsonl` can be chosen by name in an
+//! experiment (`observers`); the iteration and mutation files are written for every run.
+//!
+//! Files are opened in append mode when a run is resumed from a checkpoint.
+//!
+//! `mutated_nodes_{id}.txt` has one line per mutated child with the number of genes the mutation
+//! changed, for every chromosome mutation operator. Before the `MutationReport`s it was written
+//! by the single and multi mutations only and counted the drawn nodes; the counts are the same
+//! unless a drawn gene had no other value, e.g. with a function set of a single function.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use crate::components::cgp_components::chromosome_mutation_operators::NodeMutation;
+use crate::utils::observer::{EvolutionObserver, MutationEvent};
+use crate::utils::runner::{RunResult, Runner, StoppingCriterion};
+
+pub const OBSERVERS: [&str; 3] = ["stdout", "csv", "jsonl"];
+
+/// Returns the observer of the given name; files are written to `save_path`.
+pub fn get_observer<T>(name: &str, save_path: &Path, run_id: usize, append: bool) -> Result<Box<dyn EvolutionObserver<T>>, String> {
+    match name {
+        "stdout" => Ok(Box::new(StdoutObserver::new(500))),
+        "csv" => Ok(Box::new(CsvObserver::create(&save_path.join(format!("run_{}_progress.csv", run_id)), append)?)),
+        "jsonl" => Ok(Box::new(JsonLinesObserver::create(&save_path.join(format!("run_{}_events.jsonl", run_id)), append)?)),
+        _ => Err(format!("unknown observer '{}', expected one of {:?}", name, OBSERVERS)),
+    }
+}
+
+fn open_output_file(path: &Path, append: bool) -> Result<BufWriter<File>, String> {
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .map(BufWriter::new)
+        .map_err(|e| format!("cannot create {}: {}", path.display(), e))
+}
+
+fn flushed_length(path: &Path, file: &mut BufWriter<File>) -> (PathBuf, u64) {
+    file.flush().expect("cannot write");
+    (path.to_path_buf(), file.get_ref().metadata().expect("cannot read file").len())
+}
+
+/// Prints the best fitness every `interval` generations and the end of the run.
+pub struct StdoutObserver {
+    interval: usize,
+}
+
+impl StdoutObserver {
+    pub fn new(interval: usize) -> Self {
+        Self { interval }
+    }
+}
+
+impl<T> EvolutionObserver<T> for StdoutObserver {
+    fn on_generation(&mut self, progress: &RunResult, runner: &Runner<T>) {
+        if progress.generations.is_multiple_of(self.interval) {
+            println!("i: {}, fitness: {}", progress.generations, runner.get_best_fitness());
+        }
+    }
+
+    fn on_finish(&mut self, result: &RunResult, _runner: &Runner<T>) {
+        println!("Stopped by {} at iteration {}; best fitness: {}; fitness evaluations: {} ({} skipped)",
+                 result.stop_reason.as_ref().unwrap(), result.generations, result.best_fitness,
+                 result.fitness_evaluations, result.skipped_evaluations);
+    }
+}
+
+/// `run_{id}_iteration.txt`: the best fitness before each generation and the end of the run.
+pub struct IterationFileWriter {
+    path: PathBuf,
+    file: BufWriter<File>,
+}
+
+impl IterationFileWriter {
+    pub fn create(path: &Path, append: bool) -> Result<Self, String> {
+        Ok(Self { path: path.to_path_buf(), file: open_output_file(path, append)? })
+    }
+}
+
+impl<T> EvolutionObserver<T> for IterationFileWriter {
+    fn on_generation(&mut self, progress: &RunResult, runner: &Runner<T>) {
+        writeln!(self.file, "Iteration: {}, Fitness: {:?}", progress.generations, runner.get_best_fitness()).expect("write not okay??");
+    }
+
+    fn on_finish(&mut self, result: &RunResult, _runner: &Runner<T>) {
+        writeln!(self.file, "Stopped by: {}", result.stop_reason.as_ref().unwrap()).expect("cannot write");
+        writeln!(self.file, "Fitness evaluations: {}", result.fitness_evaluations).expect("cannot write");
+        writeln!(self.file, "Skipped evaluations: {}", result.skipped_evaluations).expect("cannot write");
+        writeln!(self.file, "End at iteration: {}", result.generations).expect("cannot write");
+        self.file.flush().expect("cannot write");
+    }
+
+    fn output_files(&mut self) -> Vec<(PathBuf, u64)> {
+        vec![flushed_length(&self.path, &mut self.file)]
+    }
+}
+
+/// `mutated_nodes_{id}.txt`: the number of changed genes of each mutation; see the module doc.
+pub struct MutationFileWriter {
+    path: PathBuf,
+    file: BufWriter<File>,
+}
+
+impl MutationFileWriter {
+    pub fn create(path: &Path, append: bool) -> Result<Self, String> {
+        Ok(Self { path: path.to_path_buf(), file: open_output_file(path, append)? })
+    }
+}
+
+impl<T> EvolutionObserver<T> for MutationFileWriter {
+    fn on_mutation(&mut self, mutation: &MutationEvent) {
+        writeln!(self.file, "{:?}", mutation.report.len()).expect("write not okay??");
+    }
+
+    fn on_finish(&mut self, _result: &RunResult, _runner: &Runner<T>) {
+        self.file.flush().expect("cannot write");
+    }
+
+    fn output_files(&mut self) -> Vec<(PathBuf, u64)> {
+        vec![flushed_length(&self.path, &mut self.file)]
+    }
+}
+
+/// `run_{id}_progress.csv`: one row before each generation and one at the end of the run.
+pub struct CsvObserver {
+    path: PathBuf,
+    file: BufWriter<File>,
+}
+
+impl CsvObserver {
+    pub fn create(path: &Path, append: bool) -> Result<Self, String> {
+        let mut file = open_output_file(path, append)?;
+        if !append {
+            writeln!(file, "generation,fitness_evaluations,skipped_evaluations,best_fitness,elapsed_seconds").expect("cannot write");
+        }
+        Ok(Self { path: path.to_path_buf(), file })
+    }
+
+    fn write_row(&mut self, progress: &RunResult) {
+        writeln!(self.file, "{},{},{},{},{}", progress.generations, progress.fitness_evaluations,
+                 progress.skipped_evaluations, progress.best_fitness, progress.elapsed.as_secs_f64()).expect("cannot write");
+    }
+}
+
+impl<T> EvolutionObserver<T> for CsvObserver {
+    fn on_generation(&mut self, progress: &RunResult, _runner: &Runner<T>) {
+        self.write_row(progress);
+    }
+
+    fn on_finish(&mut self, result: &RunResult, _runner: &Runner<T>) {
+        self.write_row(result);
+        self.file.flush().expect("cannot write");
+    }
+
+    fn output_files(&mut self) -> Vec<(PathBuf, u64)> {
+        vec![flushed_length(&self.path, &mut self.file)]
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    Generation(ProgressRecord<'a>),
+    Improvement(ProgressRecord<'a>),
+    Mutation { generation: usize, chromosome_id: usize, mutations: &'a [NodeMutation] },
+    Selection { generation: usize, elitist_ids: &'a [usize], best_fitness: f32 },
+    Finish(ProgressRecord<'a>),
+}
+
+#[derive(Serialize)]
+struct ProgressRecord<'a> {
+    generation: usize,
+    fitness_evaluations: usize,
+    skipped_evaluations: usize,
+    best_fitness: f32,
+    elapsed_seconds: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_reason: Option<&'a StoppingCriterion>,
+}
+
+impl<'a> ProgressRecord<'a> {
+    fn new(progress: &'a RunResult) -> Self {
+        Self {
+            generation: progress.generations,
+            fitness_evaluations: progress.fitness_evaluations,
+            skipped_evaluations: progress.skipped_evaluations,
+            best_fitness: progress.best_fitness,
+            elapsed_seconds: progress.elapsed.as_secs_f64(),
+            stop_reason: progress.stop_reason.as_ref(),
+        }
+    }
+}
+
+/// `run_{id}_events.jsonl`: one JSON object per event, i.e.
+/// `{"event":"mutation","generation":12,"chromosome_id":3,"mutations":[{"node_id":40,"gene":"function",
+/// "old_value":1,"new_value":3,"active":true}]}`.
+pub struct JsonLinesObserver {
+    path: PathBuf,
+    file: BufWriter<File>,
+    // generation of the mutation and selection events
+    generation: usize,
+}
+
+impl JsonLinesObserver {
+    pub fn create(path: &Path, append: bool) -> Result<Self, String> {
+        Ok(Self { path: path.to_path_buf(), file: open_output_file(path, append)?, generation: 0 })
+    }
+
+    fn write_event(&mut self, event: &Event) {
+        serde_json::to_writer(&mut self.file, event).expect("cannot write");
+        writeln!(self.file).expect("cannot write");
+    }
+}
+
+impl<T> EvolutionObserver<T> for JsonLinesObserver {
+    fn on_generation(&mut self, progress: &RunResult, _runner: &Runner<T>) {
+        self.generation = progress.generations;
+        self.write_event(&Event::Generation(ProgressRecord::new(progress)));
+    }
+
+    fn on_improvement(&mut self, progress: &RunResult, _runner: &Runner<T>) {
+        self.write_event(&Event::Improvement(ProgressRecord::new(progress)));
+    }
+
+    fn on_mutation(&mut self, mutation: &MutationEvent) {
+        self.write_event(&Event::Mutation {
+            generation: self.generation,
+            chromosome_id: mutation.chromosome_id,
+            mutations: &mutation.report.mutations,
+        });
+    }
+
+    fn on_selection(&mut self, runner: &Runner<T>) {
+        self.write_event(&Event::Selection {
+            generation: self.generation,
+            elitist_ids: &runner.elitist_ids,
+            best_fitness: runner.get_best_fitness(),
+        });
+    }
+
+    fn on_finish(&mut self, result: &RunResult, _runner: &Runner<T>) {
+        self.write_event(&Event::Finish(ProgressRecord::new(result)));
+        self.file.flush().expect("cannot write");
+    }
+
+    fn output_files(&mut self) -> Vec<(PathBuf, u64)> {
+        vec![flushed_length(&self.path, &mut self.file)]
+    }
+}
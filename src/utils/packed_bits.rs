@@ -0,0 +1,86 @@
+//! Bit-parallel representation of Boolean datasets: a value of `PackedBits` holds 64 rows of a
+//! truth table column, so a node computes 64 rows with a single word operation.
+//! See `packed_boolean_function_set` and `fitness_metrics::fitness_boolean_packed`.
+
+use std::ops::{BitAnd, BitOr, Not};
+
+/// Rows `64 * i .. 64 * (i + 1)` of a column; row `64 * i + j` is bit `j`. Bits of rows beyond
+/// the end of the dataset are not `valid` and always 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackedBits {
+    pub bits: u64,
+    pub valid: u64,
+}
+
+impl PackedBits {
+    /// Number of valid rows in which `self` and `other` differ.
+    pub fn hamming_distance(self, other: PackedBits) -> u32 {
+        ((self.bits ^ other.bits) & self.valid).count_ones()
+    }
+}
+
+impl BitAnd for PackedBits {
+    type Output = PackedBits;
+
+    fn bitand(self, other: PackedBits) -> PackedBits {
+        PackedBits { bits: self.bits & other.bits, valid: self.valid }
+    }
+}
+
+impl BitOr for PackedBits {
+    type Output = PackedBits;
+
+    fn bitor(self, other: PackedBits) -> PackedBits {
+        PackedBits { bits: self.bits | other.bits, valid: self.valid }
+    }
+}
+
+impl Not for PackedBits {
+    type Output = PackedBits;
+
+    /// Clears the bits of invalid rows.
+    fn not(self) -> PackedBits {
+        PackedBits { bits: !self.bits & self.valid, valid: self.valid }
+    }
+}
+
+/// Packs a dataset of `rows x columns` (i.e. as returned by `boolean_datasets`) into
+/// `ceil(rows / 64) x columns`; the packed dataset can be used like the original one.
+pub fn pack_dataset(dataset: &[Vec<bool>]) -> Vec<Vec<PackedBits>> {
+    let nbr_columns = dataset[0].len();
+
+    return dataset
+        .chunks(64)
+        .map(|rows| {
+            (0..nbr_columns)
+                .map(|column| {
+                    let mut packed = PackedBits { bits: 0, valid: 0 };
+                    for (j, row) in rows.iter().enumerate() {
+                        packed.bits |= (row[column] as u64) << j;
+                        packed.valid |= 1 << j;
+                    }
+                    packed
+                })
+                .collect()
+        })
+        .collect();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_rows_into_bits() {
+        let dataset: Vec<Vec<bool>> = (0..70).map(|row| vec![row % 2 == 1, row == 65]).collect();
+
+        let packed = pack_dataset(&dataset);
+
+        assert_eq!(2, packed.len());
+        assert_eq!(PackedBits { bits: 0xAAAA_AAAA_AAAA_AAAA, valid: u64::MAX }, packed[0][0]);
+        assert_eq!(PackedBits { bits: 0b101010, valid: 0b111111 }, packed[1][0]);
+        assert_eq!(PackedBits { bits: 0b10, valid: 0b111111 }, packed[1][1]);
+        assert_eq!(PackedBits { bits: 0b010101, valid: 0b111111 }, !packed[1][0]);
+    }
+}
@@ -1,15 +1,33 @@
 
 
+use std::fmt::{Display, Formatter};
 use std::rc::Rc;
-use rand::prelude::ThreadRng;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 use crate::global_params::CgpParameters;
 use crate::components::cgp_components::chromosome::Chromosome;
-use crate::components::cgp_components::chromosome_evaluator_operators::{EvaluateChromosomeTrait, ChromosomeEvaluator};
+use crate::components::cgp_components::chromosome_evaluator_operators::{DatasetId, EvaluateChromosomeTrait, ChromosomeEvaluator};
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
+use crate::components::evo_operators_for_population::evaluation_operators::eval_population_trait::EvaluationCount;
+use crate::components::operator_registry::EvolutionPipeline;
 use crate::function_set::function_trait::FunctionTrait;
+use crate::utils::fitness_trait::FitnessFunction;
+use crate::utils::checkpoint;
+use crate::utils::observer::{EvolutionObserver, Observers};
 use crate::utils::utility_funcs::{get_argmin, get_argmins_of_value, transpose, vect_difference};
 
 
+/// The parent a child was cloned from in this generation and the nodes that were mutated since;
+/// see `EvaluateChromosomeTrait::evaluate_mutant`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lineage {
+    pub parent_id: usize,
+    // a node that is mutated twice is listed twice
+    pub mutated_nodes: Vec<usize>,
+}
+
 // in case of mu+lambda: mu == elitsists_ids, lambda = child_ids
 #[derive(Clone)]
 pub struct Runner<T>
@@ -25,22 +43,29 @@ pub struct Runner<T>
     pub elitist_ids: Vec<usize>,
     pub child_ids: Vec<usize>,
     pub tournament_selected: Option<Vec<usize>>,
-    pub rng: ThreadRng,
+    // lineage of every chromosome in this generation, indexed by id; `None` if it was not cloned
+    // from a parent, e.g. after a crossover, or was reordered
+    pub lineage: Vec<Option<Lineage>>,
+    // the only source of randomness of a run; see `--seed`
+    pub rng: ChaCha8Rng,
 }
 
 
 impl<T> Runner<T>
 where
-    ChromosomeEvaluator: EvaluateChromosomeTrait<T>,
+    ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
     T: Clone,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(params: CgpParameters,
                data: Vec<Vec<T>>,
                label: Vec<Vec<T>>,
                mut eval_data: Option<Vec<Vec<T>>>,
                eval_label: Option<Vec<Vec<T>>>,
-               function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>,
-               active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>,
+               active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+               fitness_function: Arc<dyn FitnessFunction<T>>,
+               mut rng: ChaCha8Rng,
     ) -> Self {
         // Data "must" be transposed for later evaluation cycles.
         // Doesn't really need to be, but otherwise the logic is more confusing later.
@@ -51,17 +76,19 @@ where
 
         let mut population: Vec<Chromosome> = Vec::with_capacity(params.elitists + params.population_size);
         let mut fitness_vals: Vec<f32> = Vec::with_capacity(params.elitists + params.population_size);
-        let evaluator = ChromosomeEvaluator::new();
+        let evaluator = ChromosomeEvaluator::with_fitness_function(fitness_function);
 
         for _ in 0..(params.elitists + params.population_size) {
             let mut chromosome = Chromosome::new(
                 params.clone(),
+                &mut rng,
             );
             let fitness = evaluator.evaluate(&mut chromosome,
-                                             Rc::clone(&active_node_func),
+                                             Arc::clone(&active_node_func),
+                                             DatasetId::Train,
                                              &data,
                                              &label,
-                                             Rc::clone(&function_set));
+                                             Arc::clone(&function_set));
             fitness_vals.push(fitness);
             population.push(chromosome);
         }
@@ -103,8 +130,7 @@ where
             child_ids = vect_difference(&child_ids, &elitist_ids);
         }
 
-
-        let rng = rand::thread_rng();
+        let lineage = vec![None; params.elitists + params.population_size];
 
         Self {
             params,
@@ -119,10 +145,12 @@ where
             child_ids,
             rng,
             tournament_selected: None,
+            lineage,
         }
     }
+}
 
-
+impl<T> Runner<T> {
     pub fn get_best_fitness(&self) -> f32 {
         return self.fitness_vals_sorted[0];
     }
@@ -143,5 +171,311 @@ pub fn get_runner_parent<T>(runner: &Runner<T>) -> Chromosome {
 }
 
 
+/// Stops a run of `Evolution`. Criteria are checked before each generation; the first one that is
+/// met ends the run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StoppingCriterion {
+    MaxGenerations(usize),
+    // includes the evaluations of the initial population
+    MaxFitnessEvaluations(usize),
+    WallClock(Duration),
+    // number of generations without improvement of the best fitness
+    Stagnation(usize),
+    // best fitness < target fitness, i.e. `CgpParameters::fitness_threshold`
+    TargetFitness(f32),
+    // SIGINT or SIGTERM; see `checkpoint::install_signal_handler`
+    Interrupted,
+}
+
+impl StoppingCriterion {
+    fn is_met(&self, result: &RunResult) -> bool {
+        match self {
+            StoppingCriterion::MaxGenerations(max) => result.generations >= *max,
+            StoppingCriterion::MaxFitnessEvaluations(max) => result.fitness_evaluations >= *max,
+            StoppingCriterion::WallClock(limit) => result.elapsed >= *limit,
+            StoppingCriterion::Stagnation(window) => result.generations_without_improvement >= *window,
+            StoppingCriterion::TargetFitness(target) => result.best_fitness < *target,
+            StoppingCriterion::Interrupted => checkpoint::is_interrupted(),
+        }
+    }
+}
+
+impl Display for StoppingCriterion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoppingCriterion::MaxGenerations(max) => write!(f, "max generations ({})", max),
+            StoppingCriterion::MaxFitnessEvaluations(max) => write!(f, "max fitness evaluations ({})", max),
+            StoppingCriterion::WallClock(limit) => write!(f, "wall clock ({}s)", limit.as_secs_f32()),
+            StoppingCriterion::Stagnation(window) => write!(f, "stagnation ({} generations)", window),
+            StoppingCriterion::TargetFitness(target) => write!(f, "target fitness ({})", target),
+            StoppingCriterion::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+/// Summary of a finished run of `Evolution`; during a run, its progress.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunResult {
+    pub generations: usize,
+    // forward passes on the training data, including the initial population
+    pub fitness_evaluations: usize,
+    // children that got the fitness of an elitist with the same active genotype; see
+    // `EvaluationCount`
+    pub skipped_evaluations: usize,
+    pub best_fitness: f32,
+    pub generations_without_improvement: usize,
+    pub elapsed: Duration,
+    // the criterion that ended the run
+    pub stop_reason: Option<StoppingCriterion>,
+}
+
+/// Runs the generational loop of an `EvolutionPipeline` on a `Runner` until one of the stopping
+/// criteria is met. Every event of the run is passed to `observers`.
+pub struct Evolution<T: Clone> {
+    pub runner: Runner<T>,
+    pub pipeline: EvolutionPipeline<T>,
+    pub stopping_criteria: Vec<StoppingCriterion>,
+    pub observers: Observers<T>,
+}
+
+impl<T> Evolution<T>
+where
+    ChromosomeEvaluator<T>: EvaluateChromosomeTrait<T>,
+    T: Clone,
+{
+    pub fn new(runner: Runner<T>,
+               pipeline: EvolutionPipeline<T>,
+               stopping_criteria: Vec<StoppingCriterion>) -> Self {
+        assert!(!stopping_criteria.is_empty(), "Evolution needs at least one stopping criterion");
+
+        Self {
+            runner,
+            pipeline,
+            stopping_criteria,
+            observers: Observers::new(),
+        }
+    }
+
+    /// (clone | crossover) -> reorder -> mutate -> evaluate -> select; the constants are tuned in
+    /// `run_from`, which knows the generation.
+    pub fn next_generation(&mut self) -> EvaluationCount {
+        let runner = &mut self.runner;
+        let pipeline = &mut self.pipeline;
+
+        runner.lineage.iter_mut().for_each(|lineage| *lineage = None);
+        match &pipeline.crossover_operator {
+            Some(crossover_operator) => crossover_operator.execute(runner, Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.function_set)),
+            None => pipeline.clone_operator.execute(runner),
+        }
+        if let Some(chromosome_reorder_op) = &pipeline.chromosome_reorder_op {
+            pipeline.reorder_operator.execute(runner, Rc::clone(chromosome_reorder_op), Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.function_set));
+        }
+        pipeline.mutation_operator.execute(runner, Rc::clone(&pipeline.node_mutation_op), Rc::clone(&pipeline.chromosome_mutation_op), &mut self.observers);
+        let count = pipeline.eval_operator.execute(runner, Arc::clone(&pipeline.chromosome_eval_op), Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.function_set), pipeline.thread_pool.as_ref());
+        pipeline.selection_operator.execute(runner, Arc::clone(&pipeline.function_set));
+        self.observers.on_selection(runner);
+        return count;
+    }
+
+    /// Starts a new run; see `run_from`.
+    pub fn run<F>(&mut self, on_generation: F) -> RunResult
+    where F: FnMut(&RunResult, &Runner<T>, &mut Observers<T>) {
+        if self.pipeline.select_before_first_generation {
+            self.pipeline.selection_operator.execute(&mut self.runner, Arc::clone(&self.pipeline.function_set));
+        }
+
+        let progress = RunResult {
+            generations: 0,
+            fitness_evaluations: self.runner.params.elitists + self.runner.params.population_size,
+            skipped_evaluations: 0,
+            best_fitness: self.runner.get_best_fitness(),
+            generations_without_improvement: 0,
+            elapsed: Duration::ZERO,
+            stop_reason: None,
+        };
+        return self.run_from(progress, on_generation);
+    }
+
+    /// Runs generations until a stopping criterion is met, starting at `progress`, i.e. the
+    /// progress of a checkpoint.
+    /// `on_generation` is called with the progress before each generation and before the
+    /// observers, i.e. for checkpoints; the runner is in a consistent state at this point.
+    pub fn run_from<F>(&mut self, progress: RunResult, mut on_generation: F) -> RunResult
+    where F: FnMut(&RunResult, &Runner<T>, &mut Observers<T>) {
+        let start = Instant::now();
+        let elapsed_before = progress.elapsed;
+        let mut result = progress;
+
+        loop {
+            result.elapsed = elapsed_before + start.elapsed();
+            result.stop_reason = self.stopping_criteria
+                .iter()
+                .find(|criterion| criterion.is_met(&result))
+                .cloned();
+            if let Some(stop_reason) = &result.stop_reason {
+                if *stop_reason != StoppingCriterion::Interrupted {
+                    self.observers.on_finish(&result, &self.runner);
+                }
+                return result;
+            }
 
+            on_generation(&result, &self.runner, &mut self.observers);
+            self.observers.on_generation(&result, &self.runner);
+            let count = self.next_generation();
+
+            result.generations += 1;
+            result.fitness_evaluations += count.evaluated;
+            result.skipped_evaluations += count.skipped;
+            let pipeline = &self.pipeline;
+            if let Some(tune_constants_operator) = &pipeline.tune_constants_operator {
+                result.fitness_evaluations += tune_constants_operator.execute(result.generations, &mut self.runner, Arc::clone(&pipeline.chromosome_eval_op), Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.function_set));
+            }
 
+            let best_fitness = self.runner.get_best_fitness();
+            if best_fitness < result.best_fitness {
+                result.best_fitness = best_fitness;
+                result.generations_without_improvement = 0;
+                self.observers.on_improvement(&result, &self.runner);
+            } else {
+                result.generations_without_improvement += 1;
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use rand::SeedableRng;
+    use super::*;
+    use crate::datasets::boolean_datasets;
+    use crate::experiment::analysis::parse_iteration_file;
+    use crate::experiment::experiment_config::ExperimentConfig;
+    use crate::function_set::boolean_function_set::get_boolean_function_set;
+    use crate::utils::fitness_metrics::BooleanMetric;
+    use crate::utils::observer_sinks::IterationFileWriter;
+
+    fn evolution(stopping_criteria: Vec<StoppingCriterion>) -> Evolution<bool> {
+        let mut config = ExperimentConfig::load(Path::new("experiments/multiply_single.toml")).unwrap();
+        config.cgp.graph_width = 50;
+        config.cgp.population_size = 4;
+
+        let function_set = get_boolean_function_set();
+        let (data, label) = boolean_datasets::multiply::get_dataset();
+        let params = config.resolve_cgp_parameters(data[0].len(), label[0].len(), function_set.len()).unwrap();
+        let pipeline = EvolutionPipeline::new(&config.operators, &params, Arc::clone(&function_set), Arc::new(BooleanMetric::Hamming)).unwrap();
+        let runner = Runner::new(params, data, label, None, None, Arc::clone(&function_set), Arc::clone(&pipeline.chromosome_active_op), Arc::clone(&pipeline.fitness_function), ChaCha8Rng::seed_from_u64(0));
+        return Evolution::new(runner, pipeline, stopping_criteria);
+    }
+
+    fn progress() -> RunResult {
+        RunResult {
+            generations: 10,
+            fitness_evaluations: 45,
+            skipped_evaluations: 0,
+            best_fitness: 0.5,
+            generations_without_improvement: 3,
+            elapsed: Duration::from_secs(2),
+            stop_reason: None,
+        }
+    }
+
+    #[test]
+    fn criteria_are_met_at_their_limit() {
+        let progress = progress();
+
+        assert!(StoppingCriterion::MaxGenerations(10).is_met(&progress));
+        assert!(!StoppingCriterion::MaxGenerations(11).is_met(&progress));
+        assert!(StoppingCriterion::MaxFitnessEvaluations(45).is_met(&progress));
+        assert!(!StoppingCriterion::MaxFitnessEvaluations(46).is_met(&progress));
+        assert!(StoppingCriterion::WallClock(Duration::from_secs(2)).is_met(&progress));
+        assert!(!StoppingCriterion::WallClock(Duration::from_secs(3)).is_met(&progress));
+        assert!(StoppingCriterion::Stagnation(3).is_met(&progress));
+        assert!(!StoppingCriterion::Stagnation(4).is_met(&progress));
+        // the best fitness has to be below the target, as for `fitness_threshold`
+        assert!(StoppingCriterion::TargetFitness(0.6).is_met(&progress));
+        assert!(!StoppingCriterion::TargetFitness(0.5).is_met(&progress));
+    }
+
+    #[test]
+    fn run_stops_at_max_generations() {
+        let mut evolution = evolution(vec![StoppingCriterion::MaxGenerations(10)]);
+        let mut generations_seen = vec![];
+        let result = evolution.run(|progress, _, _| generations_seen.push(progress.generations));
+
+        assert_eq!(result.generations, 10);
+        assert_eq!(generations_seen, (0..10).collect::<Vec<usize>>());
+        assert_eq!(result.stop_reason, Some(StoppingCriterion::MaxGenerations(10)));
+    }
+
+    #[test]
+    fn run_stops_at_max_fitness_evaluations() {
+        let mut evolution = evolution(vec![StoppingCriterion::MaxFitnessEvaluations(50), StoppingCriterion::MaxGenerations(1000)]);
+        let mut previous_evaluations = 0;
+        let result = evolution.run(|progress, _, _| previous_evaluations = progress.fitness_evaluations);
+
+        assert!(result.fitness_evaluations >= 50);
+        assert!(previous_evaluations < 50);
+        assert_eq!(result.stop_reason, Some(StoppingCriterion::MaxFitnessEvaluations(50)));
+    }
+
+    #[test]
+    fn run_stops_at_target_fitness() {
+        let mut evolution = evolution(vec![StoppingCriterion::MaxGenerations(1000), StoppingCriterion::TargetFitness(f32::INFINITY)]);
+        let result = evolution.run(|_, _, _| {});
+
+        assert_eq!(result.generations, 0);
+        assert_eq!(result.stop_reason, Some(StoppingCriterion::TargetFitness(f32::INFINITY)));
+    }
+
+    #[test]
+    fn run_stops_at_stagnation() {
+        let mut evolution = evolution(vec![StoppingCriterion::Stagnation(5), StoppingCriterion::MaxGenerations(100_000)]);
+        let result = evolution.run(|_, _, _| {});
+
+        assert_eq!(result.generations_without_improvement, 5);
+        assert_eq!(result.stop_reason, Some(StoppingCriterion::Stagnation(5)));
+    }
+
+    #[test]
+    fn run_reports_first_met_criterion() {
+        let mut evolution = evolution(vec![StoppingCriterion::MaxGenerations(0), StoppingCriterion::TargetFitness(f32::INFINITY)]);
+        let result = evolution.run(|_, _, _| {});
+
+        assert_eq!(result.stop_reason, Some(StoppingCriterion::MaxGenerations(0)));
+    }
+
+    #[test]
+    fn stop_reason_names_criterion() {
+        assert_eq!(StoppingCriterion::MaxGenerations(10).to_string(), "max generations (10)");
+        assert_eq!(StoppingCriterion::MaxFitnessEvaluations(50).to_string(), "max fitness evaluations (50)");
+        assert_eq!(StoppingCriterion::WallClock(Duration::from_millis(1500)).to_string(), "wall clock (1.5s)");
+        assert_eq!(StoppingCriterion::Stagnation(5).to_string(), "stagnation (5 generations)");
+        assert_eq!(StoppingCriterion::TargetFitness(0.01).to_string(), "target fitness (0.01)");
+        assert_eq!(StoppingCriterion::Interrupted.to_string(), "interrupted");
+    }
+
+    #[test]
+    fn analysis_reads_stop_reason_of_iteration_file() {
+        let dir = std::env::temp_dir().join(format!("cgp_runner_stop_reason_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for (criterion, success) in [(StoppingCriterion::TargetFitness(f32::INFINITY), true),
+                                     (StoppingCriterion::MaxGenerations(3), false)] {
+            let path = dir.join("iteration.txt");
+            let mut evolution = evolution(vec![criterion]);
+            evolution.observers.push(Box::new(IterationFileWriter::create(&path, false).unwrap()));
+            let result = evolution.run(|_, _, _| {});
+            drop(evolution);
+
+            let content = std::fs::read_to_string(&path).unwrap();
+            let outcome = parse_iteration_file(0, &content, 1000).unwrap().unwrap();
+            assert_eq!(outcome.iterations, result.generations);
+            assert_eq!(outcome.fitness_evaluations, Some(result.fitness_evaluations));
+            assert_eq!(outcome.success, success);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,10 @@
 use std::fs::File;
 use std::path::Path;
-use std::rc::Rc;
+use std::sync::Arc;
+use crate::components::cgp_components::chromosome::Chromosome;
 use crate::components::cgp_components::chromosome_find_active_node_operators::ChromosomeActiveNodeTrait;
+use crate::components::cgp_components::objectives::{objective_values, pareto_front, Objective};
+use crate::components::cgp_components::program::Program;
 use crate::function_set::function_trait::FunctionTrait;
 use crate::utils::runner::{get_runner_parent, Runner};
 use std::io::Write;
@@ -9,16 +12,85 @@ use std::io::Write;
 pub fn active_nodes_writer<T>(runner: &mut Runner<T>,
                               save_path: &Path,
                               run_id: usize,
-                              active_node_func: Rc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
-                              function_set: Rc<Vec<Box<dyn FunctionTrait<T>>>>)
+                              active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                              function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>)
 {
     let save_file_active_node = format!("run_{}_active_node.txt", run_id);
     let mut output = File::create(save_path.join(save_file_active_node))
         .expect("cannot create file");
 
-    let mut parent = get_runner_parent(&runner);
+    let mut parent = get_runner_parent(runner);
 
-    active_node_func.execute(&mut parent, Rc::clone(&function_set));
+    active_node_func.execute(&mut parent, Arc::clone(&function_set));
 
     write!(output, "{:?}", parent.active_nodes).expect("cannot write");
-}
\ No newline at end of file
+}
+/// Saves the genome of the best chromosome; see `Chromosome::load`.
+pub fn best_chromosome_writer<T>(runner: &Runner<T>,
+                                 save_path: &Path,
+                                 run_id: usize,
+                                 function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>)
+{
+    let save_file_chromosome = format!("run_{}_best_chromosome.toml", run_id);
+    let parent = get_runner_parent(runner);
+
+    parent.save(&save_path.join(save_file_chromosome), &function_set).expect("cannot write");
+}
+
+/// Saves the program of the best chromosome, one instruction per line; see `Program`. With linear
+/// scaling, a line per output node gives its scaling, e.g. `scaled r7 = 0.5 + 2 * r7`.
+pub fn best_program_writer<T>(runner: &Runner<T>,
+                              save_path: &Path,
+                              run_id: usize,
+                              active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                              function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>)
+{
+    let save_file_program = format!("run_{}_program.txt", run_id);
+    let mut parent = get_runner_parent(runner);
+    active_node_func.execute(&mut parent, Arc::clone(&function_set));
+
+    std::fs::write(save_path.join(save_file_program), program_text(&parent, &function_set)).expect("cannot write");
+}
+
+/// Saves the Pareto front of the population over the fitness and `objectives`, see
+/// `objectives::pareto_front`; a line with the objective values of each chromosome is followed by
+/// its program, e.g. `# fitness 0.25, active_nodes 7, depth 4`.
+pub fn pareto_front_writer<T>(runner: &Runner<T>,
+                              save_path: &Path,
+                              run_id: usize,
+                              objectives: &[Objective],
+                              active_node_func: Arc<Box<dyn ChromosomeActiveNodeTrait<T>>>,
+                              function_set: Arc<Vec<Box<dyn FunctionTrait<T>>>>)
+{
+    let save_file_pareto_front = format!("run_{}_pareto_front.txt", run_id);
+    let mut population = runner.population.clone();
+    for chromosome in population.iter_mut() {
+        active_node_func.execute(chromosome, Arc::clone(&function_set));
+    }
+    let values: Vec<Vec<f32>> = population
+        .iter()
+        .zip(runner.fitness_vals.iter())
+        .map(|(chromosome, fitness)| objective_values(*fitness, chromosome, objectives, &function_set))
+        .collect();
+
+    let names: Vec<&str> = ["fitness"].into_iter().chain(objectives.iter().map(|objective| objective.name())).collect();
+    let mut text = String::new();
+    for id in pareto_front(&values) {
+        let header: Vec<String> = names.iter().zip(values[id].iter()).map(|(name, value)| format!("{} {}", name, value)).collect();
+        text += &format!("# {}\n{}\n", header.join(", "), program_text(&population[id], &function_set));
+    }
+    std::fs::write(save_path.join(save_file_pareto_front), text).expect("cannot write");
+}
+
+/// The program of `chromosome`, whose active nodes must be up to date. With linear scaling, a line
+/// per output node gives its scaling.
+fn program_text<T>(chromosome: &Chromosome, function_set: &[Box<dyn FunctionTrait<T>>]) -> String {
+    let program = Program::compile(chromosome, function_set).expect("cannot compile the chromosome");
+    let mut text = program.to_string();
+    if let Some(linear_scaling) = &chromosome.linear_scaling {
+        for (register, scaling) in program.outputs.iter().zip(linear_scaling.iter()) {
+            text += &format!("scaled r{} = {} + {} * r{}\n", register, scaling.intercept, scaling.slope, register);
+        }
+    }
+    return text;
+}
@@ -1,8 +1,10 @@
 use std::collections::HashSet;
+use num::Float;
 use rand::distributions::{Distribution, Uniform};
+use rand_chacha::ChaCha8Rng;
 
 /// Return all indices of `comp_value` in `vecs`.
-pub fn get_argmins_of_value(vecs: &Vec<f32>, comp_value: f32) -> Vec<usize>{
+pub fn get_argmins_of_value(vecs: &[f32], comp_value: f32) -> Vec<usize>{
     let mut argmins: Vec<usize> = vec![];
 
     vecs.iter()
@@ -21,7 +23,7 @@ pub fn get_argmins_of_value(vecs: &Vec<f32>, comp_value: f32) -> Vec<usize>{
     return argmins;
 }
 
-pub fn get_argmin(vecs: &Vec<f32>) -> usize {
+pub fn get_argmin(vecs: &[f32]) -> usize {
     vecs.iter()
         .enumerate()
         .min_by(|(_, a), (_, b)| a.total_cmp(b))
@@ -29,15 +31,15 @@ pub fn get_argmin(vecs: &Vec<f32>) -> usize {
         .unwrap()
 }
 
-pub fn get_min(vecs: &Vec<f32>) -> f32 {
-    *vecs.into_iter()
+pub fn get_min(vecs: &[f32]) -> f32 {
+    *vecs.iter()
         .min_by(|a, b| a.partial_cmp(b)
             .unwrap())
         .unwrap()
 }
 
 
-pub fn vect_difference(v1: &Vec<usize>, v2: &Vec<usize>) -> Vec<usize> {
+pub fn vect_difference(v1: &[usize], v2: &[usize]) -> Vec<usize> {
     let s1: HashSet<usize, nohash_hasher::BuildNoHashHasher<usize>> = v1.iter().cloned().collect();
     let s2: HashSet<usize, nohash_hasher::BuildNoHashHasher<usize>> = v2.iter().cloned().collect();
     (&s1 - &s2).iter().cloned().collect()
@@ -45,7 +47,7 @@ pub fn vect_difference(v1: &Vec<usize>, v2: &Vec<usize>) -> Vec<usize> {
 
 
 /// upper_range is exclusive
-pub fn gen_random_number_for_node(excluded: usize, upper_range: usize) -> usize {
+pub fn gen_random_number_for_node(excluded: usize, upper_range: usize, rng: &mut ChaCha8Rng) -> usize {
     // This is an important edge-case scenario.
     // In case upper_range <= 1: The node that is currently mutated has a position of 1
     // The random number generator will generate a random number in range [0, 1)
@@ -58,10 +60,9 @@ pub fn gen_random_number_for_node(excluded: usize, upper_range: usize) -> usize
     }
 
     let between = Uniform::from(0..upper_range);
-    let mut rng = rand::thread_rng();
 
     loop {
-        let rand_nbr: usize = between.sample(&mut rng);
+        let rand_nbr: usize = between.sample(rng);
         if rand_nbr != excluded {
             return rand_nbr;
         }
@@ -83,8 +84,8 @@ pub fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
         .collect()
 }
 
-pub fn get_float_iterator(start: f32, threshold: f32, step_size: f32) -> impl Iterator<Item=f32> {
-    let threshold: f32 = threshold + 1.;
+pub fn get_float_iterator<T: Float>(start: T, threshold: T, step_size: T) -> impl Iterator<Item=T> {
+    let threshold: T = threshold + T::one();
     std::iter::successors(Some(start), move |&prev| {
         let next = prev + step_size;
         (next < threshold).then_some(next)